@@ -0,0 +1,216 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Ecosystem a development artifact belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    Rust,
+    Node,
+    Python,
+    Maven,
+    Gradle,
+    DotNet,
+    Elixir,
+}
+
+impl fmt::Display for ProjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProjectKind::Rust => "Rust",
+            ProjectKind::Node => "Node",
+            ProjectKind::Python => "Python",
+            ProjectKind::Maven => "Maven",
+            ProjectKind::Gradle => "Gradle",
+            ProjectKind::DotNet => ".NET",
+            ProjectKind::Elixir => "Elixir",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Project files that identify the owner of a directory's siblings, checked in order.
+const PROJECT_MARKERS: &[(&str, ProjectKind)] = &[
+    ("Cargo.toml", ProjectKind::Rust),
+    ("package.json", ProjectKind::Node),
+    ("pyproject.toml", ProjectKind::Python),
+    ("requirements.txt", ProjectKind::Python),
+    ("setup.py", ProjectKind::Python),
+    ("pom.xml", ProjectKind::Maven),
+    ("build.gradle", ProjectKind::Gradle),
+    ("build.gradle.kts", ProjectKind::Gradle),
+    ("mix.exs", ProjectKind::Elixir),
+    ("*.csproj", ProjectKind::DotNet),
+    ("*.fsproj", ProjectKind::DotNet),
+    ("*.sln", ProjectKind::DotNet),
+];
+
+/// A directory name that holds regenerable build output.
+pub struct ArtifactDir {
+    pub name: &'static str,
+    /// Ecosystem implied by the name alone, if any
+    pub kind: Option<ProjectKind>,
+    /// Files of which at least one must sit beside the directory for it to
+    /// count as an artifact; `*.ext` matches any file with that extension.
+    /// Empty for names that are unambiguous on their own.
+    pub markers: &'static [&'static str],
+}
+
+const DOTNET_MARKERS: &[&str] = &["*.csproj", "*.fsproj", "*.vbproj", "*.sln"];
+const GRADLE_MARKERS: &[&str] = &["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"];
+const MIX_MARKERS: &[&str] = &["mix.exs"];
+// Maven also builds into `target`, but only Cargo's is cheap to regenerate
+const RUST_MARKERS: &[&str] = &["Cargo.toml"];
+
+/// Built-in artifact directories shared by discovery and the removal safety check.
+pub const ARTIFACT_DIRS: &[ArtifactDir] = &[
+    ArtifactDir { name: "node_modules", kind: Some(ProjectKind::Node), markers: &[] },
+    ArtifactDir { name: ".next", kind: Some(ProjectKind::Node), markers: &[] },
+    ArtifactDir { name: ".nuxt", kind: Some(ProjectKind::Node), markers: &[] },
+    ArtifactDir { name: ".venv", kind: Some(ProjectKind::Python), markers: &[] },
+    ArtifactDir { name: "venv", kind: Some(ProjectKind::Python), markers: &[] },
+    ArtifactDir { name: "__pycache__", kind: Some(ProjectKind::Python), markers: &[] },
+    ArtifactDir { name: ".tox", kind: Some(ProjectKind::Python), markers: &[] },
+    ArtifactDir { name: "target", kind: Some(ProjectKind::Rust), markers: RUST_MARKERS },
+    ArtifactDir { name: "build", kind: None, markers: &[] },
+    ArtifactDir { name: "dist", kind: None, markers: &[] },
+    ArtifactDir { name: ".gradle", kind: Some(ProjectKind::Gradle), markers: GRADLE_MARKERS },
+    ArtifactDir { name: "bin", kind: Some(ProjectKind::DotNet), markers: DOTNET_MARKERS },
+    ArtifactDir { name: "obj", kind: Some(ProjectKind::DotNet), markers: DOTNET_MARKERS },
+    ArtifactDir { name: "_build", kind: Some(ProjectKind::Elixir), markers: MIX_MARKERS },
+    ArtifactDir { name: "deps", kind: Some(ProjectKind::Elixir), markers: MIX_MARKERS },
+];
+
+static EXTRA_ARTIFACT_DIRS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Adds the config file's `artifact_dirs` to the built-in list. Only the
+/// first call has an effect.
+pub fn add_artifact_dirs(names: Vec<String>) {
+    let _ = EXTRA_ARTIFACT_DIRS.set(names);
+}
+
+/// Every artifact directory name: the built-in ones followed by the config's.
+pub fn artifact_names() -> Vec<&'static str> {
+    let extra = EXTRA_ARTIFACT_DIRS.get().map(Vec::as_slice).unwrap_or_default();
+    ARTIFACT_DIRS.iter().map(|artifact| artifact.name).chain(extra.iter().map(String::as_str)).collect()
+}
+
+/// The artifact names one dev-clean run considers, narrowed with
+/// `--only-artifact` and `--exclude-artifact`. The default allows all of them.
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactSelection {
+    only: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl ArtifactSelection {
+    /// Fails on names that are not artifact directories, which are most likely typos.
+    pub fn new(only: Vec<String>, exclude: Vec<String>) -> Result<ArtifactSelection> {
+        let known = artifact_names();
+        if let Some(unknown) = only.iter().chain(&exclude).find(|name| !known.contains(&name.as_str())) {
+            return Err(anyhow!("Unknown artifact directory '{}' (known: {})", unknown, known.join(", ")));
+        }
+        Ok(ArtifactSelection { only, exclude })
+    }
+
+    /// True when the artifact directory `path` belongs to this run.
+    pub fn allows(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        (self.only.is_empty() || self.only.iter().any(|only| only == name))
+            && !self.exclude.iter().any(|exclude| exclude == name)
+    }
+}
+
+/// True when `path` is named like an artifact directory and, for ambiguous
+/// names such as `bin`, sits next to a matching project file.
+pub fn is_artifact_dir(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+
+    let extra = EXTRA_ARTIFACT_DIRS.get().map(Vec::as_slice).unwrap_or_default();
+    if extra.iter().any(|extra| extra == name) {
+        return true;
+    }
+
+    ARTIFACT_DIRS
+        .iter()
+        .filter(|artifact| artifact.name == name)
+        .any(|artifact| artifact.markers.is_empty() || has_marker(path, artifact.markers))
+}
+
+/// Infers which kind of project owns the artifact directory `path`, from the
+/// project files beside it or, failing that, from the directory name.
+pub fn project_kind(path: &Path) -> Option<ProjectKind> {
+    let from_siblings = path.parent().and_then(|parent| {
+        PROJECT_MARKERS
+            .iter()
+            .find(|(marker, _)| sibling_matches(parent, marker))
+            .map(|(_, kind)| *kind)
+    });
+
+    from_siblings.or_else(|| {
+        let name = path.file_name()?.to_str()?;
+        ARTIFACT_DIRS.iter().find(|artifact| artifact.name == name)?.kind
+    })
+}
+
+fn has_marker(dir: &Path, markers: &[&str]) -> bool {
+    match dir.parent() {
+        Some(parent) => markers.iter().any(|marker| sibling_matches(parent, marker)),
+        None => false,
+    }
+}
+
+/// True when `parent` contains the file `marker`, or any file with the
+/// extension of a `*.ext` marker.
+fn sibling_matches(parent: &Path, marker: &str) -> bool {
+    match marker.strip_prefix("*.") {
+        Some(ext) => fs::read_dir(parent).into_iter().flatten().flatten().any(|entry| {
+            entry.path().extension().is_some_and(|e| e == ext) && entry.path().is_file()
+        }),
+        None => parent.join(marker).is_file(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ambiguous_names_need_a_marker() {
+        let root = tempfile::tempdir().unwrap();
+        let bin = root.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        assert!(!is_artifact_dir(&bin));
+
+        fs::write(root.path().join("App.csproj"), "<Project />").unwrap();
+        assert!(is_artifact_dir(&bin));
+        assert!(is_artifact_dir(&root.path().join("node_modules")));
+        assert!(!is_artifact_dir(&root.path().join("src")));
+    }
+
+    #[test]
+    fn test_target_requires_cargo_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let rust = root.path().join("rust-app");
+        let maven = root.path().join("java-app");
+        fs::create_dir_all(rust.join("target")).unwrap();
+        fs::create_dir_all(maven.join("target")).unwrap();
+        fs::write(rust.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(maven.join("pom.xml"), "<project />").unwrap();
+
+        assert!(is_artifact_dir(&rust.join("target")));
+        assert_eq!(project_kind(&rust.join("target")), Some(ProjectKind::Rust));
+        assert!(!is_artifact_dir(&maven.join("target")));
+        assert_eq!(project_kind(&maven.join("target")), Some(ProjectKind::Maven));
+
+        fs::create_dir_all(root.path().join("lib/__pycache__")).unwrap();
+        assert_eq!(project_kind(&root.path().join("lib/__pycache__")), Some(ProjectKind::Python));
+    }
+}
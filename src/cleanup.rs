@@ -1,3 +1,9 @@
 pub mod docker;
 pub mod temp;
-pub mod dev;
\ No newline at end of file
+pub mod dev;
+pub mod empty;
+pub mod removal;
+pub mod undo;
+pub mod report;
+pub mod trash_bin;
+pub mod caches;
\ No newline at end of file
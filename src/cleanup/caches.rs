@@ -0,0 +1,170 @@
+use anyhow::{anyhow, Result};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use crate::cleanup::removal::remove_items;
+use crate::cleanup::report::CleanupReport;
+use crate::cleanup::undo;
+use crate::discovery::{DirAnalyzer, FileItem};
+use crate::utils::{confirm_removal, format_size, format_size_as, interrupt_flag, note_skipped, parse_size, truncate_path};
+use crate::color::outln;
+
+/// A global cache kept by a language toolchain or package manager. Everything
+/// in it is downloaded or rebuilt again on demand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCache {
+    /// Name used with `--only-cache` and `--exclude-cache`
+    pub name: &'static str,
+    pub path: PathBuf,
+    /// Only cleaned when named with `--only-cache`
+    pub opt_in: bool,
+}
+
+/// Names of the caches `cache` knows about.
+///
+/// Maven's `~/.m2/repository` is opt-in: it also holds whatever
+/// `mvn install` put there, which cannot be downloaded again.
+pub const CACHE_NAMES: &[&str] = &["cargo", "npm", "pip", "go", "maven"];
+
+pub async fn cleanup(
+    dry_run: bool,
+    only_caches: Vec<String>,
+    exclude_caches: Vec<String>,
+    use_trash: bool,
+    assume_yes: bool,
+    confirm_threshold: String,
+    raw_bytes: bool,
+) -> Result<CleanupReport> {
+    let confirm_threshold = parse_size(&confirm_threshold)?;
+    let selected = select_caches(known_caches(), &only_caches, &exclude_caches)?;
+
+    info!("📦 Tool Cache Cleanup");
+    info!("=====================");
+    if selected.iter().any(|cache| cache.name == "maven") {
+        warn!("⚠️  The maven repository also holds artifacts installed with `mvn install`; those cannot be downloaded again");
+    }
+
+    let mut analyzer = DirAnalyzer::new();
+    analyzer.cancel = interrupt_flag();
+    let mut found = Vec::new();
+    for cache in selected.into_iter().filter(|cache| cache.path.is_dir()) {
+        let contents = analyzer.analyze_directory(&cache.path, 1).await?;
+        let item = FileItem {
+            size: contents.iter().map(|item| item.size).sum(),
+            item_count: Some(contents.iter().map(|item| item.item_count.unwrap_or(1)).sum()),
+            is_dir: true,
+            mtime: contents.iter().map(|item| item.mtime).max().unwrap_or(std::time::UNIX_EPOCH),
+            project_kind: None,
+            path: cache.path,
+        };
+        found.push((cache.name, item));
+    }
+    note_skipped(&analyzer.skipped);
+    let skipped = analyzer.skipped.count();
+
+    let total_size: u64 = found.iter().map(|(_, item)| item.size).sum();
+    if total_size == 0 {
+        outln!("\n✅ No tool caches found to clean up.");
+        return Ok(CleanupReport::nothing_found(skipped));
+    }
+
+    outln!("\n{:<8} {:<60} {:>15}", "Cache", "Path", "Size");
+    outln!("{:-<85}", "");
+    for (name, item) in &found {
+        outln!("{:<8} {:<60} {:>15}", name, truncate_path(&item.path, 57), format_size_as(item.size, raw_bytes));
+    }
+    outln!("\n📈 Total: {}", format_size_as(total_size, raw_bytes));
+
+    if dry_run {
+        outln!("\n[DRY RUN] Would remove {} tool caches ({})", found.len(), format_size_as(total_size, raw_bytes));
+        return Ok(CleanupReport::after_scan(skipped));
+    }
+
+    let prompt = format!("Remove {} tool caches ({})? They are downloaded again when needed", found.len(), format_size(total_size));
+    if !confirm_removal(prompt, total_size, confirm_threshold, assume_yes)? {
+        return Ok(CleanupReport::after_scan(skipped));
+    }
+
+    let caches: Vec<FileItem> = found.into_iter().map(|(_, item)| item).collect();
+    undo::record("cache", &caches, use_trash)?;
+    let mut report = remove_items(caches, use_trash).await?;
+    undo::record_removed("cache", &report, use_trash)?;
+    report.skipped_entries = skipped;
+    Ok(report)
+}
+
+/// Narrows `caches` to `only` (all but the opt-in ones when empty) minus
+/// `exclude`, rejecting unknown names.
+fn select_caches(caches: Vec<ToolCache>, only: &[String], exclude: &[String]) -> Result<Vec<ToolCache>> {
+    if let Some(unknown) = only.iter().chain(exclude).find(|name| !CACHE_NAMES.contains(&name.as_str())) {
+        return Err(anyhow!("Unknown cache '{}' (known: {})", unknown, CACHE_NAMES.join(", ")));
+    }
+    Ok(caches
+        .into_iter()
+        .filter(|cache| if only.is_empty() { !cache.opt_in } else { only.iter().any(|name| name == cache.name) })
+        .filter(|cache| !exclude.iter().any(|name| name == cache.name))
+        .collect())
+}
+
+/// Where the current user's tool caches live.
+fn known_caches() -> Vec<ToolCache> {
+    match (dirs::home_dir(), dirs::cache_dir()) {
+        (Some(home), Some(cache_dir)) => cache_locations(&home, &cache_dir, |var| std::env::var_os(var)),
+        _ => Vec::new(),
+    }
+}
+
+/// Tool cache locations below `home` and the platform cache directory
+/// `cache_dir`, honoring the environment variables (looked up with `env`)
+/// that the tools themselves use to move them.
+fn cache_locations(home: &Path, cache_dir: &Path, env: impl Fn(&str) -> Option<OsString>) -> Vec<ToolCache> {
+    let cargo_home = env("CARGO_HOME").map(PathBuf::from).unwrap_or_else(|| home.join(".cargo"));
+    let npm_cache = if cfg!(windows) { cache_dir.join("npm-cache") } else { home.join(".npm") };
+    let pip_cache = env("PIP_CACHE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| if cfg!(windows) { cache_dir.join("pip").join("Cache") } else { cache_dir.join("pip") });
+    let go_cache = env("GOCACHE").map(PathBuf::from).unwrap_or_else(|| cache_dir.join("go-build"));
+
+    vec![
+        ToolCache { name: "cargo", path: cargo_home.join("registry").join("cache"), opt_in: false },
+        ToolCache { name: "npm", path: npm_cache.join("_cacache"), opt_in: false },
+        ToolCache { name: "pip", path: pip_cache, opt_in: false },
+        ToolCache { name: "go", path: go_cache, opt_in: false },
+        ToolCache { name: "maven", path: home.join(".m2").join("repository"), opt_in: true },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_locations_and_env_overrides() {
+        let home = Path::new("/home/dev");
+        let cache_dir = Path::new("/home/dev/.cache");
+
+        let caches = cache_locations(home, cache_dir, |_| None);
+        let names: Vec<_> = caches.iter().map(|cache| cache.name).collect();
+        assert_eq!(names, CACHE_NAMES);
+        assert!(caches[0].path.ends_with(".cargo/registry/cache"));
+        assert_eq!(caches[3].path, cache_dir.join("go-build"));
+        #[cfg(unix)]
+        assert_eq!(caches[1].path, home.join(".npm/_cacache"));
+
+        let env = |var: &str| match var {
+            "CARGO_HOME" => Some(OsString::from("/opt/cargo")),
+            "GOCACHE" => Some(OsString::from("/tmp/gocache")),
+            _ => None,
+        };
+        let caches = cache_locations(home, cache_dir, env);
+        assert_eq!(caches[0].path, Path::new("/opt/cargo").join("registry").join("cache"));
+        assert_eq!(caches[3].path, PathBuf::from("/tmp/gocache"));
+
+        let selected = select_caches(caches.clone(), &[], &["pip".to_string()]).unwrap();
+        assert_eq!(selected.iter().map(|cache| cache.name).collect::<Vec<_>>(), ["cargo", "npm", "go"]);
+        let selected = select_caches(caches, &["maven".to_string()], &[]).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].path, home.join(".m2").join("repository"));
+        assert!(select_caches(Vec::new(), &["gradle".to_string()], &[]).is_err());
+    }
+}
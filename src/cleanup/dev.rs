@@ -1,100 +1,378 @@
-use anyhow::Result;
-use std::path::Path;
-use std::fs;
-use dialoguer::Confirm;
-use crate::discovery::{DevArtifactFinder, FileItem};
-use crate::utils::format_size;
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::process::Command;
+use tracing::{error, info, warn};
+use crate::artifacts::{is_artifact_dir, ArtifactSelection};
+use crate::discovery::{outermost, total_size, DevArtifactFinder, FileItem, WalkOptions};
+use crate::cleanup::removal::remove_path;
+use crate::cleanup::report::CleanupReport;
+use crate::cleanup::undo;
+use crate::safety;
+use crate::utils::{confirm_each, confirm_removal, describe_roots, format_size, format_size_exact, interrupt_flag, note_coverage, note_skipped, parse_size, scan_roots, truncate_path};
+use crate::color::{self, outln, outln_to};
 
-pub async fn cleanup(path: Option<String>, dry_run: bool) -> Result<()> {
-    let target_path = path.unwrap_or_else(|| ".".to_string());
-    let path = Path::new(&target_path);
+/// Artifacts below this size are hidden unless `--min-size` says otherwise.
+pub const DEFAULT_MIN_SIZE: &str = "1MB";
 
-    println!("🛠️  Development Artifacts Cleanup");
-    println!("=================================");
-    println!("Searching in: {}", path.display());
+/// How `dev-clean` picks artifacts and asks before removing them, as given
+/// on the command line.
+pub struct DevCleanOptions {
+    pub dry_run: bool,
+    pub respect_gitignore: bool,
+    pub min_size: String,
+    /// Also report artifacts nested inside other artifacts
+    pub deep: bool,
+    pub only_artifacts: Vec<String>,
+    pub exclude_artifacts: Vec<String>,
+    pub use_trash: bool,
+    pub elevate: bool,
+    /// Skip an artifact whose size changed by more than this percentage since the scan
+    pub max_size_change: f64,
+    /// Artifacts above this size need their own confirmation
+    pub max_artifact_size: Option<String>,
+    /// Skip the artifacts above `max_artifact_size` instead of asking about them
+    pub skip_oversize: bool,
+    pub assume_yes: bool,
+    pub interactive: bool,
+    pub summary_only: bool,
+    /// How many of the largest artifacts to show before the prompt
+    pub preview: usize,
+    pub confirm_threshold: String,
+}
 
-    let finder = DevArtifactFinder::new();
-    let artifacts = finder.find_artifacts(path).await?;
+pub async fn cleanup(paths: Vec<String>, listed: Option<Vec<PathBuf>>, walk: WalkOptions, options: DevCleanOptions) -> Result<CleanupReport> {
+    let DevCleanOptions {
+        dry_run, respect_gitignore, min_size, deep, only_artifacts, exclude_artifacts, use_trash, elevate, max_size_change,
+        max_artifact_size, skip_oversize, assume_yes, interactive, summary_only, preview, confirm_threshold,
+    } = options;
+    let confirm_threshold = parse_size(&confirm_threshold)?;
+    let max_artifact_size = max_artifact_size.map(|size| parse_size(&size)).transpose()?;
+    let roots = scan_roots(paths);
 
-    if artifacts.is_empty() {
-        println!("\n✅ No development artifacts found.");
-        return Ok(());
+    info!("🛠️  Development Artifacts Cleanup");
+    info!("=================================");
+
+    let mut finder = DevArtifactFinder::new();
+    finder.cancel = interrupt_flag();
+    finder.respect_gitignore = respect_gitignore;
+    finder.walk = walk;
+    finder.min_size = parse_size(&min_size)?;
+    finder.selection = ArtifactSelection::new(only_artifacts, exclude_artifacts)?;
+    finder.deep = deep;
+    let scan = match listed {
+        Some(listed) => {
+            info!("Checking {} listed paths", listed.len());
+            finder.check_listed(listed).await?
+        }
+        None => {
+            info!("Searching in: {}", describe_roots(&roots));
+            finder.scan_all(&roots).await?
+        }
+    };
+    note_skipped(&finder.skipped);
+    note_coverage(&finder.scanned, scan.artifacts.len());
+    let skipped = finder.skipped.count();
+    let mut artifacts = scan.artifacts;
+
+    if !scan.tracked.is_empty() {
+        info!("\n🔒 Skipping {} artifact directories tracked by git:", scan.tracked.len());
+        for tracked in &scan.tracked {
+            info!("   {} ({})", tracked.path.display(), format_size(tracked.size));
+        }
     }
 
-    let total_size: u64 = artifacts.iter().map(|a| a.size).sum();
-    let total_items: usize = artifacts.iter().map(|a| a.item_count.unwrap_or(0)).sum();
+    if !scan.rejected.is_empty() {
+        warn!("\n🚫 Rejected {} listed paths:", scan.rejected.len());
+        for (path, reason) in &scan.rejected {
+            warn!("   {}: {}", path.display(), reason);
+        }
+    }
 
-    println!("\n📊 Found development artifacts:");
-    println!("{:<60} {:>15} {:>10}", "Path", "Size", "Items");
-    println!("{:-<85}", "");
+    if !scan.small.is_empty() {
+        info!("\n🔎 Hiding {} artifacts smaller than {} (use --min-size 0 to show them)",
+              scan.small.len(), format_size_exact(finder.min_size));
+    }
 
-    for artifact in &artifacts {
-        println!(
-            "{:<60} {:>15} {:>10}",
-            if artifact.path.to_string_lossy().len() > 57 {
-                format!("...{}", &artifact.path.to_string_lossy()[artifact.path.to_string_lossy().len()-54..])
-            } else {
-                artifact.path.to_string_lossy().to_string()
-            },
-            format_size(artifact.size),
-            artifact.item_count.unwrap_or(0)
-        );
+    if artifacts.is_empty() {
+        outln!("\n✅ No development artifacts found.");
+        return Ok(CleanupReport::nothing_found(skipped));
     }
 
-    println!("\n📈 Summary:");
-    println!("   Total artifacts: {}", artifacts.len());
-    println!("   Total size: {}", format_size(total_size));
-    println!("   Total items: {}", total_items);
+    print_artifacts(&mut color::prose_writer(), &artifacts, summary_only)?;
+
+    let mut oversize = Vec::new();
+    // Oversize artifacts left alone, reported as skipped
+    let mut held_back = Vec::new();
+    if let Some(cap) = max_artifact_size {
+        oversize = split_oversize(&mut artifacts, cap);
+        if !oversize.is_empty() {
+            warn!("\n⚠️  {} artifacts are larger than {} and {}:", oversize.len(), format_size_exact(cap),
+                  if skip_oversize { "are skipped" } else { "each need their own confirmation" });
+            for artifact in &oversize {
+                warn!("   {} ({})", artifact.path.display(), format_size(artifact.size));
+            }
+        }
+        if skip_oversize {
+            held_back = hold_back(std::mem::take(&mut oversize), "--skip-oversize is set");
+        }
+    }
+    let total_size = total_size(&artifacts);
 
     if dry_run {
-        println!("\n[DRY RUN] Would remove {} development artifacts ({})", 
+        outln!("\n[DRY RUN] Would remove {} development artifacts ({})", 
                  artifacts.len(), format_size(total_size));
-        return Ok(());
+        if !oversize.is_empty() {
+            outln!("[DRY RUN] Would ask about {} oversize artifacts one by one", oversize.len());
+        }
+        return Ok(CleanupReport::after_scan(skipped));
+    }
+
+    if interactive {
+        artifacts = confirm_each(artifacts, assume_yes)?;
+    } else if !artifacts.is_empty() {
+        let prompt = if roots.len() > 1 {
+            format!("Remove {} development artifacts ({}) across {} paths?",
+                    artifacts.len(), format_size(total_size), roots.len())
+        } else {
+            format!("Remove {} development artifacts ({})?",
+                    artifacts.len(), format_size(total_size))
+        };
+        if !assume_yes {
+            print_preview(&mut color::prose_writer(), &artifacts, preview)?;
+        }
+        if !confirm_removal(prompt, total_size, confirm_threshold, assume_yes)? {
+            return Ok(CleanupReport::after_scan(skipped));
+        }
+    }
+    artifacts.extend(confirm_oversize(oversize, &mut held_back)?);
+    if artifacts.is_empty() {
+        return Ok(CleanupReport { skipped: held_back, ..CleanupReport::after_scan(skipped) });
+    }
+
+    undo::record("dev-clean", &artifacts, use_trash)?;
+    let mut report = remove_artifacts(artifacts, finder, use_trash, elevate, max_size_change).await?;
+    undo::record_removed("dev-clean", &report, use_trash)?;
+    report.skipped.extend(held_back);
+    report.skipped_entries = skipped;
+    Ok(report)
+}
+
+/// Moves the artifacts larger than `cap` out of `artifacts`, so they are not
+/// removed along with the rest.
+fn split_oversize(artifacts: &mut Vec<FileItem>, cap: u64) -> Vec<FileItem> {
+    let (oversize, rest) = artifacts.drain(..).partition(|artifact| artifact.size > cap);
+    *artifacts = rest;
+    oversize
+}
+
+/// Asks about each artifact above `--max-artifact-size` separately, even
+/// with `--yes`. Without a terminal to ask on they all go to `held_back`.
+fn confirm_oversize(oversize: Vec<FileItem>, held_back: &mut Vec<(PathBuf, String)>) -> Result<Vec<FileItem>> {
+    if oversize.is_empty() {
+        return Ok(oversize);
     }
+    if !io::stdin().is_terminal() {
+        warn!("   Skipping {} oversize artifacts: stdin is not a terminal to confirm them on", oversize.len());
+        held_back.extend(hold_back(oversize, "stdin is not a terminal to confirm it on"));
+        return Ok(Vec::new());
+    }
+    confirm_each(oversize, false)
+}
+
+/// Skip entries for oversize artifacts that are left alone because `why`.
+fn hold_back(oversize: Vec<FileItem>, why: &str) -> Vec<(PathBuf, String)> {
+    oversize
+        .into_iter()
+        .map(|artifact| (artifact.path, format!("it is larger than --max-artifact-size and {}", why)))
+        .collect()
+}
 
-    if Confirm::new()
-        .with_prompt(&format!("Remove {} development artifacts ({})?", 
-                              artifacts.len(), format_size(total_size)))
-        .interact()?
-    {
-        remove_artifacts(artifacts).await?;
-        println!("\n✅ Development artifacts cleanup completed!");
+/// Lists `artifacts` in a table, unless `summary_only`, followed by their totals.
+fn print_artifacts(out: &mut dyn Write, artifacts: &[FileItem], summary_only: bool) -> io::Result<()> {
+    // With --deep nested artifacts are already part of the outer one's totals
+    let total_size = total_size(artifacts);
+    let total_files: usize = outermost(artifacts).map(|a| a.item_count.unwrap_or(0)).sum();
+
+    if !summary_only {
+        outln_to!(out, "\n📊 Found development artifacts:")?;
+        outln_to!(out, "{:<60} {:<8} {:>15} {:>10}", "Path", "Kind", "Size", "Files")?;
+        outln_to!(out, "{:-<94}", "")?;
+
+        for artifact in artifacts {
+            outln_to!(
+                out,
+                "{:<60} {:<8} {:>15} {:>10}",
+                truncate_path(&artifact.path, 57),
+                artifact.project_kind.map(|kind| kind.to_string()).unwrap_or_else(|| "-".to_string()),
+                format_size(artifact.size),
+                artifact.item_count.unwrap_or(0)
+            )?;
+        }
     }
 
+    outln_to!(out, "\n📈 Summary:")?;
+    outln_to!(out, "   Total artifacts: {}", artifacts.len())?;
+    outln_to!(out, "   Total size: {}", format_size(total_size))?;
+    outln_to!(out, "   Total files: {}", total_files)
+}
+
+/// Lists the `count` largest of `artifacts` as a last check before the
+/// confirmation prompt.
+fn print_preview(out: &mut dyn Write, artifacts: &[FileItem], count: usize) -> io::Result<()> {
+    let largest = largest_artifacts(artifacts, count);
+    if largest.is_empty() {
+        return Ok(());
+    }
+    outln_to!(out, "\n🔝 Largest {}:", largest.len())?;
+    for artifact in largest {
+        outln_to!(out, "   {:<60} {:>15}", truncate_path(&artifact.path, 57), format_size(artifact.size))?;
+    }
     Ok(())
 }
 
-async fn remove_artifacts(artifacts: Vec<FileItem>) -> Result<()> {
-    let artifacts_clone = artifacts.clone();
-    
-    tokio::task::spawn_blocking(move || {
-        let mut removed_count = 0;
-        let mut removed_size = 0u64;
-
-        for artifact in artifacts_clone {
-            match remove_dir_all_safe(&artifact.path) {
-                Ok(_) => {
-                    removed_count += 1;
-                    removed_size += artifact.size;
-                    println!("   ✅ Removed: {}", artifact.path.display());
-                },
-                Err(e) => {
-                    eprintln!("   ❌ Failed to remove {}: {}", artifact.path.display(), e);
-                }
-            }
+/// The `count` largest of `artifacts`, largest first.
+fn largest_artifacts(artifacts: &[FileItem], count: usize) -> Vec<&FileItem> {
+    let mut largest: Vec<&FileItem> = artifacts.iter().collect();
+    largest.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    largest.truncate(count);
+    largest
+}
+
+/// The paths for `--from-file` or `--stdin`, or `None` to search as usual.
+pub fn listed_paths(from_file: Option<&Path>, stdin: bool) -> Result<Option<Vec<PathBuf>>> {
+    if let Some(file) = from_file {
+        let reader = File::open(file).with_context(|| format!("Failed to open {}", file.display()))?;
+        return Ok(Some(read_path_list(BufReader::new(reader))?));
+    }
+    if stdin {
+        return Ok(Some(read_path_list(io::stdin().lock())?));
+    }
+    Ok(None)
+}
+
+/// One path per line, as printed by `find` or `fd`; blank lines are ignored.
+fn read_path_list(reader: impl BufRead) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if !line.trim().is_empty() {
+            paths.push(PathBuf::from(line));
         }
+    }
+    Ok(paths)
+}
+
+async fn remove_artifacts(
+    artifacts: Vec<FileItem>,
+    finder: DevArtifactFinder,
+    use_trash: bool,
+    elevate: bool,
+    max_size_change: f64,
+) -> Result<CleanupReport> {
+    Ok(tokio::task::spawn_blocking(move || remove_each(&artifacts, &finder, use_trash, elevate, max_size_change)).await?)
+}
+
+/// Removes `artifacts` one by one, logging each. Failures are logged and
+/// skipped, and end up in the report alongside the successes. Each artifact
+/// is measured again first with `finder` and left alone when its size moved
+/// more than `max_size_change` percent since the scan, e.g. because a build
+/// is writing to it. Artifacts that look in use are skipped the same way.
+fn remove_each(artifacts: &[FileItem], finder: &DevArtifactFinder, use_trash: bool, elevate: bool, max_size_change: f64) -> CleanupReport {
+    let mut report = CleanupReport::default();
+    let mut removed: Vec<&Path> = Vec::new();
+    // In path order an outer artifact always comes before those nested in it
+    let mut artifacts: Vec<&FileItem> = artifacts.iter().collect();
+    artifacts.sort_by(|a, b| a.path.cmp(&b.path));
 
-        if removed_count > 0 {
-            println!("\n📊 Cleanup Summary:");
-            println!("   Removed {} artifacts", removed_count);
-            println!("   Freed up {}", format_size(removed_size));
+    for artifact in artifacts {
+        // With --deep an artifact can sit inside one that is already gone
+        if let Some(outer) = removed.iter().find(|outer| artifact.path.starts_with(outer)) {
+            info!("   ✅ Removed with {}: {}", outer.display(), artifact.path.display());
+            continue;
         }
+        let changed = finder.measure(&artifact.path).map(|size| size_changed(artifact, size, max_size_change));
+        match changed {
+            Ok(None) => {}
+            Ok(Some(reason)) => {
+                warn!("   ⚠️  Skipping {}: {}", artifact.path.display(), reason);
+                report.skipped.push((artifact.path.clone(), reason));
+                continue;
+            }
+            Err(e) => {
+                error!("   ❌ Failed to measure {}: {}", artifact.path.display(), e);
+                report.record(artifact, Err(e));
+                continue;
+            }
+        }
+        let result = retry_if_denied(remove_dir_all_safe(&artifact.path, use_trash), &artifact.path, elevate, remove_elevated);
+        match &result {
+            Ok(_) => info!("   ✅ Removed: {}", artifact.path.display()),
+            Err(e) => match e.downcast_ref::<safety::InUse>() {
+                Some(in_use) => warn!("   ⚠️  Skipping {}: {}", artifact.path.display(), in_use.reason()),
+                None => error!("   ❌ Failed to remove {}: {}", artifact.path.display(), e),
+            },
+        }
+        if result.is_ok() {
+            removed.push(&artifact.path);
+        }
+        report.record(artifact, result);
+    }
+
+    report
+}
+
+/// Why `artifact` should be left alone when its size is now `current`: set
+/// when that is more than `max_change` percent away from the scanned size.
+fn size_changed(artifact: &FileItem, current: u64, max_change: f64) -> Option<String> {
+    let scanned = artifact.size as f64;
+    if (current as f64 - scanned).abs() <= scanned * max_change / 100.0 {
+        return None;
+    }
+    Some(format!("it changed since scanning ({} then, {} now)", format_size(artifact.size), format_size(current)))
+}
+
+/// With `--elevate`, hands a removal that failed for lack of permissions to
+/// `elevated`; any other result is returned unchanged.
+fn retry_if_denied(result: Result<()>, path: &Path, elevate: bool, elevated: impl FnOnce(&Path) -> Result<()>) -> Result<()> {
+    match result {
+        Err(e) if elevate && is_permission_denied(&e) => elevated(path),
+        result => result,
+    }
+}
+
+fn is_permission_denied(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<io::Error>())
+        .any(|cause| cause.kind() == io::ErrorKind::PermissionDenied)
+}
+
+/// Retries a removal with `sudo rm -rf`, after asking separately. The prompt
+/// is never skipped, not even with `--yes`.
+#[cfg(unix)]
+fn remove_elevated(path: &Path) -> Result<()> {
+    safety::check_delete(path)?;
+    let prompt = format!("Permission denied. Permanently delete {} with `sudo rm -rf`?", path.display());
+    if !crate::utils::confirm(prompt, false)? {
+        bail!("Permission denied");
+    }
+    let status = Command::new("sudo").args(["rm", "-rf", "--"]).arg(path).status().context("Failed to run sudo")?;
+    if !status.success() {
+        bail!("sudo rm -rf failed ({})", status);
+    }
+    Ok(())
+}
 
-        Ok(())
-    }).await?
+#[cfg(not(unix))]
+fn remove_elevated(path: &Path) -> Result<()> {
+    bail!("Permission denied; run safe-clean as administrator to remove {}", path.display())
 }
 
-fn remove_dir_all_safe(path: &Path) -> Result<()> {
+pub fn remove_dir_all_safe(path: &Path, use_trash: bool) -> Result<()> {
     // Additional safety checks before removal
     if !path.exists() {
         return Ok(());
@@ -105,16 +383,8 @@ fn remove_dir_all_safe(path: &Path) -> Result<()> {
     }
 
     // Check if it's actually a development artifact directory
-    let dir_name = path.file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("");
-
-    let safe_dirs = [
-        "node_modules", ".venv", "venv", "__pycache__", 
-        ".tox", "target", "build", "dist"
-    ];
-
-    if !safe_dirs.contains(&dir_name) {
+    if !is_artifact_dir(path) {
+        let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
         return Err(anyhow::anyhow!("Directory name '{}' is not in the safe removal list", dir_name));
     }
 
@@ -123,6 +393,238 @@ fn remove_dir_all_safe(path: &Path) -> Result<()> {
         return Err(anyhow::anyhow!("Refusing to remove directory at filesystem root"));
     }
 
-    fs::remove_dir_all(path)?;
-    Ok(())
-}
\ No newline at end of file
+    safety::check_in_use(path)?;
+
+    remove_path(path, use_trash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::capture;
+    use std::fs;
+    use tracing::Level;
+
+    fn artifact(path: &Path) -> FileItem {
+        fs::create_dir_all(path).unwrap();
+        safety::backdate(path);
+        FileItem::test(path, 0, Some(0))
+    }
+
+    #[test]
+    fn test_quiet_suppresses_removed_lines() {
+        let root = tempfile::tempdir().unwrap();
+        let first = artifact(&root.path().join("a/node_modules"));
+        let second = artifact(&root.path().join("b/node_modules"));
+
+        let (removed, output) = capture::with_level(Level::ERROR, || remove_each(&[first], &DevArtifactFinder::new(), false, false, 0.0));
+        assert_eq!(removed.removed.len(), 1);
+        assert!(!output.contains("Removed"));
+
+        let (removed, output) = capture::with_level(Level::INFO, || remove_each(&[second], &DevArtifactFinder::new(), false, false, 0.0));
+        assert_eq!(removed.removed.len(), 1);
+        assert!(output.contains("Removed"));
+    }
+
+    #[tokio::test]
+    async fn test_min_size_hides_small_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = root.path().join("app/__pycache__");
+        fs::create_dir_all(&cache).unwrap();
+        fs::write(cache.join("mod.pyc"), vec![0u8; 500_000]).unwrap();
+
+        let mut finder = DevArtifactFinder::new();
+        finder.allocated = false;
+        finder.min_size = parse_size(DEFAULT_MIN_SIZE).unwrap();
+        let scan = finder.scan(root.path()).await.unwrap();
+        assert!(scan.artifacts.is_empty());
+        assert_eq!(scan.small.len(), 1);
+
+        finder.min_size = parse_size("0").unwrap();
+        let scan = finder.scan(root.path()).await.unwrap();
+        assert_eq!(scan.artifacts.len(), 1);
+        assert_eq!(scan.artifacts[0].path, cache);
+    }
+
+    #[test]
+    fn test_report_records_success_and_failure() {
+        let root = tempfile::tempdir().unwrap();
+        let removable = artifact(&root.path().join("app/node_modules"));
+        let protected = artifact(&root.path().join("app/src"));
+
+        let report = remove_each(&[removable.clone(), protected.clone()], &DevArtifactFinder::new(), false, false, 0.0);
+        assert_eq!(report.removed, vec![(removable.path.clone(), 0)]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, protected.path);
+        assert!(!removable.path.exists());
+        assert!(protected.path.exists());
+    }
+
+    #[test]
+    fn test_nested_artifact_goes_with_outer_one() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = artifact(&root.path().join("web/node_modules/pkg/node_modules"));
+        let outer = artifact(&root.path().join("web/node_modules"));
+
+        let report = remove_each(&[outer.clone(), nested.clone()], &DevArtifactFinder::new(), false, false, 0.0);
+        assert_eq!(report.removed, vec![(outer.path.clone(), 0)]);
+        assert!(report.failed.is_empty());
+        assert!(!outer.path.exists());
+
+        // Same when the nested one comes first, e.g. after an oversize outer one was confirmed last
+        let nested = artifact(&nested.path);
+        let outer = artifact(&outer.path);
+        let report = remove_each(&[nested, outer.clone()], &DevArtifactFinder::new(), false, false, 0.0);
+        assert_eq!(report.removed, vec![(outer.path.clone(), 0)]);
+        assert!(report.failed.is_empty() && report.skipped.is_empty());
+        assert!(!outer.path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_listed_paths_checked_without_search() {
+        let root = tempfile::tempdir().unwrap();
+        let node = artifact(&root.path().join("web/node_modules")).path;
+        let venv = artifact(&root.path().join("api/.venv")).path;
+        let src = artifact(&root.path().join("api/src")).path;
+        let list = root.path().join("candidates.txt");
+        fs::write(&list, format!("{}\n\n{}\r\n{}\n", node.display(), src.display(), venv.display())).unwrap();
+
+        let listed = listed_paths(Some(&list), false).unwrap().unwrap();
+        assert_eq!(listed, [node.clone(), src.clone(), venv.clone()]);
+
+        let scan = DevArtifactFinder::new().check_listed(listed).await.unwrap();
+        let mut candidates: Vec<_> = scan.artifacts.iter().map(|a| a.path.clone()).collect();
+        candidates.sort();
+        assert_eq!(candidates, [venv, node]);
+        assert_eq!(scan.rejected.len(), 1);
+        assert_eq!(scan.rejected[0].0, src);
+        assert!(listed_paths(None, false).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_permission_denied_elevates_only_with_flag() {
+        let path = Path::new("/srv/app/node_modules");
+        let denied = || Err(anyhow::Error::from(io::Error::from(io::ErrorKind::PermissionDenied)).context("Failed to remove"));
+        let mut elevated = Vec::new();
+        let mut elevate = |p: &Path| {
+            elevated.push(p.to_owned());
+            Ok(())
+        };
+
+        assert!(retry_if_denied(denied(), path, false, &mut elevate).is_err());
+        assert!(retry_if_denied(denied(), path, true, &mut elevate).is_ok());
+        assert_eq!(elevated, [path]);
+
+        let missing = Err(io::Error::from(io::ErrorKind::NotFound).into());
+        assert!(retry_if_denied(missing, path, true, |_| panic!("only permission errors elevate")).is_err());
+    }
+
+    #[test]
+    fn test_size_change_since_scan_skips_removal() {
+        let root = tempfile::tempdir().unwrap();
+        let mut growing = artifact(&root.path().join("web/node_modules"));
+        let mut steady = artifact(&root.path().join("api/node_modules"));
+        fs::write(growing.path.join("bundle.js"), vec![0u8; 1500]).unwrap();
+        fs::write(steady.path.join("index.js"), vec![0u8; 1050]).unwrap();
+        safety::backdate(root.path());
+        // Both measured 1000 bytes at scan time
+        growing.size = 1000;
+        steady.size = 1000;
+
+        let mut finder = DevArtifactFinder::new();
+        finder.allocated = false;
+        let report = remove_each(&[growing.clone(), steady.clone()], &finder, false, false, 10.0);
+        assert_eq!(report.removed, vec![(steady.path.clone(), 1000)]);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, growing.path);
+        assert!(report.skipped[0].1.contains("changed since scanning"));
+        assert!(growing.path.exists());
+        assert!(!steady.path.exists());
+    }
+
+    #[test]
+    fn test_artifact_in_use_is_skipped() {
+        let root = tempfile::tempdir().unwrap();
+        let idle = artifact(&root.path().join("api/node_modules"));
+        let mut busy = artifact(&root.path().join("web/node_modules"));
+        fs::write(busy.path.join("bundle.js"), b"data").unwrap();
+        busy.size = 4;
+
+        let mut finder = DevArtifactFinder::new();
+        finder.allocated = false;
+        let report = remove_each(&[idle.clone(), busy.clone()], &finder, false, false, 0.0);
+        assert_eq!(report.removed, vec![(idle.path.clone(), 0)]);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, busy.path);
+        assert!(report.skipped[0].1.contains("in use"));
+        assert!(busy.path.exists());
+    }
+
+    #[test]
+    fn test_summary_only_leaves_out_paths() {
+        // The --deep one inside web/node_modules is part of its totals already
+        let artifacts = [
+            FileItem::test("/work/web/node_modules", 3000, Some(12)),
+            FileItem::test("/work/api/.venv", 1000, Some(3)),
+            FileItem::test("/work/web/node_modules/pkg/node_modules", 500, Some(2)),
+        ];
+        assert_eq!(total_size(&artifacts), 4000);
+
+        let mut out = Vec::new();
+        print_artifacts(&mut out, &artifacts, true).unwrap();
+        let summary = String::from_utf8(out).unwrap();
+        assert!(!summary.contains("node_modules") && !summary.contains(".venv"));
+        assert!(summary.contains("Total artifacts: 3"));
+        assert!(summary.contains("Total files: 15"));
+
+        let mut out = Vec::new();
+        print_artifacts(&mut out, &artifacts, false).unwrap();
+        let full = String::from_utf8(out).unwrap();
+        assert!(full.contains("/work/web/node_modules") && full.contains("/work/api/.venv"));
+        assert!(full.ends_with(&summary));
+    }
+
+    #[test]
+    fn test_preview_picks_largest_artifacts() {
+        let artifacts = [
+            FileItem::test("/work/a/target", 200, Some(1)),
+            FileItem::test("/work/b/node_modules", 900, Some(1)),
+            FileItem::test("/work/c/.venv", 50, Some(1)),
+            FileItem::test("/work/d/target", 700, Some(1)),
+            FileItem::test("/work/e/dist", 300, Some(1)),
+        ];
+
+        let largest: Vec<_> = largest_artifacts(&artifacts, 3).iter().map(|a| a.size).collect();
+        assert_eq!(largest, [900, 700, 300]);
+        assert_eq!(largest_artifacts(&artifacts, 10).len(), 5);
+        assert!(largest_artifacts(&artifacts, 0).is_empty());
+
+        let mut out = Vec::new();
+        print_preview(&mut out, &artifacts, 2).unwrap();
+        let preview = String::from_utf8(out).unwrap();
+        assert!(preview.contains("Largest 2:"));
+        assert!(preview.find("/work/b/node_modules").unwrap() < preview.find("/work/d/target").unwrap());
+        assert!(!preview.contains("/work/e/dist"));
+    }
+
+    #[test]
+    fn test_oversize_artifacts_split_from_removal() {
+        let mut artifacts = vec![
+            FileItem::test("/work/web/node_modules", 800, Some(1)),
+            FileItem::test("/data/survey/build", 500_000, Some(1)),
+            FileItem::test("/work/api/target", 1000, Some(1)),
+            FileItem::test("/data/scans/dist", 1001, Some(1)),
+        ];
+
+        let oversize = split_oversize(&mut artifacts, 1000);
+        let paths = |items: &[FileItem]| items.iter().map(|a| a.path.to_str().unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(paths(&oversize), ["/data/survey/build", "/data/scans/dist"]);
+        assert_eq!(paths(&artifacts), ["/work/web/node_modules", "/work/api/target"]);
+
+        let held_back = hold_back(oversize, "--skip-oversize is set");
+        assert_eq!(held_back[0].0, PathBuf::from("/data/survey/build"));
+        assert_eq!(held_back[1].1, "it is larger than --max-artifact-size and --skip-oversize is set");
+    }
+}
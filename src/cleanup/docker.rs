@@ -1,28 +1,78 @@
 use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use tokio::process::Command as AsyncCommand;
-use dialoguer::Confirm;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info};
+use chrono::{DateTime, Local};
+use crate::cleanup::report::CleanupReport;
+use crate::utils::{confirm, format_size_as, parse_size};
+use crate::color::outln;
 
-pub async fn cleanup(dry_run: bool) -> Result<()> {
-    println!("🐳 Docker Safe Cleanup");
-    println!("======================");
+pub async fn cleanup(
+    dry_run: bool,
+    prune_all: bool,
+    until: Option<Duration>,
+    assume_yes: bool,
+) -> Result<CleanupReport> {
+    info!("🐳 Docker Safe Cleanup");
+    info!("======================");
 
     if !is_docker_available().await? {
         return Err(anyhow!("Docker is not available or not running"));
     }
 
+    let mut report = CleanupReport::default();
+    // Each check returns whether it found anything to clean
+    let mut found = false;
+
     // Check for unused containers
-    cleanup_containers(dry_run).await?;
+    found |= cleanup_containers(dry_run, until, assume_yes, &mut report).await?;
     
     // Check for unused images
-    cleanup_images(dry_run).await?;
+    found |= cleanup_images(dry_run, assume_yes, &mut report).await?;
     
     // Check for unused volumes
-    cleanup_volumes(dry_run).await?;
+    found |= cleanup_volumes(dry_run, assume_yes, &mut report).await?;
     
     // Check for unused networks
-    cleanup_networks(dry_run).await?;
+    found |= cleanup_networks(dry_run, assume_yes, &mut report).await?;
+
+    // Check for reclaimable build cache
+    found |= cleanup_build_cache(dry_run, prune_all, assume_yes, &mut report).await?;
+
+    report.nothing_found = !found;
+    Ok(report)
+}
+
+/// Prints how much each kind of Docker resource takes up and how much of it
+/// could be reclaimed, without prompting or removing anything.
+pub async fn analyze(raw_bytes: bool) -> Result<()> {
+    if !is_docker_available().await? {
+        return Err(anyhow!("Docker is not available or not running"));
+    }
+
+    let rows: Vec<DiskUsageRow> = docker_list(&["system", "df"]).await?;
+    let usage = disk_usage(&rows);
+
+    outln!("🐳 Docker disk usage");
+    outln!("   {:<14} {:>7} {:>7} {:>12} {:>12}", "Type", "Total", "Active", "Size", "Reclaimable");
+    for entry in &usage {
+        outln!(
+            "   {:<14} {:>7} {:>7} {:>12} {:>12}",
+            entry.kind,
+            entry.total,
+            entry.active,
+            format_size_as(entry.size, raw_bytes),
+            format_size_as(entry.reclaimable, raw_bytes)
+        );
+    }
+    outln!(
+        "   Total reclaimable: {}",
+        format_size_as(usage.iter().map(|entry| entry.reclaimable).sum(), raw_bytes)
+    );
 
-    println!("\n✅ Docker cleanup completed!");
     Ok(())
 }
 
@@ -38,170 +88,583 @@ async fn is_docker_available() -> Result<bool> {
     }
 }
 
-async fn cleanup_containers(dry_run: bool) -> Result<()> {
-    println!("\n📦 Checking for stopped containers...");
-    
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerInfo {
+    #[serde(rename = "ID")]
+    id: String,
+    image: String,
+    status: String,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ImageInfo {
+    #[serde(rename = "ID")]
+    id: String,
+    repository: String,
+    tag: String,
+    size: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct VolumeInfo {
+    name: String,
+    driver: String,
+    scope: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct NetworkInfo {
+    #[serde(rename = "ID")]
+    id: String,
+    name: String,
+    driver: String,
+}
+
+/// One row of `docker system df`, e.g. `Reclaimable: "1.2GB (40%)"`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DiskUsageRow {
+    #[serde(rename = "Type")]
+    kind: String,
+    total_count: String,
+    active: String,
+    size: String,
+    reclaimable: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct DiskUsage {
+    kind: String,
+    total: String,
+    active: String,
+    size: u64,
+    reclaimable: u64,
+}
+
+/// Converts docker's human-readable sizes into bytes; anything unparseable counts as 0.
+fn disk_usage(rows: &[DiskUsageRow]) -> Vec<DiskUsage> {
+    let bytes = |size: &str| size.split_whitespace().next().and_then(|size| parse_size(size).ok()).unwrap_or(0);
+    rows.iter()
+        .map(|row| DiskUsage {
+            kind: row.kind.clone(),
+            total: row.total_count.clone(),
+            active: row.active.clone(),
+            size: bytes(&row.size),
+            reclaimable: bytes(&row.reclaimable),
+        })
+        .collect()
+}
+
+/// Runs a docker listing command with `--format {{json .}}` and parses one object per line.
+async fn docker_list<T: DeserializeOwned>(args: &[&str]) -> Result<Vec<T>> {
     let output = AsyncCommand::new("docker")
-        .args(["ps", "-a", "--filter", "status=exited", "--format", "table {{.ID}}\\t{{.Image}}\\t{{.Status}}"])
+        .args(args)
+        .args(["--format", "{{json .}}"])
         .output()
         .await?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
+    if !output.status.success() {
+        return Err(anyhow!(
+            "docker {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    parse_json_lines(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_json_lines<T: DeserializeOwned>(output: &str) -> Result<Vec<T>> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| anyhow!("Unexpected docker output {:?}: {}", line, e)))
+        .collect()
+}
+
+async fn cleanup_containers(dry_run: bool, until: Option<Duration>, assume_yes: bool, report: &mut CleanupReport) -> Result<bool> {
+    info!("\n📦 Checking for stopped containers...");
     
-    if lines.len() <= 1 {
-        println!("   No stopped containers found.");
-        return Ok(());
+    let mut containers: Vec<ContainerInfo> = docker_list(&["ps", "-a", "--filter", "status=exited"]).await?;
+
+    let cutoff = until
+        .map(|age| {
+            chrono::Duration::from_std(age)
+                .ok()
+                .and_then(|age| Local::now().checked_sub_signed(age))
+                .ok_or_else(|| anyhow!("--until {}s reaches back further than a date can go", age.as_secs()))
+        })
+        .transpose()?;
+    if let Some(cutoff) = cutoff {
+        info!("   Only considering containers created before {}", cutoff.format("%Y-%m-%d %H:%M"));
+        containers.retain(|container| created_before(&container.created_at, cutoff));
+    }
+    
+    if containers.is_empty() {
+        info!("   No stopped containers found.");
+        return Ok(false);
     }
 
-    println!("{}", stdout);
+    outln!("   {:<14} {:<40} Status", "ID", "Image");
+    for container in &containers {
+        outln!("   {:<14} {:<40} {}", container.id, container.image, container.status);
+    }
     
     if dry_run {
-        println!("   [DRY RUN] Would remove {} stopped containers", lines.len() - 1);
-        return Ok(());
+        match cutoff {
+            Some(cutoff) => outln!(
+                "   [DRY RUN] Would remove {} stopped containers created before {}",
+                containers.len(),
+                cutoff.format("%Y-%m-%d %H:%M")
+            ),
+            None => outln!("   [DRY RUN] Would remove {} stopped containers", containers.len()),
+        }
+        return Ok(true);
     }
 
-    if Confirm::new()
-        .with_prompt(&format!("Remove {} stopped containers?", lines.len() - 1))
-        .interact()?
-    {
+    if confirm(format!("Remove {} stopped containers?", containers.len()), assume_yes)? {
         let result = AsyncCommand::new("docker")
-            .args(["container", "prune", "-f"])
+            .args(container_prune_args(until))
             .output()
             .await?;
 
         if result.status.success() {
-            println!("   ✅ Stopped containers removed successfully");
+            info!("   ✅ Stopped containers removed successfully");
+            let output = String::from_utf8_lossy(&result.stdout);
+            record_prune(report, &output, deleted_entries(&output));
         } else {
-            println!("   ❌ Failed to remove containers: {}", String::from_utf8_lossy(&result.stderr));
+            let reason = String::from_utf8_lossy(&result.stderr);
+            error!("   ❌ Failed to remove containers: {}", reason);
+            report.failed.push((PathBuf::from("containers"), reason.trim().to_string()));
         }
     }
 
-    Ok(())
+    Ok(true)
+}
+
+/// Arguments for `docker container prune`, limited to containers older than `until`.
+fn container_prune_args(until: Option<Duration>) -> Vec<String> {
+    let mut args = vec!["container".to_string(), "prune".to_string(), "-f".to_string()];
+    if let Some(age) = until {
+        args.push("--filter".to_string());
+        args.push(format!("until={}s", age.as_secs()));
+    }
+    args
+}
+
+/// Compares docker's `CreatedAt` (e.g. `2024-01-02 10:00:00 +0000 UTC`) against `cutoff`.
+/// Unparseable timestamps are kept, since docker applies the real filter on prune.
+fn created_before(created_at: &str, cutoff: DateTime<Local>) -> bool {
+    let timestamp: Vec<&str> = created_at.split_whitespace().take(3).collect();
+    DateTime::parse_from_str(&timestamp.join(" "), "%Y-%m-%d %H:%M:%S %z")
+        .map_or(true, |created| created < cutoff)
 }
 
-async fn cleanup_images(dry_run: bool) -> Result<()> {
-    println!("\n🖼️  Checking for unused images...");
+async fn cleanup_images(dry_run: bool, assume_yes: bool, report: &mut CleanupReport) -> Result<bool> {
+    info!("\n🖼️  Checking for unused images...");
     
-    let output = AsyncCommand::new("docker")
-        .args(["images", "--filter", "dangling=true", "--format", "table {{.ID}}\\t{{.Repository}}\\t{{.Tag}}\\t{{.Size}}"])
-        .output()
-        .await?;
+    let images: Vec<ImageInfo> = docker_list(&["images", "--filter", "dangling=true"]).await?;
+    let containers: Vec<ContainerInfo> = docker_list(&["ps", "-a"]).await?;
+    let (images, protected) = unused_images(images, &containers);
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
+    if protected > 0 {
+        info!("   🔒 Keeping {} images still used by containers (running or stopped)", protected);
+    }
+    if images.is_empty() {
+        info!("   No dangling images found.");
+        return Ok(false);
+    }
+
+    outln!("   {:<14} {:<40} {:>10}", "ID", "Repository:Tag", "Size");
+    for image in &images {
+        outln!("   {:<14} {:<40} {:>10}", image.id, format!("{}:{}", image.repository, image.tag), image.size);
+    }
+    
+    if dry_run {
+        outln!("   [DRY RUN] Would remove {} dangling images", images.len());
+        return Ok(true);
+    }
+
+    if confirm(format!("Remove {} dangling images?", images.len()), assume_yes)? {
+        // Removed one ID at a time rather than with `image prune`, so exactly
+        // the images listed above go and each one's outcome is known; without
+        // -f docker also refuses any a container uses
+        for image in &images {
+            let result = AsyncCommand::new("docker")
+                .args(["rmi", &image.id])
+                .output()
+                .await?;
+
+            if result.status.success() {
+                info!("   ✅ Removed image {}", image.id);
+                record_image(report, image);
+            } else {
+                let reason = String::from_utf8_lossy(&result.stderr);
+                error!("   ❌ Failed to remove image {}: {}", image.id, reason.trim());
+                report.failed.push((PathBuf::from(&image.id), reason.trim().to_string()));
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+/// Records a removed image under its ID with the size `docker images` listed
+/// for it, so `bytes_freed` grows by exactly what `removed` gains.
+fn record_image(report: &mut CleanupReport, image: &ImageInfo) {
+    let size = parse_size(&image.size).unwrap_or(0);
+    report.removed.push((PathBuf::from(&image.id), size));
+    report.bytes_freed += size;
+}
+
+/// Splits off the images a container (running or stopped) was created from,
+/// returning the rest and how many were held back.
+fn unused_images(images: Vec<ImageInfo>, containers: &[ContainerInfo]) -> (Vec<ImageInfo>, usize) {
+    let total = images.len();
+    let unused: Vec<ImageInfo> = images
+        .into_iter()
+        .filter(|image| !containers.iter().any(|container| uses_image(&container.image, image)))
+        .collect();
+    let protected = total - unused.len();
+    (unused, protected)
+}
+
+/// Whether a container's `Image` column refers to `image`. Docker shows the
+/// name the container was started with, or the image ID once that name has
+/// moved on to a newer image (which is how images become dangling).
+fn uses_image(reference: &str, image: &ImageInfo) -> bool {
+    let reference = reference.trim_start_matches("sha256:");
+    let id = image.id.trim_start_matches("sha256:");
+    let id_match = reference.len() >= 12 && id.len() >= 12 && (id.starts_with(reference) || reference.starts_with(id));
+    let name_match = image.repository != "<none>"
+        && (reference == format!("{}:{}", image.repository, image.tag)
+            || (image.tag == "latest" && reference == image.repository));
+    id_match || name_match
+}
+
+async fn cleanup_volumes(dry_run: bool, assume_yes: bool, report: &mut CleanupReport) -> Result<bool> {
+    info!("\n💾 Checking for unused volumes...");
     
-    if lines.len() <= 1 {
-        println!("   No dangling images found.");
-        return Ok(());
+    let volumes: Vec<VolumeInfo> = docker_list(&["volume", "ls", "--filter", "dangling=true"]).await?;
+    
+    if volumes.is_empty() {
+        info!("   No unused volumes found.");
+        return Ok(false);
     }
 
-    println!("{}", stdout);
+    outln!("   {:<40} {:<10} Scope", "Name", "Driver");
+    for volume in &volumes {
+        outln!("   {:<40} {:<10} {}", volume.name, volume.driver, volume.scope);
+    }
     
     if dry_run {
-        println!("   [DRY RUN] Would remove {} dangling images", lines.len() - 1);
-        return Ok(());
+        outln!("   [DRY RUN] Would remove {} unused volumes", volumes.len());
+        return Ok(true);
     }
 
-    if Confirm::new()
-        .with_prompt(&format!("Remove {} dangling images?", lines.len() - 1))
-        .interact()?
-    {
+    if confirm(format!("Remove {} unused volumes?", volumes.len()), assume_yes)? {
         let result = AsyncCommand::new("docker")
-            .args(["image", "prune", "-f"])
+            .args(["volume", "prune", "-f"])
             .output()
             .await?;
 
         if result.status.success() {
-            println!("   ✅ Dangling images removed successfully");
+            info!("   ✅ Unused volumes removed successfully");
+            let output = String::from_utf8_lossy(&result.stdout);
+            record_prune(report, &output, deleted_entries(&output));
         } else {
-            println!("   ❌ Failed to remove images: {}", String::from_utf8_lossy(&result.stderr));
+            let reason = String::from_utf8_lossy(&result.stderr);
+            error!("   ❌ Failed to remove volumes: {}", reason);
+            report.failed.push((PathBuf::from("volumes"), reason.trim().to_string()));
         }
     }
 
-    Ok(())
+    Ok(true)
 }
 
-async fn cleanup_volumes(dry_run: bool) -> Result<()> {
-    println!("\n💾 Checking for unused volumes...");
+async fn cleanup_networks(dry_run: bool, assume_yes: bool, report: &mut CleanupReport) -> Result<bool> {
+    info!("\n🌐 Checking for unused networks...");
     
-    let output = AsyncCommand::new("docker")
-        .args(["volume", "ls", "--filter", "dangling=true", "--format", "table {{.Name}}\\t{{.Driver}}\\t{{.Scope}}"])
-        .output()
-        .await?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
+    let networks: Vec<NetworkInfo> = docker_list(&["network", "ls", "--filter", "dangling=true"]).await?;
     
-    if lines.len() <= 1 {
-        println!("   No unused volumes found.");
-        return Ok(());
+    if networks.is_empty() {
+        info!("   No unused networks found.");
+        return Ok(false);
     }
 
-    println!("{}", stdout);
+    outln!("   {:<14} {:<40} Driver", "ID", "Name");
+    for network in &networks {
+        outln!("   {:<14} {:<40} {}", network.id, network.name, network.driver);
+    }
     
     if dry_run {
-        println!("   [DRY RUN] Would remove {} unused volumes", lines.len() - 1);
-        return Ok(());
+        outln!("   [DRY RUN] Would remove {} unused networks", networks.len());
+        return Ok(true);
     }
 
-    if Confirm::new()
-        .with_prompt(&format!("Remove {} unused volumes?", lines.len() - 1))
-        .interact()?
-    {
+    if confirm(format!("Remove {} unused networks?", networks.len()), assume_yes)? {
         let result = AsyncCommand::new("docker")
-            .args(["volume", "prune", "-f"])
+            .args(["network", "prune", "-f"])
             .output()
             .await?;
 
         if result.status.success() {
-            println!("   ✅ Unused volumes removed successfully");
+            info!("   ✅ Unused networks removed successfully");
+            let output = String::from_utf8_lossy(&result.stdout);
+            record_prune(report, &output, deleted_entries(&output));
         } else {
-            println!("   ❌ Failed to remove volumes: {}", String::from_utf8_lossy(&result.stderr));
+            let reason = String::from_utf8_lossy(&result.stderr);
+            error!("   ❌ Failed to remove networks: {}", reason);
+            report.failed.push((PathBuf::from("networks"), reason.trim().to_string()));
         }
     }
 
-    Ok(())
+    Ok(true)
 }
 
-async fn cleanup_networks(dry_run: bool) -> Result<()> {
-    println!("\n🌐 Checking for unused networks...");
-    
+async fn cleanup_build_cache(dry_run: bool, prune_all: bool, assume_yes: bool, report: &mut CleanupReport) -> Result<bool> {
+    info!("\n🏗️  Checking build cache...");
+
     let output = AsyncCommand::new("docker")
-        .args(["network", "ls", "--filter", "dangling=true", "--format", "table {{.ID}}\\t{{.Name}}\\t{{.Driver}}"])
+        .args(["system", "df", "--format", "{{.Type}}\t{{.Reclaimable}}"])
         .output()
         .await?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
-    
-    if lines.len() <= 1 {
-        println!("   No unused networks found.");
-        return Ok(());
-    }
+    let reclaimable = match parse_build_cache_reclaimable(&stdout) {
+        Some(size) => size,
+        None => {
+            info!("   No reclaimable build cache found.");
+            return Ok(false);
+        }
+    };
+
+    outln!("   Reclaimable build cache: {}", reclaimable);
 
-    println!("{}", stdout);
-    
     if dry_run {
-        println!("   [DRY RUN] Would remove {} unused networks", lines.len() - 1);
-        return Ok(());
+        outln!("   [DRY RUN] Would prune build cache ({})", reclaimable);
+        return Ok(true);
     }
 
-    if Confirm::new()
-        .with_prompt(&format!("Remove {} unused networks?", lines.len() - 1))
-        .interact()?
-    {
+    if confirm(format!("Prune {} of build cache?", reclaimable), assume_yes)? {
         let result = AsyncCommand::new("docker")
-            .args(["network", "prune", "-f"])
+            .args(builder_prune_args(prune_all))
             .output()
             .await?;
 
         if result.status.success() {
-            println!("   ✅ Unused networks removed successfully");
+            info!("   ✅ Build cache pruned successfully");
+            record_prune(report, &String::from_utf8_lossy(&result.stdout), ["build cache".to_string()]);
         } else {
-            println!("   ❌ Failed to remove networks: {}", String::from_utf8_lossy(&result.stderr));
+            let reason = String::from_utf8_lossy(&result.stderr);
+            error!("   ❌ Failed to prune build cache: {}", reason);
+            report.failed.push((PathBuf::from("build cache"), reason.trim().to_string()));
         }
     }
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(true)
+}
+
+/// Records a successful prune: `removed` are the IDs or names of what went
+/// away. Docker only reports the total it reclaimed, so that goes into
+/// `bytes_freed` while the individual entries have size 0.
+fn record_prune(report: &mut CleanupReport, output: &str, removed: impl IntoIterator<Item = String>) {
+    report.removed.extend(removed.into_iter().map(|id| (PathBuf::from(id), 0)));
+    report.bytes_freed += reclaimed_space(output);
+}
+
+/// The IDs or names listed under the "Deleted Containers:" (or Volumes,
+/// Networks) heading of a prune's output. Prune can leave out some of what
+/// was listed beforehand, e.g. a container started again in between, so
+/// only these count as removed.
+fn deleted_entries(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("Deleted "))
+        .skip(1)
+        .map(str::trim)
+        .take_while(|line| !line.is_empty() && !line.starts_with("Total"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parses the "Total reclaimed space: 1.2GB" (or, for the builder, "Total: 1.2GB")
+/// line that docker's prune commands print last.
+fn reclaimed_space(output: &str) -> u64 {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Total reclaimed space:").or_else(|| line.strip_prefix("Total:")))
+        .filter_map(|size| parse_size(size).ok())
+        .sum()
+}
+
+/// Arguments for `docker builder prune`; `-a` also drops cache that is still referenced.
+fn builder_prune_args(prune_all: bool) -> Vec<&'static str> {
+    let mut args = vec!["builder", "prune", "-f"];
+    if prune_all {
+        args.push("-a");
+    }
+    args
+}
+
+/// Picks the reclaimable size of the "Build Cache" row out of `docker system df` output,
+/// ignoring it when nothing can be freed.
+fn parse_build_cache_reclaimable(output: &str) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .find(|(kind, _)| kind.trim() == "Build Cache")
+        .map(|(_, reclaimable)| reclaimable.trim().to_string())
+        .filter(|size| !size.is_empty() && !size.starts_with("0B"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_prune_args() {
+        assert_eq!(builder_prune_args(false), ["builder", "prune", "-f"]);
+        assert_eq!(builder_prune_args(true), ["builder", "prune", "-f", "-a"]);
+    }
+
+    #[test]
+    fn test_deleted_entries() {
+        let output = "Deleted Containers:\n4f2a9c1b7d3e\n8e1d0a6c5b2f\n\nTotal reclaimed space: 12kB\n";
+        assert_eq!(deleted_entries(output), ["4f2a9c1b7d3e", "8e1d0a6c5b2f"]);
+        assert_eq!(deleted_entries("Deleted Volumes:\npgdata\nTotal reclaimed space: 1MB\n"), ["pgdata"]);
+        assert!(deleted_entries("Total reclaimed space: 0B\n").is_empty());
+        assert!(deleted_entries("").is_empty());
+
+        let mut report = CleanupReport::default();
+        record_prune(&mut report, output, deleted_entries(output));
+        assert_eq!(report.removed, [(PathBuf::from("4f2a9c1b7d3e"), 0), (PathBuf::from("8e1d0a6c5b2f"), 0)]);
+        assert_eq!(report.bytes_freed, 12_000);
+    }
+
+    #[test]
+    fn test_reclaimed_space() {
+        let output = "Deleted Images:\ndeleted: sha256:3f2a9c1b\n\nTotal reclaimed space: 1.5MB\n";
+        assert_eq!(reclaimed_space(output), 1_500_000);
+        assert_eq!(reclaimed_space("ID\tRECLAIMABLE\nabc\ttrue\nTotal:\t2.1kB\n"), 2100);
+        assert_eq!(reclaimed_space("Total reclaimed space: 0B\n"), 0);
+        assert_eq!(reclaimed_space(""), 0);
+    }
+
+    #[test]
+    fn test_disk_usage_from_system_df() {
+        let output = r#"{"Active":"3","Reclaimable":"1.2GB (40%)","Size":"3GB","TotalCount":"7","Type":"Images"}
+{"Active":"1","Reclaimable":"0B (0%)","Size":"12.5kB","TotalCount":"1","Type":"Containers"}
+{"Active":"0","Reclaimable":"250MB (100%)","Size":"250MB","TotalCount":"2","Type":"Local Volumes"}
+{"Active":"0","Reclaimable":"21.4GB","Size":"21.4GB","TotalCount":"96","Type":"Build Cache"}
+"#;
+        let usage = disk_usage(&parse_json_lines(output).unwrap());
+        assert_eq!(usage.len(), 4);
+        assert_eq!(
+            usage[0],
+            DiskUsage {
+                kind: "Images".to_string(),
+                total: "7".to_string(),
+                active: "3".to_string(),
+                size: 3_000_000_000,
+                reclaimable: 1_200_000_000,
+            }
+        );
+        assert_eq!(usage[1].reclaimable, 0);
+        assert_eq!(usage[2].reclaimable, 250_000_000);
+        assert_eq!(usage[3].kind, "Build Cache");
+        assert_eq!(usage.iter().map(|entry| entry.reclaimable).sum::<u64>(), 22_850_000_000);
+    }
+
+    #[test]
+    fn test_parse_build_cache_reclaimable() {
+        let output = "Images\t1.2GB (40%)\nContainers\t0B (0%)\nLocal Volumes\t0B\nBuild Cache\t21.4GB\n";
+        assert_eq!(parse_build_cache_reclaimable(output), Some("21.4GB".to_string()));
+        assert_eq!(parse_build_cache_reclaimable("Build Cache\t0B\n"), None);
+        assert_eq!(parse_build_cache_reclaimable(""), None);
+    }
+
+    #[test]
+    fn test_parse_container_json_lines() {
+        let output = r#"{"Command":"\"nginx -g\"","CreatedAt":"2024-01-02 10:00:00 +0000 UTC","ID":"3f2a9c1b7d4e","Image":"nginx:latest","Names":"web","Status":"Exited (0) 2 hours ago"}
+{"Command":"\"bash\"","CreatedAt":"2024-01-01 09:00:00 +0000 UTC","ID":"a81b00c4e5f6","Image":"ubuntu:22.04","Names":"shell","Status":"Exited (137) 3 days ago"}
+
+"#;
+        let containers: Vec<ContainerInfo> = parse_json_lines(output).unwrap();
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].id, "3f2a9c1b7d4e");
+        assert_eq!(containers[1].id, "a81b00c4e5f6");
+        assert_eq!(containers[1].image, "ubuntu:22.04");
+
+        assert!(parse_json_lines::<ContainerInfo>("").unwrap().is_empty());
+        assert!(parse_json_lines::<ContainerInfo>("CONTAINER ID   IMAGE").is_err());
+    }
+
+    #[test]
+    fn test_container_prune_until_filter() {
+        assert_eq!(container_prune_args(None), ["container", "prune", "-f"]);
+        assert_eq!(
+            container_prune_args(Some(Duration::from_secs(24 * 60 * 60))),
+            ["container", "prune", "-f", "--filter", "until=86400s"]
+        );
+
+        let cutoff = DateTime::parse_from_rfc3339("2024-01-02T00:00:00Z").unwrap().with_timezone(&Local);
+        assert!(created_before("2024-01-01 09:00:00 +0000 UTC", cutoff));
+        assert!(!created_before("2024-01-02 10:00:00 +0000 UTC", cutoff));
+    }
+
+    #[test]
+    fn test_images_used_by_containers_are_kept() {
+        let images: Vec<ImageInfo> = parse_json_lines(
+            r#"{"Containers":"N/A","CreatedAt":"2024-01-01 09:00:00 +0000 UTC","ID":"5d0da3dc9764","Repository":"<none>","Size":"72.8MB","Tag":"<none>"}
+{"Containers":"N/A","CreatedAt":"2024-01-02 09:00:00 +0000 UTC","ID":"b2c6e1f0a3d4","Repository":"<none>","Size":"1.2GB","Tag":"<none>"}
+{"Containers":"N/A","CreatedAt":"2024-01-03 09:00:00 +0000 UTC","ID":"e4a7c9d1f2b3","Repository":"<none>","Size":"300MB","Tag":"<none>"}
+"#,
+        )
+        .unwrap();
+        let containers: Vec<ContainerInfo> = parse_json_lines(
+            r#"{"CreatedAt":"2024-01-02 10:00:00 +0000 UTC","ID":"3f2a9c1b7d4e","Image":"b2c6e1f0a3d4","Names":"old-api","Status":"Exited (0) 3 weeks ago"}
+{"CreatedAt":"2024-01-04 10:00:00 +0000 UTC","ID":"a81b00c4e5f6","Image":"nginx:latest","Names":"web","Status":"Up 2 hours"}
+{"CreatedAt":"2024-01-04 11:00:00 +0000 UTC","ID":"c0ffee123456","Image":"sha256:e4a7c9d1f2b3a5c6d7e8f9","Names":"worker","Status":"Up 1 hour"}
+"#,
+        )
+        .unwrap();
+
+        let (unused, protected) = unused_images(images, &containers);
+        assert_eq!(protected, 2);
+        assert_eq!(unused.iter().map(|image| image.id.as_str()).collect::<Vec<_>>(), ["5d0da3dc9764"]);
+
+        let tagged = ImageInfo {
+            id: "9a8b7c6d5e4f".to_string(),
+            repository: "nginx".to_string(),
+            tag: "latest".to_string(),
+            size: "180MB".to_string(),
+        };
+        assert!(uses_image("nginx", &tagged));
+        assert!(uses_image("nginx:latest", &tagged));
+        assert!(!uses_image("nginx:1.25", &tagged));
+        assert!(!uses_image("9a8b", &tagged));
+    }
+
+    #[test]
+    fn test_removed_images_recorded_with_their_size() {
+        let images: Vec<ImageInfo> = parse_json_lines(
+            r#"{"ID":"5d0da3dc9764","Repository":"<none>","Size":"72.8MB","Tag":"<none>"}
+{"ID":"b2c6e1f0a3d4","Repository":"<none>","Size":"1.2GB","Tag":"<none>"}
+"#,
+        )
+        .unwrap();
+
+        let mut report = CleanupReport::default();
+        for image in &images {
+            record_image(&mut report, image);
+        }
+        assert_eq!(report.removed, [(PathBuf::from("5d0da3dc9764"), 72_800_000), (PathBuf::from("b2c6e1f0a3d4"), 1_200_000_000)]);
+        assert_eq!(report.bytes_freed, report.removed.iter().map(|(_, size)| size).sum::<u64>());
+    }
+}
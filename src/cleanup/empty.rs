@@ -0,0 +1,77 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use crate::cleanup::removal::remove_path;
+use crate::cleanup::report::CleanupReport;
+use crate::discovery::{EmptyDirFinder, FileItem, WalkOptions};
+use crate::utils::{confirm, interrupt_flag, note_skipped};
+use crate::color::{eoutln, outln};
+
+pub async fn cleanup(
+    path: Option<String>,
+    dry_run: bool,
+    walk: WalkOptions,
+    use_trash: bool,
+    assume_yes: bool,
+) -> Result<CleanupReport> {
+    let target_path = path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&target_path);
+
+    outln!("📂 Empty Directories Cleanup");
+    outln!("============================");
+    outln!("Searching in: {}", path.display());
+
+    let mut finder = EmptyDirFinder::new();
+    finder.cancel = interrupt_flag();
+    finder.walk = walk;
+    let empty_dirs = finder.find_empty_dirs(path).await?;
+    note_skipped(&finder.skipped);
+    let skipped = finder.skipped.count();
+
+    if empty_dirs.is_empty() {
+        outln!("\n✅ No empty directories found.");
+        return Ok(CleanupReport::nothing_found(skipped));
+    }
+
+    outln!("\n📊 Found {} empty directories:", empty_dirs.len());
+    for dir in &empty_dirs {
+        outln!("   {}", dir.path.display());
+    }
+
+    if dry_run {
+        outln!("\n[DRY RUN] Would remove {} empty directories", empty_dirs.len());
+        return Ok(CleanupReport::after_scan(skipped));
+    }
+
+    if !confirm(format!("Remove {} empty directories?", empty_dirs.len()), assume_yes)? {
+        return Ok(CleanupReport::after_scan(skipped));
+    }
+
+    let mut report = remove_empty_dirs(empty_dirs, use_trash).await?;
+    report.skipped_entries = skipped;
+    Ok(report)
+}
+
+/// Removes directories in the given (deepest-first) order. Without `--trash`
+/// `fs::remove_dir` is used, which refuses to delete a directory that gained
+/// content since the scan.
+async fn remove_empty_dirs(dirs: Vec<FileItem>, use_trash: bool) -> Result<CleanupReport> {
+    tokio::task::spawn_blocking(move || {
+        let mut report = CleanupReport::default();
+
+        for dir in dirs {
+            let result = if use_trash {
+                remove_path(&dir.path, true)
+            } else {
+                fs::remove_dir(&dir.path).map_err(Into::into)
+            };
+
+            if let Err(e) = &result {
+                eoutln!("   ❌ Failed to remove {}: {}", dir.path.display(), e);
+            }
+            report.record(&dir, result);
+        }
+
+        Ok(report)
+    }).await?
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use std::sync::Once;
+use tracing::{error, info};
+use crate::cleanup::report::CleanupReport;
+use crate::discovery::FileItem;
+use crate::safety;
+use crate::color::eoutln;
+
+static FALLBACK_WARNING: Once = Once::new();
+
+/// Removes a file or directory tree, moving it to the system trash when
+/// `use_trash` is set. On platforms without a trash the item is deleted
+/// permanently and a warning is printed once. Protected paths are never removed.
+pub fn remove_path(path: &Path, use_trash: bool) -> Result<()> {
+    safety::check_delete(path)?;
+
+    if use_trash {
+        match move_to_trash(path) {
+            Some(result) => return result,
+            None => FALLBACK_WARNING.call_once(|| {
+                eoutln!("⚠️  Trash is not supported on this platform; deleting permanently");
+            }),
+        }
+    }
+
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Removes each of `items` with [`remove_path`] on the blocking pool, logging
+/// every outcome, and reports what was freed and what failed.
+pub async fn remove_items(items: Vec<FileItem>, use_trash: bool) -> Result<CleanupReport> {
+    let report = tokio::task::spawn_blocking(move || {
+        let mut report = CleanupReport::default();
+        for item in &items {
+            let result = remove_path(&item.path, use_trash);
+            match &result {
+                Ok(_) => info!("   ✅ Removed: {}", item.path.display()),
+                Err(e) => error!("   ❌ Failed to remove {}: {}", item.path.display(), e),
+            }
+            report.record(item, result);
+        }
+        report
+    })
+    .await?;
+    Ok(report)
+}
+
+/// Moves `path` to the system trash, or returns `None` when the platform has none.
+#[cfg(any(
+    windows,
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+fn move_to_trash(path: &Path) -> Option<Result<()>> {
+    Some(trash::delete(path).map_err(Into::into))
+}
+
+#[cfg(not(any(
+    windows,
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+fn move_to_trash(_path: &Path) -> Option<Result<()>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permanent_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("gone.tmp");
+        fs::write(&file, b"data").unwrap();
+
+        remove_path(&file, false).unwrap();
+        assert!(!file.exists());
+    }
+
+    #[cfg(any(windows, target_os = "linux"))]
+    #[test]
+    fn test_trash_keeps_item_recoverable() {
+        let dir = tempfile::tempdir().unwrap();
+        let name = format!("safe-clean-trash-test-{}.tmp", std::process::id());
+        let file = dir.path().join(&name);
+        fs::write(&file, b"data").unwrap();
+
+        remove_path(&file, true).unwrap();
+        assert!(!file.exists());
+
+        let trashed: Vec<_> = trash::os_limited::list()
+            .unwrap()
+            .into_iter()
+            .filter(|item| item.name == name.as_str())
+            .collect();
+        assert_eq!(trashed.len(), 1);
+        trash::os_limited::purge_all(trashed).unwrap();
+    }
+}
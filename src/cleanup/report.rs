@@ -0,0 +1,156 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use crate::discovery::FileItem;
+use crate::safety::InUse;
+
+/// Outcome of a cleanup run: what was removed (with its size), what could
+/// not be, and how much space was reclaimed. Empty when nothing was attempted
+/// (dry run, declined prompt or nothing found).
+#[derive(Debug, Default)]
+pub struct CleanupReport {
+    pub removed: Vec<(PathBuf, u64)>,
+    pub failed: Vec<(PathBuf, String)>,
+    /// Items deliberately left alone right before removal, with the reason.
+    /// Unlike `failed` these don't make the run a partial failure
+    pub skipped: Vec<(PathBuf, String)>,
+    pub bytes_freed: u64,
+    /// Entries the scan could not read, so the cleanup may have missed some
+    pub skipped_entries: usize,
+    /// The scan turned up nothing to clean, as opposed to a dry run or a
+    /// declined prompt
+    pub nothing_found: bool,
+}
+
+/// How a cleanup command reports what it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable summary
+    Text,
+    /// A single JSON object on stdout; progress and prompts go to stderr
+    Json,
+}
+
+/// Serialized form of a [`CleanupReport`] for `--format json`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonReport {
+    /// Cleanup that ran, e.g. "dev" or "temp"
+    pub kind: String,
+    pub removed: Vec<JsonRemoved>,
+    pub failed: Vec<JsonFailed>,
+    pub skipped: Vec<JsonSkipped>,
+    pub bytes_freed: u64,
+    pub skipped_entries: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRemoved {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonFailed {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonSkipped {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl CleanupReport {
+    /// A report with nothing removed yet, for a scan that skipped
+    /// `skipped_entries` unreadable entries.
+    pub fn after_scan(skipped_entries: usize) -> CleanupReport {
+        CleanupReport { skipped_entries, ..CleanupReport::default() }
+    }
+
+    /// A report for a scan that found nothing to clean.
+    pub fn nothing_found(skipped_entries: usize) -> CleanupReport {
+        CleanupReport { nothing_found: true, ..CleanupReport::after_scan(skipped_entries) }
+    }
+
+    /// Records the result of removing `item`. An item refused because it
+    /// looks in use counts as skipped, not failed.
+    pub fn record(&mut self, item: &FileItem, result: anyhow::Result<()>) {
+        match result {
+            Ok(_) => {
+                self.removed.push((item.path.clone(), item.size));
+                self.bytes_freed += item.size;
+            }
+            Err(e) => match e.downcast_ref::<InUse>() {
+                Some(in_use) => self.skipped.push((item.path.clone(), in_use.reason())),
+                None => self.failed.push((item.path.clone(), e.to_string())),
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.failed.is_empty() && self.skipped.is_empty()
+    }
+
+    /// Writes the report as one JSON object followed by a newline.
+    pub fn write_json<W: Write>(&self, mut writer: W, kind: &str) -> Result<()> {
+        let json = JsonReport {
+            kind: kind.to_string(),
+            removed: self
+                .removed
+                .iter()
+                .map(|(path, size)| JsonRemoved { path: path.clone(), size: *size })
+                .collect(),
+            failed: self
+                .failed
+                .iter()
+                .map(|(path, error)| JsonFailed { path: path.clone(), error: error.clone() })
+                .collect(),
+            skipped: self
+                .skipped
+                .iter()
+                .map(|(path, reason)| JsonSkipped { path: path.clone(), reason: reason.clone() })
+                .collect(),
+            bytes_freed: self.bytes_freed,
+            skipped_entries: self.skipped_entries,
+        };
+        serde_json::to_writer_pretty(&mut writer, &json)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleanup::removal::remove_path;
+    use std::fs;
+
+    #[test]
+    fn test_json_bytes_freed_matches_removed_sizes() {
+        let root = tempfile::tempdir().unwrap();
+        let mut report = CleanupReport::default();
+        for (name, size) in [("a.tmp", 100), ("b.tmp", 2500)] {
+            let path = root.path().join(name);
+            fs::write(&path, vec![0u8; size]).unwrap();
+            let item = FileItem::test(&path, size as u64, None);
+            report.record(&item, remove_path(&path, false));
+        }
+        let missing = root.path().join("missing.tmp");
+        report.failed.push((missing.clone(), "not found".to_string()));
+        report.skipped_entries = 3;
+
+        let mut out = Vec::new();
+        report.write_json(&mut out, "temp").unwrap();
+        let json: JsonReport = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(json.kind, "temp");
+        assert_eq!(json.removed.len(), 2);
+        assert_eq!(json.bytes_freed, 2600);
+        assert_eq!(json.bytes_freed, json.removed.iter().map(|item| item.size).sum::<u64>());
+        assert_eq!(json.failed[0].path, missing);
+        assert_eq!(json.skipped_entries, 3);
+    }
+}
@@ -1,53 +1,246 @@
 use anyhow::Result;
-use std::path::Path;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::fs;
+use tracing::{info, warn, Level};
+use chrono::{DateTime, Local};
+use std::time::{Duration, SystemTime};
+use globset::GlobSet;
 use walkdir::WalkDir;
-use dialoguer::Confirm;
-use crate::utils::format_size;
+use crate::cleanup::removal::remove_path;
+use crate::cleanup::report::CleanupReport;
+use crate::cleanup::undo;
+use crate::safety;
+use crate::discovery::{build_glob_set, FileItem};
+use crate::utils::{confirm_each, confirm_removal, format_age, format_size, parse_size};
+use crate::color::outln;
 
-pub async fn cleanup(dry_run: bool) -> Result<()> {
-    println!("🗂️  System Temporary Files Cleanup");
-    println!("==================================");
+#[allow(clippy::too_many_arguments)]
+pub async fn cleanup(
+    dry_run: bool,
+    min_age: Option<Duration>,
+    protect_newer_than: Option<SystemTime>,
+    max_depth: usize,
+    browser: bool,
+    system: bool,
+    patterns: Vec<String>,
+    only_patterns: bool,
+    use_trash: bool,
+    assume_yes: bool,
+    interactive: bool,
+    summary_only: bool,
+    confirm_threshold: String,
+) -> Result<CleanupReport> {
+    let confirm_threshold = parse_size(&confirm_threshold)?;
+    info!("🗂️  System Temporary Files Cleanup");
+    info!("==================================");
 
-    let temp_dirs = get_temp_directories();
+    let names = TempNames::new(&patterns, only_patterns)?;
+    let mut temp_dirs: Vec<TempDir> = get_temp_directories()
+        .into_iter()
+        .map(|path| TempDir { path, max_depth, every_file: false, names: names.clone() })
+        .collect();
+    if browser {
+        // Browsers recreate their caches, so everything inside them can go
+        temp_dirs.extend(
+            browser_cache_dirs()
+                .into_iter()
+                .map(|path| TempDir { path, max_depth: usize::MAX, every_file: true, names: names.clone() }),
+        );
+    }
+    if system {
+        if !cfg!(windows) {
+            warn!("⚠️  --system only applies on Windows; ignoring it");
+        }
+        // Windows rebuilds these itself, so everything inside them can go
+        let (readable, denied) = readable_dirs(system_temp_dirs());
+        for dir in &denied {
+            warn!("⚠️  Skipping {}: access denied (run as administrator to include it)", dir.display());
+        }
+        temp_dirs.extend(
+            readable
+                .into_iter()
+                .map(|path| TempDir { path, max_depth: usize::MAX, every_file: true, names: names.clone() }),
+        );
+    }
+
+    if let Some(min_age) = min_age {
+        info!("Only considering files last modified more than {}", format_age(min_age));
+    }
     let mut total_size = 0u64;
     let mut total_files = 0usize;
 
+    let progress = ProgressBar::new_spinner();
+    progress.set_style(ProgressStyle::with_template("{spinner} {msg}")?);
+    progress.enable_steady_tick(Duration::from_millis(100));
+
+    // The files found here are exactly the ones confirmed, deleted and recorded
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
     for temp_dir in &temp_dirs {
-        if let Some((size, files)) = analyze_temp_dir(temp_dir).await? {
-            println!("\n📁 {}", temp_dir.display());
-            println!("   Size: {}", format_size(size));
-            println!("   Files: {}", files);
+        let start = files.len();
+        if let Some((size, count)) = analyze_temp_dir(temp_dir, min_age, protect_newer_than, &progress, &mut seen, &mut files).await? {
+            if !summary_only {
+                progress.suspend(|| {
+                    info!("\n📁 {}", temp_dir.path.display());
+                    info!("   Size: {}", format_size(size));
+                    info!("   Files: {}", count);
+                    if dry_run {
+                        for file in &files[start..] {
+                            list_file(file);
+                        }
+                    }
+                });
+            }
             total_size += size;
-            total_files += files;
+            total_files += count;
         }
     }
+    progress.finish_and_clear();
 
     if total_size == 0 {
-        println!("\n✅ No temporary files found to clean up.");
-        return Ok(());
+        outln!("\n✅ No temporary files found to clean up.");
+        return Ok(CleanupReport::nothing_found(0));
     }
 
-    println!("\n📊 Summary:");
-    println!("   Total size: {}", format_size(total_size));
-    println!("   Total files: {}", total_files);
+    outln!("\n📊 Summary:");
+    outln!("   Total size: {}", format_size(total_size));
+    outln!("   Total files: {}", total_files);
 
     if dry_run {
-        println!("\n[DRY RUN] Would clean {} of temporary files", format_size(total_size));
-        return Ok(());
+        outln!("\n[DRY RUN] Would clean {} of temporary files", format_size(total_size));
+        return Ok(CleanupReport::default());
     }
 
-    if Confirm::new()
-        .with_prompt(&format!("Clean up {} of temporary files?", format_size(total_size)))
-        .interact()?
-    {
-        for temp_dir in &temp_dirs {
-            cleanup_temp_dir(temp_dir).await?;
+    // With --interactive each file gets its own prompt instead
+    let prompt = format!("Clean up {} temporary files ({})?", total_files, format_size(total_size));
+    if !interactive && !confirm_removal(prompt, total_size, confirm_threshold, assume_yes)? {
+        return Ok(CleanupReport::default());
+    }
+
+    if interactive {
+        files = confirm_each(files, assume_yes)?;
+        if files.is_empty() {
+            return Ok(CleanupReport::default());
         }
-        println!("\n✅ Temporary files cleanup completed!");
     }
+    undo::record("temp", &files, use_trash)?;
+    let report = remove_temp_files(files, use_trash).await?;
+    undo::record_removed("temp", &report, use_trash)?;
+    Ok(report)
+}
 
-    Ok(())
+/// A directory to scan for temporary files.
+struct TempDir {
+    path: PathBuf,
+    max_depth: usize,
+    /// Count every file, not just the ones named like temp files.
+    every_file: bool,
+    names: TempNames,
+}
+
+/// Which file names count as temp files: the built-in heuristics of
+/// [`is_safe_temp_file`] (unless `--only-pattern`) plus any `--pattern` globs,
+/// matched against the file name.
+#[derive(Clone)]
+struct TempNames {
+    patterns: GlobSet,
+    builtin: bool,
+}
+
+impl TempNames {
+    fn new(patterns: &[String], only_patterns: bool) -> Result<TempNames> {
+        Ok(TempNames { patterns: build_glob_set(patterns)?, builtin: !only_patterns })
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        (self.builtin && is_safe_temp_file(path))
+            || path.file_name().is_some_and(|name| self.patterns.is_match(name))
+    }
+}
+
+/// Browser caches relative to the home directory.
+#[cfg(target_os = "macos")]
+const BROWSER_CACHES: &[&str] = &[
+    "Library/Caches/Google/Chrome/Default/Cache",
+    "Library/Caches/Chromium/Default/Cache",
+    "Library/Caches/BraveSoftware/Brave-Browser/Default/Cache",
+];
+#[cfg(windows)]
+const BROWSER_CACHES: &[&str] = &[
+    "AppData\\Local\\Google\\Chrome\\User Data\\Default\\Cache",
+    "AppData\\Local\\Microsoft\\Edge\\User Data\\Default\\Cache",
+    "AppData\\Local\\BraveSoftware\\Brave-Browser\\User Data\\Default\\Cache",
+];
+#[cfg(all(unix, not(target_os = "macos")))]
+const BROWSER_CACHES: &[&str] = &[
+    ".cache/google-chrome/Default/Cache",
+    ".cache/chromium/Default/Cache",
+    ".cache/BraveSoftware/Brave-Browser/Default/Cache",
+];
+#[cfg(not(any(unix, windows)))]
+const BROWSER_CACHES: &[&str] = &[];
+
+/// Firefox keeps a `cache2` directory inside each profile under this one.
+#[cfg(target_os = "macos")]
+const FIREFOX_PROFILES: &str = "Library/Caches/Firefox/Profiles";
+#[cfg(windows)]
+const FIREFOX_PROFILES: &str = "AppData\\Local\\Mozilla\\Firefox\\Profiles";
+#[cfg(all(unix, not(target_os = "macos")))]
+const FIREFOX_PROFILES: &str = ".cache/mozilla/firefox";
+#[cfg(not(any(unix, windows)))]
+const FIREFOX_PROFILES: &str = "";
+
+/// Existing browser cache directories for the current user.
+fn browser_cache_dirs() -> Vec<PathBuf> {
+    match dirs::home_dir() {
+        Some(home) => browser_cache_dirs_in(&home),
+        None => Vec::new(),
+    }
+}
+
+fn browser_cache_dirs_in(home: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = BROWSER_CACHES.iter().map(|suffix| home.join(suffix)).collect();
+
+    if !FIREFOX_PROFILES.is_empty() {
+        if let Ok(profiles) = fs::read_dir(home.join(FIREFOX_PROFILES)) {
+            dirs.extend(profiles.filter_map(|e| e.ok()).map(|profile| profile.path().join("cache2")));
+        }
+    }
+
+    dirs.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+/// Windows maintenance caches cleaned with `--system`: downloaded update
+/// packages and the Prefetch launch traces. Only administrators can read them.
+const WINDOWS_SYSTEM_CACHES: &[&str] = &[
+    "C:\\Windows\\SoftwareDistribution\\Download",
+    "C:\\Windows\\Prefetch",
+];
+
+/// Candidate directories for `--system`; there are none outside Windows.
+fn system_temp_dirs() -> Vec<PathBuf> {
+    if cfg!(windows) {
+        WINDOWS_SYSTEM_CACHES.iter().map(PathBuf::from).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Splits `dirs` into the ones that can be listed and the ones access was
+/// denied to. Directories that don't exist are dropped.
+fn readable_dirs(dirs: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut readable = Vec::new();
+    let mut denied = Vec::new();
+    for dir in dirs {
+        match fs::read_dir(&dir) {
+            Ok(_) => readable.push(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => denied.push(dir),
+            Err(_) => {}
+        }
+    }
+    (readable, denied)
 }
 
 fn get_temp_directories() -> Vec<std::path::PathBuf> {
@@ -90,41 +283,101 @@ fn get_temp_directories() -> Vec<std::path::PathBuf> {
         .collect()
 }
 
-async fn analyze_temp_dir(path: &Path) -> Result<Option<(u64, usize)>> {
-    let path = path.to_owned();
+/// Total size and number of the temp files in `dir`, or `None` when there
+/// are none. The files themselves are appended to `matched`, so the caller
+/// deletes exactly what it showed and confirmed. Files already in `seen`,
+/// by canonical path, are left out: temp dirs can overlap, such as browser
+/// caches inside `~/.cache` or `TMP` and `TEMP` naming the same directory.
+async fn analyze_temp_dir(
+    dir: &TempDir,
+    min_age: Option<Duration>,
+    protect_newer_than: Option<SystemTime>,
+    progress: &ProgressBar,
+    seen: &mut HashSet<PathBuf>,
+    matched: &mut Vec<FileItem>,
+) -> Result<Option<(u64, usize)>> {
+    let mut files = find_temp_files(dir, min_age, protect_newer_than, progress).await?;
+    files.retain(|file| seen.insert(fs::canonicalize(&file.path).unwrap_or_else(|_| file.path.clone())));
+    let summary = (files.iter().map(|f| f.size).sum(), files.len());
+
+    matched.extend(files);
+    Ok(Some(summary).filter(|&(_, count)| count > 0))
+}
+
+/// Collects the files in `dir`, at most `max_depth` levels down, that look
+/// like safe-to-delete temp files and were not modified after
+/// `protect_newer_than`. `progress` shows the running totals.
+async fn find_temp_files(
+    dir: &TempDir,
+    min_age: Option<Duration>,
+    protect_newer_than: Option<SystemTime>,
+    progress: &ProgressBar,
+) -> Result<Vec<FileItem>> {
+    let path = dir.path.clone();
+    let max_depth = dir.max_depth;
+    let every_file = dir.every_file;
+    let names = dir.names.clone();
+    let progress = progress.clone();
     
     tokio::task::spawn_blocking(move || {
+        let mut files = Vec::new();
+        let mut dirs_scanned = 0usize;
+        let mut found_size = 0u64;
+
         if !path.exists() || !path.is_dir() {
-            return Ok(None);
+            return Ok(files);
         }
 
-        let mut total_size = 0u64;
-        let mut file_count = 0usize;
-
         for entry in WalkDir::new(&path)
-            .max_depth(2) // Limit depth for performance
+            .max_depth(max_depth)
             .into_iter()
             .filter_map(|e| e.ok())
         {
+            if entry.file_type().is_dir() {
+                dirs_scanned += 1;
+                progress.set_message(format!(
+                    "Scanning {}: {} directories, {} reclaimable",
+                    path.display(),
+                    dirs_scanned,
+                    format_size(found_size)
+                ));
+            }
+
             if let Ok(metadata) = entry.metadata() {
                 if metadata.is_file() {
                     // Only count files that are likely safe to delete
-                    if is_safe_temp_file(entry.path()) {
-                        total_size += metadata.len();
-                        file_count += 1;
+                    if (every_file || names.matches(entry.path()))
+                        && is_old_enough(&metadata, min_age)
+                        && protect_newer_than.is_none_or(|cutoff| metadata.modified().is_ok_and(|mtime| mtime <= cutoff))
+                    {
+                        found_size += metadata.len();
+                        files.push(FileItem {
+                            path: entry.path().to_owned(),
+                            size: metadata.len(),
+                            item_count: None,
+                            is_dir: false,
+                            mtime: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                            project_kind: None,
+                        });
                     }
                 }
             }
         }
 
-        if file_count > 0 {
-            Ok(Some((total_size, file_count)))
-        } else {
-            Ok(None)
-        }
+        Ok(files)
     }).await?
 }
 
+/// One dry-run line per file; `-v` adds the modification time.
+fn list_file(file: &FileItem) {
+    if tracing::enabled!(Level::DEBUG) {
+        let modified: DateTime<Local> = file.mtime.into();
+        info!("      {} ({}, modified {})", file.path.display(), format_size(file.size), modified.format("%Y-%m-%d %H:%M"));
+    } else {
+        info!("      {} ({})", file.path.display(), format_size(file.size));
+    }
+}
+
 fn is_safe_temp_file(path: &Path) -> bool {
     if let Some(file_name) = path.file_name() {
         if let Some(name_str) = file_name.to_str() {
@@ -141,38 +394,224 @@ fn is_safe_temp_file(path: &Path) -> bool {
     false
 }
 
-async fn cleanup_temp_dir(path: &Path) -> Result<()> {
-    let path = path.to_owned();
-    
+/// Returns true when the file was last modified at least `min_age` ago.
+/// Files with an unreadable or future mtime are treated as too young.
+fn is_old_enough(metadata: &fs::Metadata, min_age: Option<Duration>) -> bool {
+    let min_age = match min_age {
+        Some(min_age) => min_age,
+        None => return true,
+    };
+
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= min_age)
+}
+
+async fn remove_temp_files(files: Vec<FileItem>, use_trash: bool) -> Result<CleanupReport> {
     tokio::task::spawn_blocking(move || {
-        let mut cleaned_files = 0;
-        let mut cleaned_size = 0u64;
+        let mut report = CleanupReport::default();
 
-        for entry in WalkDir::new(&path)
-            .max_depth(2)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() && is_safe_temp_file(entry.path()) {
-                    match fs::remove_file(entry.path()) {
-                        Ok(_) => {
-                            cleaned_files += 1;
-                            cleaned_size += metadata.len();
-                        },
-                        Err(e) => {
-                            // Don't fail the entire operation for individual file errors
-                            eprintln!("   Warning: Failed to remove {}: {}", entry.path().display(), e);
-                        }
-                    }
+        for file in files {
+            let result = safety::check_in_use(&file.path).and_then(|_| remove_path(&file.path, use_trash));
+            // Don't fail the entire operation for individual file errors
+            if let Err(e) = &result {
+                match e.downcast_ref::<safety::InUse>() {
+                    Some(in_use) => warn!("   ⚠️  Skipping {}: {}", file.path.display(), in_use.reason()),
+                    None => warn!("   Warning: Failed to remove {}: {}", file.path.display(), e),
                 }
             }
+            report.record(&file, result);
         }
 
-        if cleaned_files > 0 {
-            println!("   ✅ Cleaned {} files ({})", cleaned_files, format_size(cleaned_size));
+        Ok(report)
+    }).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use filetime::FileTime;
+
+    #[tokio::test]
+    async fn test_min_age_skips_recent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let old_file = dir.path().join("old.tmp");
+        let new_file = dir.path().join("new.tmp");
+        fs::write(&old_file, vec![0u8; 100]).unwrap();
+        fs::write(&new_file, vec![0u8; 10]).unwrap();
+
+        let ten_days_ago = SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60);
+        filetime::set_file_mtime(&old_file, FileTime::from_system_time(ten_days_ago)).unwrap();
+
+        let progress = ProgressBar::hidden();
+        let temp_dir = scan_dir(dir.path(), 2);
+        assert_eq!(analyze_temp_dir(&temp_dir, None, None, &progress, &mut HashSet::new(), &mut Vec::new()).await.unwrap(), Some((110, 2)));
+        assert_eq!(analyze_temp_dir(&temp_dir, Some(Duration::from_secs(7 * 24 * 60 * 60)), None, &progress, &mut HashSet::new(), &mut Vec::new()).await.unwrap(), Some((100, 1)));
+        let hour_ago = SystemTime::now() - Duration::from_secs(60 * 60);
+        assert_eq!(analyze_temp_dir(&temp_dir, None, Some(hour_ago), &progress, &mut HashSet::new(), &mut Vec::new()).await.unwrap(), Some((100, 1)));
+
+        let files = find_temp_files(&temp_dir, Some(Duration::from_secs(7 * 24 * 60 * 60)), None, &progress).await.unwrap();
+        let report = remove_temp_files(files, false).await.unwrap();
+        assert_eq!(report.removed, vec![(old_file.clone(), 100)]);
+        assert_eq!(report.bytes_freed, 100);
+        assert!(!old_file.exists());
+        assert!(new_file.exists());
+
+        // Just written, so it looks in use and is skipped rather than failed
+        let files = find_temp_files(&temp_dir, None, None, &progress).await.unwrap();
+        let report = remove_temp_files(files, false).await.unwrap();
+        assert!(report.removed.is_empty() && report.failed.is_empty());
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, new_file);
+        assert!(new_file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_collects_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["build.tmp", "tmp4f2a", "notes.txt~", "report.pdf", "main.rs", "data.cache"] {
+            fs::write(dir.path().join(name), vec![0u8; 10]).unwrap();
         }
 
-        Ok(())
-    }).await?
-}
\ No newline at end of file
+        let mut matched = Vec::new();
+        let summary = analyze_temp_dir(&scan_dir(dir.path(), 2), None, None, &ProgressBar::hidden(), &mut HashSet::new(), &mut matched)
+            .await
+            .unwrap();
+        assert_eq!(summary, Some((40, 4)));
+
+        let mut names: Vec<String> = matched
+            .iter()
+            .map(|file| file.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["build.tmp", "data.cache", "notes.txt~", "tmp4f2a"]);
+        assert!(matched.iter().all(|file| file.size == 10));
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_dirs_count_each_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let browser = dir.path().join("chromium/Default/Cache");
+        fs::create_dir_all(&browser).unwrap();
+        fs::write(dir.path().join("build.tmp"), vec![0u8; 10]).unwrap();
+        fs::write(browser.join("data_0.tmp"), vec![0u8; 20]).unwrap();
+        fs::write(browser.join("f_000001"), vec![0u8; 40]).unwrap();
+        let hour_ago = FileTime::from_system_time(SystemTime::now() - Duration::from_secs(60 * 60));
+        for file in [dir.path().join("build.tmp"), browser.join("data_0.tmp"), browser.join("f_000001")] {
+            filetime::set_file_mtime(file, hour_ago).unwrap();
+        }
+
+        // The cache sits inside the scanned dir, which is also listed twice
+        let temp_dirs = [
+            scan_dir(dir.path(), usize::MAX),
+            scan_dir(dir.path(), usize::MAX),
+            TempDir { every_file: true, ..scan_dir(&browser, usize::MAX) },
+        ];
+        let progress = ProgressBar::hidden();
+        let mut seen = HashSet::new();
+        let mut files = Vec::new();
+        let mut summaries = Vec::new();
+        for temp_dir in &temp_dirs {
+            summaries.push(analyze_temp_dir(temp_dir, None, None, &progress, &mut seen, &mut files).await.unwrap());
+        }
+        assert_eq!(summaries, [Some((30, 2)), None, Some((40, 1))]);
+        assert_eq!(files.len(), 3);
+
+        let report = remove_temp_files(files, false).await.unwrap();
+        assert_eq!(report.removed.len(), 3);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.bytes_freed, 70);
+    }
+
+    #[tokio::test]
+    async fn test_custom_patterns_extend_or_replace_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["config.bak", "build.tmp", "notes.txt", "config.bak.txt"] {
+            fs::write(dir.path().join(name), vec![0u8; 10]).unwrap();
+        }
+        let bak = vec!["*.bak".to_string()];
+        assert_eq!(found_names(dir.path(), TempNames::new(&[], false).unwrap()).await, ["build.tmp"]);
+        assert_eq!(found_names(dir.path(), TempNames::new(&bak, false).unwrap()).await, ["build.tmp", "config.bak"]);
+        assert_eq!(found_names(dir.path(), TempNames::new(&bak, true).unwrap()).await, ["config.bak"]);
+    }
+
+    #[tokio::test]
+    async fn test_custom_depth_finds_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("session.tmp"), vec![0u8; 10]).unwrap();
+
+        let progress = ProgressBar::hidden();
+        assert_eq!(analyze_temp_dir(&scan_dir(dir.path(), 2), None, None, &progress, &mut HashSet::new(), &mut Vec::new()).await.unwrap(), None);
+        assert_eq!(analyze_temp_dir(&scan_dir(dir.path(), 4), None, None, &progress, &mut HashSet::new(), &mut Vec::new()).await.unwrap(), Some((10, 1)));
+    }
+
+    #[test]
+    fn test_browser_cache_dirs() {
+        for suffix in BROWSER_CACHES.iter().chain([&FIREFOX_PROFILES]) {
+            assert!(Path::new(suffix).is_relative(), "{}", suffix);
+            if cfg!(target_os = "macos") {
+                assert!(suffix.starts_with("Library/Caches/"), "{}", suffix);
+            } else if cfg!(windows) {
+                assert!(suffix.starts_with("AppData\\Local\\"), "{}", suffix);
+            } else if cfg!(unix) {
+                assert!(suffix.starts_with(".cache/"), "{}", suffix);
+            }
+        }
+
+        let home = tempfile::tempdir().unwrap();
+        assert!(browser_cache_dirs_in(home.path()).is_empty());
+
+        let chrome = home.path().join(BROWSER_CACHES[0]);
+        let firefox = home.path().join(FIREFOX_PROFILES).join("abc.default-release/cache2");
+        fs::create_dir_all(&chrome).unwrap();
+        fs::create_dir_all(&firefox).unwrap();
+        fs::create_dir_all(home.path().join(FIREFOX_PROFILES).join("no-cache")).unwrap();
+
+        let mut found = browser_cache_dirs_in(home.path());
+        found.sort();
+        let mut expected = vec![chrome, firefox];
+        expected.sort();
+        assert_eq!(found, expected);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_system_dirs_on_windows() {
+        let dirs = system_temp_dirs();
+        assert!(dirs.contains(&PathBuf::from("C:\\Windows\\SoftwareDistribution\\Download")));
+        assert!(dirs.contains(&PathBuf::from("C:\\Windows\\Prefetch")));
+
+        // Their contents are exempt from the C:\\Windows protection, nothing else is
+        assert!(safety::check_delete(Path::new("C:\\Windows\\Prefetch\\NO-SUCH-APP.EXE-1234.pf")).is_ok());
+        assert!(safety::check_delete(Path::new("C:\\Windows\\NoSuchDir\\file.tmp")).is_err());
+    }
+
+    #[test]
+    fn test_readable_dirs_drops_missing() {
+        let root = tempfile::tempdir().unwrap();
+        let missing = root.path().join("missing");
+        let (readable, denied) = readable_dirs(vec![root.path().to_path_buf(), missing]);
+        assert_eq!(readable, [root.path()]);
+        assert!(denied.is_empty());
+    }
+
+    async fn found_names(path: &Path, names: TempNames) -> Vec<String> {
+        let temp_dir = TempDir { names, ..scan_dir(path, 2) };
+        let mut found: Vec<String> = find_temp_files(&temp_dir, None, None, &ProgressBar::hidden())
+            .await
+            .unwrap()
+            .iter()
+            .map(|file| file.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        found.sort();
+        found
+    }
+
+    fn scan_dir(path: &Path, max_depth: usize) -> TempDir {
+        TempDir { path: path.to_owned(), max_depth, every_file: false, names: TempNames::new(&[], false).unwrap() }
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::Result;
+#[cfg(not(windows))]
+use std::fs;
+#[cfg(not(windows))]
+use std::path::{Path, PathBuf};
+use crate::utils::{confirm, format_size_as};
+use crate::color::{eoutln, outln};
+
+/// Shows how much the current user's trash holds and, with `empty`, deletes
+/// its contents permanently after confirmation.
+pub async fn run(empty: bool, assume_yes: bool, raw_bytes: bool) -> Result<()> {
+    outln!("🗑️  Trash");
+    outln!("========");
+
+    let entries = tokio::task::spawn_blocking(list_trash).await??;
+    if entries.is_empty() {
+        outln!("\n✅ The trash is empty.");
+        return Ok(());
+    }
+
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    outln!("\n📊 {} items, {} reclaimable", entries.len(), format_size_as(total, raw_bytes));
+    if !empty {
+        return Ok(());
+    }
+
+    let prompt = format!("Permanently delete {} items ({}) from the trash?", entries.len(), format_size_as(total, raw_bytes));
+    if !confirm(prompt, assume_yes)? {
+        return Ok(());
+    }
+
+    let failed = tokio::task::spawn_blocking(move || purge(entries)).await??;
+    for (name, error) in &failed {
+        eoutln!("   ❌ Failed to delete {}: {}", name, error);
+    }
+    if failed.is_empty() {
+        outln!("\n✅ Emptied the trash, freeing {}", format_size_as(total, raw_bytes));
+    }
+    Ok(())
+}
+
+/// One top-level item in the trash.
+#[cfg(not(windows))]
+struct TrashEntry {
+    path: PathBuf,
+    /// The freedesktop `.trashinfo` file that goes with it
+    info: Option<PathBuf>,
+    size: u64,
+}
+
+#[cfg(windows)]
+struct TrashEntry {
+    item: trash::TrashItem,
+    size: u64,
+}
+
+/// The current user's trash directory: `~/.Trash` on macOS, otherwise the
+/// freedesktop home trash in `$XDG_DATA_HOME/Trash`.
+#[cfg(not(windows))]
+fn trash_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|home| home.join(".Trash"))
+    } else {
+        dirs::data_dir().map(|data| data.join("Trash"))
+    }
+}
+
+#[cfg(not(windows))]
+fn list_trash() -> Result<Vec<TrashEntry>> {
+    match trash_dir() {
+        Some(trash) => trash_entries(&trash, !cfg!(target_os = "macos")),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Items in the trash directory `trash`. A freedesktop trash keeps them in
+/// `files/`, with a `.trashinfo` file each in `info/`.
+#[cfg(not(windows))]
+fn trash_entries(trash: &Path, freedesktop: bool) -> Result<Vec<TrashEntry>> {
+    let files = if freedesktop { trash.join("files") } else { trash.to_path_buf() };
+    let listing = match fs::read_dir(&files) {
+        Ok(listing) => listing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut entries = Vec::new();
+    for entry in listing {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        if !freedesktop && name == ".DS_Store" {
+            continue;
+        }
+        let info = freedesktop.then(|| trash.join("info").join(format!("{}.trashinfo", name)));
+        entries.push(TrashEntry { size: tree_size(&path), path, info });
+    }
+    Ok(entries)
+}
+
+/// Apparent size of the files at or below `path`.
+#[cfg(not(windows))]
+fn tree_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Deletes `entries` for good, returning the ones that could not be deleted.
+#[cfg(not(windows))]
+fn purge(entries: Vec<TrashEntry>) -> Result<Vec<(String, String)>> {
+    let mut failed = Vec::new();
+    for entry in entries {
+        let result = crate::cleanup::removal::remove_path(&entry.path, false).and_then(|_| match &entry.info {
+            Some(info) if info.exists() => Ok(fs::remove_file(info)?),
+            _ => Ok(()),
+        });
+        if let Err(e) = result {
+            failed.push((entry.path.display().to_string(), e.to_string()));
+        }
+    }
+    Ok(failed)
+}
+
+#[cfg(windows)]
+fn list_trash() -> Result<Vec<TrashEntry>> {
+    let mut entries = Vec::new();
+    for item in trash::os_limited::list()? {
+        // The Recycle Bin only reports a byte size for files, not folders
+        let size = trash::os_limited::metadata(&item).ok().and_then(|metadata| metadata.size.size()).unwrap_or(0);
+        entries.push(TrashEntry { item, size });
+    }
+    Ok(entries)
+}
+
+#[cfg(windows)]
+fn purge(entries: Vec<TrashEntry>) -> Result<Vec<(String, String)>> {
+    trash::os_limited::purge_all(entries.into_iter().map(|entry| entry.item))?;
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_freedesktop_trash_entries() {
+        use super::*;
+
+        let trash = tempfile::tempdir().unwrap();
+        fs::create_dir_all(trash.path().join("files/photos")).unwrap();
+        fs::create_dir_all(trash.path().join("info")).unwrap();
+        fs::write(trash.path().join("files/notes.txt"), vec![0u8; 10]).unwrap();
+        fs::write(trash.path().join("files/photos/a.jpg"), vec![0u8; 20]).unwrap();
+        fs::write(trash.path().join("info/notes.txt.trashinfo"), "[Trash Info]\n").unwrap();
+        fs::write(trash.path().join("info/photos.trashinfo"), "[Trash Info]\n").unwrap();
+
+        let mut entries = trash_entries(trash.path(), true).unwrap();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let summary: Vec<_> = entries.iter().map(|e| (e.path.file_name().unwrap().to_str().unwrap(), e.size)).collect();
+        assert_eq!(summary, [("notes.txt", 10), ("photos", 20)]);
+        assert_eq!(entries[1].info, Some(trash.path().join("info/photos.trashinfo")));
+
+        assert!(purge(entries).unwrap().is_empty());
+        assert!(trash_entries(trash.path(), true).unwrap().is_empty());
+        assert_eq!(fs::read_dir(trash.path().join("info")).unwrap().count(), 0);
+        assert!(trash_entries(&trash.path().join("missing"), true).unwrap().is_empty());
+    }
+}
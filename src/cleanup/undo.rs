@@ -0,0 +1,261 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::cleanup::report::CleanupReport;
+use crate::discovery::FileItem;
+use crate::utils::format_size_as;
+use crate::color::{eoutln, outln};
+
+/// What the most recent destructive cleanup removed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoManifest {
+    /// Subcommand that performed the removal (e.g. "temp", "dev-clean")
+    pub command: String,
+    /// Whether items were moved to the system trash rather than deleted
+    pub trashed: bool,
+    pub entries: Vec<UndoEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub removed_at: DateTime<Local>,
+}
+
+/// Location of the manifest, `~/.local/share/safe-clean/last-run.json` on Linux.
+pub fn manifest_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("Could not determine the user data directory"))?;
+    Ok(data_dir.join("safe-clean").join("last-run.json"))
+}
+
+/// Writes the manifest for `items` before they are removed, replacing the
+/// record of the previous run. This only survives a crash mid-removal;
+/// [`record_removed`] replaces it once removal finishes.
+pub fn record(command: &str, items: &[FileItem], trashed: bool) -> Result<()> {
+    record_to(&manifest_path()?, command, items, trashed)
+}
+
+fn record_to(manifest_path: &Path, command: &str, items: &[FileItem], trashed: bool) -> Result<()> {
+    let entries = items.iter().map(|item| (item.path.as_path(), item.size));
+    write_manifest(manifest_path, command, entries, trashed)
+}
+
+/// Rewrites the manifest with only what `report` actually removed, so items
+/// that were skipped or failed are not later reported as deleted.
+pub fn record_removed(command: &str, report: &CleanupReport, trashed: bool) -> Result<()> {
+    record_removed_to(&manifest_path()?, command, report, trashed)
+}
+
+fn record_removed_to(manifest_path: &Path, command: &str, report: &CleanupReport, trashed: bool) -> Result<()> {
+    let entries = report.removed.iter().map(|(path, size)| (path.as_path(), *size));
+    write_manifest(manifest_path, command, entries, trashed)
+}
+
+fn write_manifest<'a>(
+    manifest_path: &Path,
+    command: &str,
+    entries: impl Iterator<Item = (&'a Path, u64)>,
+    trashed: bool,
+) -> Result<()> {
+    let now = Local::now();
+    let manifest = UndoManifest {
+        command: command.to_string(),
+        trashed,
+        entries: entries
+            .map(|(path, size)| UndoEntry {
+                // Trash implementations record the canonical location
+                path: canonical(path),
+                size,
+                removed_at: now,
+            })
+            .collect(),
+    };
+
+    if let Some(parent) = manifest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    Ok(())
+}
+
+/// Canonical form of `path`, resolving only its parent once the item itself
+/// has been removed.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .ok()
+        .or_else(|| Some(fs::canonicalize(path.parent()?).ok()?.join(path.file_name()?)))
+        .unwrap_or_else(|| path.to_owned())
+}
+
+fn read_manifest(manifest_path: &Path) -> Result<Option<UndoManifest>> {
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(manifest_path)?)?))
+}
+
+pub async fn run(raw_bytes: bool) -> Result<()> {
+    let manifest_path = manifest_path()?;
+    tokio::task::spawn_blocking(move || undo_from(&manifest_path, raw_bytes)).await?
+}
+
+fn undo_from(manifest_path: &Path, raw_bytes: bool) -> Result<()> {
+    outln!("↩️  Undo Last Cleanup");
+    outln!("=====================");
+
+    let manifest = match read_manifest(manifest_path)? {
+        Some(manifest) => manifest,
+        None => {
+            outln!("\nNo cleanup has been recorded yet.");
+            return Ok(());
+        }
+    };
+
+    let total_size: u64 = manifest.entries.iter().map(|e| e.size).sum();
+    outln!(
+        "\nLast run: `{}` removed {} items ({})",
+        manifest.command,
+        manifest.entries.len(),
+        format_size_as(total_size, raw_bytes)
+    );
+
+    if !manifest.trashed {
+        outln!("\n❌ These items were deleted permanently and cannot be restored:");
+        print_entries(&manifest.entries, raw_bytes);
+        return Ok(());
+    }
+
+    match restore_from_trash(&manifest.entries)? {
+        Some(restored) => {
+            outln!("\n✅ Restored {} of {} items from the trash", restored, manifest.entries.len());
+            fs::remove_file(manifest_path)?;
+        }
+        None => {
+            outln!("\nRestoring from the trash is not supported on this platform.");
+            outln!("Restore these items from the Trash manually:");
+            print_entries(&manifest.entries, raw_bytes);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_entries(entries: &[UndoEntry], raw_bytes: bool) {
+    for entry in entries {
+        outln!("   {} ({})", entry.path.display(), format_size_as(entry.size, raw_bytes));
+    }
+}
+
+/// Restores the most recently trashed item for each entry's original path.
+/// Returns how many items were restored, or `None` when the platform's trash
+/// can't be enumerated.
+#[cfg(any(
+    windows,
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+fn restore_from_trash(entries: &[UndoEntry]) -> Result<Option<usize>> {
+    let mut trashed = trash::os_limited::list()?;
+    trashed.sort_by_key(|item| std::cmp::Reverse(item.time_deleted));
+
+    let mut to_restore = Vec::new();
+    for entry in entries {
+        if entry.path.exists() {
+            eoutln!("   ⚠️  Already exists, not restoring: {}", entry.path.display());
+            continue;
+        }
+        if let Some(index) = trashed.iter().position(|item| item.original_path() == entry.path) {
+            to_restore.push(trashed.remove(index));
+        } else {
+            eoutln!("   ⚠️  Not found in trash: {}", entry.path.display());
+        }
+    }
+
+    let restored = to_restore.len();
+    trash::os_limited::restore_all(to_restore)?;
+    Ok(Some(restored))
+}
+
+#[cfg(not(any(
+    windows,
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+)))]
+fn restore_from_trash(_entries: &[UndoEntry]) -> Result<Option<usize>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleanup::removal::{remove_items, remove_path};
+
+    fn item(path: PathBuf, size: u64) -> FileItem {
+        FileItem::test(path, size, None)
+    }
+
+    #[test]
+    fn test_manifest_records_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.tmp");
+        fs::write(&file, b"abc").unwrap();
+        let manifest_path = dir.path().join("state/last-run.json");
+
+        record_to(&manifest_path, "temp", &[item(file.clone(), 3)], false).unwrap();
+
+        let manifest = read_manifest(&manifest_path).unwrap().unwrap();
+        assert_eq!(manifest.command, "temp");
+        assert!(!manifest.trashed);
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.entries[0].path, fs::canonicalize(&file).unwrap());
+        assert_eq!(manifest.entries[0].size, 3);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_rewritten_with_removed_items_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let removed = dir.path().join("removed.tmp");
+        let skipped = dir.path().join("skipped.tmp");
+        fs::write(&removed, b"abc").unwrap();
+        fs::write(&skipped, b"de").unwrap();
+        let manifest_path = dir.path().join("last-run.json");
+        let items = vec![item(removed.clone(), 3), item(skipped.clone(), 2)];
+
+        record_to(&manifest_path, "temp", &items, false).unwrap();
+        // Gone before removal, as if it had been skipped or failed
+        fs::remove_file(&skipped).unwrap();
+        let report = remove_items(items, false).await.unwrap();
+        record_removed_to(&manifest_path, "temp", &report, false).unwrap();
+
+        let manifest = read_manifest(&manifest_path).unwrap().unwrap();
+        let paths: Vec<_> = manifest.entries.iter().map(|entry| &entry.path).collect();
+        assert_eq!(paths, [&fs::canonicalize(dir.path()).unwrap().join("removed.tmp")]);
+        assert_eq!(manifest.entries[0].size, 3);
+    }
+
+    #[cfg(any(windows, target_os = "linux"))]
+    #[test]
+    fn test_undo_restores_trashed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(format!("safe-clean-undo-test-{}.tmp", std::process::id()));
+        fs::write(&file, b"restore me").unwrap();
+        let manifest_path = dir.path().join("last-run.json");
+
+        record_to(&manifest_path, "temp", &[item(file.clone(), 10)], true).unwrap();
+        remove_path(&file, true).unwrap();
+        assert!(!file.exists());
+
+        undo_from(&manifest_path, false).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"restore me");
+        assert!(!manifest_path.exists());
+    }
+}
@@ -1,2 +1,8 @@
 pub mod list;
-pub mod large;
\ No newline at end of file
+pub mod large;
+pub mod dupes;
+pub mod output;
+pub mod tree;
+pub mod stats;
+pub mod histogram;
+pub mod watch;
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+use crate::cli::output::{showing, take_top};
+use crate::discovery::{wasted_space, DuplicateFinder, WalkOptions};
+use crate::utils::{format_size_as, interrupt_flag, note_skipped, note_throughput};
+use crate::color::outln_to;
+
+pub async fn run(path: Option<String>, top: usize, raw_bytes: bool, walk: WalkOptions, out: &mut dyn Write) -> Result<()> {
+    let target_path = path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&target_path);
+
+    outln_to!(out, "Searching for duplicate files in: {}", path.display())?;
+    outln_to!(out)?;
+
+    let started = Instant::now();
+    let mut finder = DuplicateFinder::new();
+    finder.cancel = interrupt_flag();
+    finder.walk = walk;
+    let mut groups = finder.find_duplicates(path).await?;
+    note_skipped(&finder.skipped);
+    note_throughput(&finder.scanned, started);
+
+    if groups.is_empty() {
+        outln_to!(out, "No duplicate files found")?;
+        return Ok(());
+    }
+
+    // Groups come largest wasted space first, so --top keeps the worst ones
+    let total_wasted: u64 = groups.iter().map(|group| wasted_space(group)).sum();
+    let found = take_top(&mut groups, top);
+    for group in &groups {
+        let wasted = wasted_space(group);
+        outln_to!(
+            out,
+            "{} copies of {} ({} wasted)",
+            group.len(),
+            format_size_as(group[0].size, raw_bytes),
+            format_size_as(wasted, raw_bytes)
+        )?;
+        for item in group {
+            outln_to!(out, "   {}", item.path.display())?;
+        }
+        outln_to!(out)?;
+    }
+
+    outln_to!(
+        out,
+        "Found {} duplicate groups{}, {} reclaimable",
+        found,
+        showing(groups.len(), found),
+        format_size_as(total_wasted, raw_bytes)
+    )?;
+
+    Ok(())
+}
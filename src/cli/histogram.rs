@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::time::Instant;
+use crate::discovery::{LargeFileFinder, WalkOptions};
+use crate::utils::{format_size_as, interrupt_flag, note_skipped, note_throughput, parse_size};
+use crate::color::outln;
+
+/// Upper bounds of all but the last bucket when `--buckets` is omitted.
+pub const DEFAULT_BUCKETS: &str = "1KiB,1MiB,100MiB";
+
+/// Width of the longest bar with `--bars`.
+const BAR_WIDTH: usize = 40;
+
+/// Files whose size is at least `low` and below `high` (no upper bound for the last bucket).
+#[derive(Debug, PartialEq)]
+struct Bucket {
+    low: u64,
+    high: Option<u64>,
+    count: usize,
+    bytes: u64,
+}
+
+pub async fn run(path: Option<String>, buckets: String, bars: bool, walk: WalkOptions, raw_bytes: bool) -> Result<()> {
+    let target_path = path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&target_path);
+    let bounds = parse_bounds(&buckets)?;
+
+    outln!("Size distribution of files in: {}\n", path.display());
+
+    // With no minimum size the finder returns every file; apparent sizes
+    // are what people mean by a file's size
+    let started = Instant::now();
+    let mut finder = LargeFileFinder::new();
+    finder.cancel = interrupt_flag();
+    finder.allocated = false;
+    finder.walk = walk;
+    let files = finder.find_large_files(path, 0, None, Vec::new()).await?;
+    note_skipped(&finder.skipped);
+    note_throughput(&finder.scanned, started);
+
+    let histogram = bin(files.iter().map(|file| file.size), &bounds);
+    let largest = histogram.iter().map(|bucket| bucket.bytes).max().unwrap_or(0);
+
+    outln!("{:<24} {:>10} {:>15}", "Size", "Files", "Total");
+    outln!("{:-<51}", "");
+    for bucket in &histogram {
+        let row = format!(
+            "{:<24} {:>10} {:>15}",
+            label(bucket, raw_bytes),
+            bucket.count,
+            format_size_as(bucket.bytes, raw_bytes)
+        );
+        let bar = bar(bucket.bytes, largest);
+        if bars && !bar.is_empty() {
+            outln!("{}  {}", row, bar);
+        } else {
+            outln!("{}", row);
+        }
+    }
+    outln!("{:-<51}", "");
+    outln!(
+        "{:<24} {:>10} {:>15}",
+        "Total",
+        files.len(),
+        format_size_as(histogram.iter().map(|bucket| bucket.bytes).sum(), raw_bytes)
+    );
+
+    Ok(())
+}
+
+/// Parses a comma-separated list of increasing sizes like `1KB,1MB,100MB`.
+fn parse_bounds(spec: &str) -> Result<Vec<u64>> {
+    let bounds = spec
+        .split(',')
+        .map(parse_size)
+        .collect::<Result<Vec<u64>>>()?;
+    if bounds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(anyhow!("Bucket boundaries must be increasing: {}", spec));
+    }
+    Ok(bounds)
+}
+
+/// Sorts `sizes` into one bucket below each of `bounds` plus one for
+/// everything from the last bound up.
+fn bin(sizes: impl Iterator<Item = u64>, bounds: &[u64]) -> Vec<Bucket> {
+    let mut buckets: Vec<Bucket> = (0..=bounds.len())
+        .map(|i| Bucket {
+            low: if i == 0 { 0 } else { bounds[i - 1] },
+            high: bounds.get(i).copied(),
+            count: 0,
+            bytes: 0,
+        })
+        .collect();
+
+    for size in sizes {
+        let bucket = &mut buckets[bounds.partition_point(|&bound| bound <= size)];
+        bucket.count += 1;
+        bucket.bytes += size;
+    }
+    buckets
+}
+
+fn label(bucket: &Bucket, raw_bytes: bool) -> String {
+    match bucket.high {
+        Some(high) => format!("{} - {}", format_size_as(bucket.low, raw_bytes), format_size_as(high, raw_bytes)),
+        None => format!("{}+", format_size_as(bucket.low, raw_bytes)),
+    }
+}
+
+/// `#` bar for `bytes`, scaled so that `largest` fills `BAR_WIDTH`.
+fn bar(bytes: u64, largest: u64) -> String {
+    if largest == 0 {
+        return String::new();
+    }
+    let width = (bytes as f64 / largest as f64 * BAR_WIDTH as f64).round() as usize;
+    "#".repeat(width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_fall_into_expected_buckets() {
+        let bounds = parse_bounds(DEFAULT_BUCKETS).unwrap();
+        assert_eq!(bounds, [1024, 1024 * 1024, 100 * 1024 * 1024]);
+
+        let sizes = [0, 10, 1023, 1024, 500_000, 2 * 1024 * 1024, 200 * 1024 * 1024];
+        let buckets = bin(sizes.into_iter(), &bounds);
+        let summary: Vec<_> = buckets.iter().map(|bucket| (bucket.count, bucket.bytes)).collect();
+        assert_eq!(summary, [(3, 1033), (2, 501_024), (1, 2 * 1024 * 1024), (1, 200 * 1024 * 1024)]);
+        assert_eq!(buckets[3], Bucket { low: 100 * 1024 * 1024, high: None, count: 1, bytes: 200 * 1024 * 1024 });
+
+        assert_eq!(label(&buckets[0], false), "0 B - 1.0 KiB");
+        assert_eq!(label(&buckets[3], false), "100.0 MiB+");
+        assert_eq!(bar(50, 100), "#".repeat(BAR_WIDTH / 2));
+        assert_eq!(bar(0, 0), "");
+        assert!(parse_bounds("1MB,1KB").is_err());
+    }
+}
@@ -1,40 +1,317 @@
-use anyhow::Result;
-use std::path::Path;
-use crate::discovery::LargeFileFinder;
-use crate::utils::{format_size, parse_size};
-
-pub async fn run(path: Option<String>, size_str: String) -> Result<()> {
-    let target_path = path.unwrap_or_else(|| ".".to_string());
-    let path = Path::new(&target_path);
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use dialoguer::Select;
+use crate::cli::output::{showing, take_top, write_csv, write_ndjson, write_plain, OutputFormat};
+use crate::discovery::{scan_each, FileItem, LargeFileFinder, WalkOptions};
+use crate::utils::{describe_roots, format_age_since, format_size_as, format_size_exact, interrupt_flag, note_coverage, note_skipped, note_throughput, open_in_file_manager, parent_dir, parse_size, scan_roots, truncate_path};
+use crate::color::outln_to;
+
+/// Files found but not yet printed with `--format ndjson`; the walk waits
+/// when this many are queued.
+const STREAM_BUFFER: usize = 1024;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    paths: Vec<String>,
+    size_str: String,
+    max_size_str: Option<String>,
+    extensions: Vec<String>,
+    older_than: Option<Duration>,
+    newer_than: Option<Duration>,
+    group_by_dir: bool,
+    top: usize,
+    show_age: bool,
+    reveal: bool,
+    allocated: bool,
+    raw_bytes: bool,
+    walk: WalkOptions,
+    format: OutputFormat,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let roots = scan_roots(paths);
     let min_size = parse_size(&size_str)?;
+    let max_size = max_size_str.as_deref().map(parse_size).transpose()?;
+    if let (Some(older_than), Some(newer_than)) = (older_than, newer_than) {
+        if older_than >= newer_than {
+            bail!("--older-than must be shorter than --newer-than, or no file can match");
+        }
+    }
 
-    println!("Searching for files larger than {} in: {}", format_size(min_size), path.display());
-    println!();
+    if format == OutputFormat::Table {
+        match max_size {
+            Some(max_size) => outln_to!(
+                out,
+                "Searching for files between {} and {} in: {}",
+                format_size_exact(min_size),
+                format_size_exact(max_size),
+                describe_roots(&roots)
+            )?,
+            None => outln_to!(out, "Searching for files larger than {} in: {}", format_size_exact(min_size), describe_roots(&roots))?,
+        }
+        if walk.follow_symlinks {
+            outln_to!(out, "Following symbolic links")?;
+        }
+        outln_to!(out)?;
+    }
+
+    let started = Instant::now();
+    let mut finder = LargeFileFinder::new();
+    finder.cancel = interrupt_flag();
+    finder.allocated = allocated;
+    finder.walk = walk;
+    let now = SystemTime::now();
+    finder.modified_before = older_than.map(|age| now.checked_sub(age).unwrap_or(UNIX_EPOCH));
+    finder.modified_after = newer_than.map(|age| now.checked_sub(age).unwrap_or(UNIX_EPOCH));
+    if format == OutputFormat::Ndjson {
+        if group_by_dir || top > 0 {
+            bail!("--format ndjson prints files unsorted as they are found, so it can't be combined with --top or --group-by-dir");
+        }
+        stream_ndjson(&finder, roots, min_size, max_size, extensions, out).await?;
+        note_skipped(&finder.skipped);
+        note_throughput(&finder.scanned, started);
+        return Ok(());
+    }
+    let scans = scan_each(&roots, |root| {
+        let finder = finder.clone();
+        let extensions = extensions.clone();
+        async move { finder.find_large_files(&root, min_size, max_size, extensions).await }
+    })
+    .await?;
+    let mut results: Vec<FileItem> = scans.into_iter().flatten().collect();
+    note_skipped(&finder.skipped);
+    note_throughput(&finder.scanned, started);
+    note_coverage(&finder.scanned, results.len());
+    results.sort_by_key(|item| std::cmp::Reverse(item.size));
+    if group_by_dir {
+        results = group_by_parent(results);
+    }
+    let files: usize = if group_by_dir {
+        results.iter().filter_map(|dir| dir.item_count).sum()
+    } else {
+        results.len()
+    };
+    let found = take_top(&mut results, top);
 
-    let finder = LargeFileFinder::new();
-    let results = finder.find_large_files(path, min_size).await?;
+    match format {
+        OutputFormat::Csv => return write_csv(out, &results),
+        OutputFormat::Plain => return write_plain(out, &results),
+        OutputFormat::Table | OutputFormat::Ndjson => {}
+    }
 
     if results.is_empty() {
-        println!("No files found larger than {}", format_size(min_size));
+        outln_to!(out, "No files found larger than {}", format_size_exact(min_size))?;
+        return Ok(());
+    }
+
+    if group_by_dir {
+        outln_to!(out, "{:<60} {:>8} {:>15}{}", "Directory", "Files", "Size", age_column(show_age, "Last Modified"))?;
+        outln_to!(out, "{:-<1$}", "", if show_age { 103 } else { 85 })?;
+        for dir in &results {
+            outln_to!(
+                out,
+                "{:<60} {:>8} {:>15}{}",
+                truncate_path(&dir.path, 57),
+                dir.item_count.unwrap_or(0),
+                format_size_as(dir.size, raw_bytes),
+                age_column(show_age, &format_age_since(dir.mtime))
+            )?;
+        }
+        outln_to!(out, "\nFound {} large files in {} directories{}", files, found, showing(results.len(), found))?;
+        if reveal {
+            reveal_item(&results, raw_bytes)?;
+        }
         return Ok(());
     }
 
-    println!("{:<60} {:>15}", "Path", "Size");
-    println!("{:-<75}", "");
+    outln_to!(out, "{:<60} {:>15}{}", "Path", "Size", age_column(show_age, "Last Modified"))?;
+    outln_to!(out, "{:-<1$}", "", if show_age { 93 } else { 75 })?;
 
     for item in &results {
-        println!(
-            "{:<60} {:>15}",
-            if item.path.to_string_lossy().len() > 57 {
-                format!("...{}", &item.path.to_string_lossy()[item.path.to_string_lossy().len()-54..])
-            } else {
-                item.path.to_string_lossy().to_string()
-            },
-            format_size(item.size)
-        );
+        outln_to!(
+            out,
+            "{:<60} {:>15}{}",
+            truncate_path(&item.path, 57),
+            format_size_as(item.size, raw_bytes),
+            age_column(show_age, &format_age_since(item.mtime))
+        )?;
     }
 
-    println!("\nFound {} large files", results.len());
+    outln_to!(out, "\nFound {} large files{}", found, showing(results.len(), found))?;
+    if reveal {
+        reveal_item(&results, raw_bytes)?;
+    }
+
+    Ok(())
+}
 
+/// With `--reveal`: asks which result to look at and opens its folder in the
+/// file manager. Esc opens nothing.
+fn reveal_item(results: &[FileItem], raw_bytes: bool) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!("--reveal needs a terminal to pick a result from");
+    }
+    let labels: Vec<String> = results
+        .iter()
+        .map(|item| format!("{} ({})", item.path.display(), format_size_as(item.size, raw_bytes)))
+        .collect();
+    let choice = Select::new().with_prompt("Open the folder of").items(&labels).default(0).interact_opt()?;
+    if let Some(dir) = choice.and_then(|index| reveal_target(results, index)) {
+        open_in_file_manager(&dir)?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// The folder to open for the chosen result: the directory itself with
+/// `--group-by-dir`, otherwise the one containing the file.
+fn reveal_target(results: &[FileItem], index: usize) -> Option<PathBuf> {
+    let item = results.get(index)?;
+    Some(if item.is_dir { item.path.clone() } else { parent_dir(&item.path) })
+}
+
+/// Prints each matching file as a line of NDJSON the moment the walk finds
+/// it, so memory use stays flat however many files match.
+async fn stream_ndjson(
+    finder: &LargeFileFinder,
+    roots: Vec<PathBuf>,
+    min_size: u64,
+    max_size: Option<u64>,
+    extensions: Vec<String>,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let (sender, mut found) = tokio::sync::mpsc::channel(STREAM_BUFFER);
+    let mut streaming = finder.clone();
+    streaming.stream = Some(sender);
+    let scans = tokio::spawn(async move {
+        scan_each(&roots, |root| {
+            let finder = streaming.clone();
+            let extensions = extensions.clone();
+            async move { finder.find_large_files(&root, min_size, max_size, extensions).await }
+        })
+        .await
+    });
+
+    while let Some(item) = found.recv().await {
+        write_ndjson(&mut *out, &item)?;
+    }
+    scans.await??;
+    Ok(())
+}
+
+/// The right-aligned "Last Modified" cell with `--show-age`, else nothing.
+fn age_column(show_age: bool, text: &str) -> String {
+    if show_age {
+        format!(" {:>17}", text)
+    } else {
+        String::new()
+    }
+}
+
+/// Folds `files` into one entry per parent directory, with the number of
+/// files as `item_count` and their summed size, largest directory first.
+fn group_by_parent(files: Vec<FileItem>) -> Vec<FileItem> {
+    let mut dirs: HashMap<PathBuf, FileItem> = HashMap::new();
+
+    for file in files {
+        let parent = file.path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let dir = dirs.entry(parent.clone()).or_insert_with(|| FileItem {
+            path: parent,
+            size: 0,
+            item_count: Some(0),
+            is_dir: true,
+            mtime: file.mtime,
+            project_kind: None,
+        });
+        dir.size += file.size;
+        dir.item_count = dir.item_count.map(|count| count + 1);
+        dir.mtime = dir.mtime.max(file.mtime);
+    }
+
+    let mut dirs: Vec<FileItem> = dirs.into_values().collect();
+    dirs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64) -> FileItem {
+        FileItem::test(path, size, None)
+    }
+
+    #[test]
+    fn test_group_by_parent_sums_sizes() {
+        let grouped = group_by_parent(vec![
+            file("videos/a.mp4", 300),
+            file("isos/debian.iso", 400),
+            file("videos/b.mp4", 200),
+        ]);
+
+        let summary: Vec<_> = grouped.iter().map(|d| (d.path.to_str().unwrap(), d.item_count, d.size)).collect();
+        assert_eq!(summary, vec![("videos", Some(2), 500), ("isos", Some(1), 400)]);
+        assert!(grouped.iter().all(|d| d.is_dir));
+    }
+
+    #[tokio::test]
+    async fn test_top_caps_rows() {
+        let root = tempfile::tempdir().unwrap();
+        for (i, size) in [100, 500, 300, 200, 400].into_iter().enumerate() {
+            std::fs::write(root.path().join(format!("{}.bin", i)), vec![0u8; size]).unwrap();
+        }
+
+        let mut out = Vec::new();
+        let paths = vec![root.path().to_string_lossy().into_owned()];
+        run(paths, "1".to_string(), None, Vec::new(), None, None, false, 3, false, false, false, true, WalkOptions::default(), OutputFormat::Table, &mut out)
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        let prefix = root.path().to_string_lossy();
+        let rows: Vec<&str> = output.lines().filter(|line| line.starts_with(prefix.as_ref())).collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].contains("1.bin") && rows[0].ends_with(" 500"));
+        assert!(rows[2].contains("2.bin") && rows[2].ends_with(" 300"));
+        assert!(output.contains("Found 5 large files (showing 3 of 5)"));
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_one_object_per_line() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("media")).unwrap();
+        std::fs::write(root.path().join("a.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(root.path().join("media/b.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(root.path().join("small.txt"), vec![0u8; 10]).unwrap();
+
+        let mut out = Vec::new();
+        let paths = vec![root.path().to_string_lossy().into_owned()];
+        run(paths, "100".to_string(), None, Vec::new(), None, None, false, 0, false, false, false, false, WalkOptions::default(), OutputFormat::Ndjson, &mut out)
+            .await
+            .unwrap();
+
+        let mut items: Vec<(String, u64, bool)> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let item: serde_json::Value = serde_json::from_str(line).unwrap();
+                (item["path"].as_str().unwrap().to_string(), item["size_bytes"].as_u64().unwrap(), item["is_dir"].as_bool().unwrap())
+            })
+            .collect();
+        items.sort();
+        let expected = |path: &str, size| (root.path().join(path).to_string_lossy().into_owned(), size, false);
+        assert_eq!(items, vec![expected("a.bin", 200), expected("media/b.bin", 300)]);
+    }
+
+    #[test]
+    fn test_reveal_target_for_choice() {
+        let results = vec![file("media/videos/a.mp4", 300), file("b.iso", 200)];
+        assert_eq!(reveal_target(&results, 0), Some(PathBuf::from("media/videos")));
+        assert_eq!(reveal_target(&results, 1), Some(PathBuf::from(".")));
+        assert_eq!(reveal_target(&results, 2), None);
+
+        let grouped = group_by_parent(results);
+        assert_eq!(reveal_target(&grouped, 0), Some(PathBuf::from("media/videos")));
+    }
+}
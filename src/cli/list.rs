@@ -1,33 +1,368 @@
 use anyhow::Result;
+use std::io::Write;
+use std::collections::HashSet;
 use std::path::Path;
-use crate::discovery::DirAnalyzer;
-use crate::utils::format_size;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::warn;
+use crate::cli::output::{sort_items, write_csv, write_ndjson, write_plain, OutputFormat, SortKey};
+use crate::discovery::{outermost, scan_each, total_size, DirAnalyzer, FileItem, WalkOptions};
+use crate::size_cache::{self, SizeCache};
+use crate::utils::{describe_roots, format_size_as, interrupt_flag, note_coverage, note_skipped, note_throughput, parse_size, scan_roots, truncate_path};
+use crate::color::outln_to;
 
-pub async fn run(path: Option<String>, top: usize) -> Result<()> {
-    let target_path = path.unwrap_or_else(|| ".".to_string());
-    let path = Path::new(&target_path);
+/// What `list` shows and how it measures, as given on the command line.
+pub struct ListOptions {
+    pub top: usize,
+    pub sort: SortKey,
+    pub reverse: bool,
+    pub depth: usize,
+    /// Leave out directories holding fewer files than this
+    pub min_count: usize,
+    /// Fold entries smaller than this size into one row
+    pub collapse_below: Option<String>,
+    /// Show paths relative to the listed path when there is only one
+    pub relative: bool,
+    pub count_hardlinks: bool,
+    pub use_cache: bool,
+    pub allocated: bool,
+    pub raw_bytes: bool,
+}
 
-    println!("Analyzing directory: {}", path.display());
-    println!("Finding top {} largest items...\n", top);
+pub async fn run(paths: Vec<String>, options: ListOptions, walk: WalkOptions, format: OutputFormat, out: &mut dyn Write) -> Result<()> {
+    let ListOptions { top, sort, reverse, depth, min_count, collapse_below, relative, count_hardlinks, use_cache, allocated, raw_bytes } = options;
+    let roots = scan_roots(paths);
+    let mut collapse_below = collapse_below.map(|size| parse_size(&size)).transpose()?;
+    // A summary row has no place in csv, ndjson or plain output
+    if collapse_below.is_some() && format != OutputFormat::Table {
+        warn!("--collapse-below only applies to the table format; listing every item");
+        collapse_below = None;
+    }
+    // With several roots a relative path wouldn't say which one it is under
+    let relative_root = match roots.as_slice() {
+        [root] if relative => Some(root.clone()),
+        _ => None,
+    };
+    if relative && relative_root.is_none() {
+        warn!("--relative only applies when listing a single path; showing full paths");
+    }
+
+    if format == OutputFormat::Table {
+        outln_to!(out, "Analyzing: {}", describe_roots(&roots))?;
+        if walk.follow_symlinks {
+            outln_to!(out, "Following symbolic links")?;
+        }
+        if sort == SortKey::Size && !reverse {
+            outln_to!(out, "Finding top {} largest items...\n", top)?;
+        } else {
+            outln_to!(out, "Listing {} items sorted by {:?}{}...\n", top, sort, if reverse { " (reversed)" } else { "" })?;
+        }
+    }
+
+    let started = Instant::now();
+    let mut analyzer = DirAnalyzer::new();
+    analyzer.cancel = interrupt_flag();
+    analyzer.allocated = allocated;
+    analyzer.count_hardlinks = count_hardlinks;
+    analyzer.walk = walk;
+    let cache_path = if use_cache { Some(size_cache::cache_path()?) } else { None };
+    if let Some(cache_path) = &cache_path {
+        analyzer.cache = Some(Arc::new(Mutex::new(SizeCache::load_from(cache_path))));
+    }
+
+    let scans = scan_each(&roots, |root| {
+        let analyzer = analyzer.clone();
+        async move { analyzer.analyze_directory(&root, depth).await }
+    })
+    .await?;
+    let mut results: Vec<FileItem> = scans.into_iter().flatten().collect();
+    note_skipped(&analyzer.skipped);
+    note_throughput(&analyzer.scanned, started);
 
-    let analyzer = DirAnalyzer::new();
-    let results = analyzer.analyze_directory(path, true).await?;
+    if let (Some(cache), Some(cache_path)) = (&analyzer.cache, &cache_path) {
+        cache.lock().unwrap().save_to(cache_path)?;
+    }
+    keep_min_count(&mut results, min_count);
+    sort_items(&mut results, sort, reverse);
+    let analyzed_count = results.len();
+    note_coverage(&analyzer.scanned, analyzed_count);
+    let analyzed_size = total_size(&results);
+    let collapsed = collapse_below.and_then(|threshold| collapse_small(&mut results, threshold));
+    results.truncate(top);
+    if relative_root.is_some() {
+        for item in &mut results {
+            item.path = relative_to(&item.path, relative_root.as_deref()).to_path_buf();
+        }
+    }
+
+    match format {
+        OutputFormat::Csv => return write_csv(out, &results),
+        OutputFormat::Ndjson => return results.iter().try_for_each(|item| write_ndjson(&mut *out, item)),
+        OutputFormat::Plain => return write_plain(out, &results),
+        OutputFormat::Table => {}
+    }
 
-    println!("{:<50} {:>15} {:>10}", "Path", "Size", "Items");
-    println!("{:-<75}", "");
+    outln_to!(out, "{:<50} {:>15} {:>10}", "Path", "Size", "Files")?;
+    outln_to!(out, "{:-<75}", "")?;
 
-    for item in results.iter().take(top) {
-        println!(
+    for item in &results {
+        outln_to!(
+            out,
             "{:<50} {:>15} {:>10}",
-            if item.path.to_string_lossy().len() > 47 {
-                format!("...{}", &item.path.to_string_lossy()[item.path.to_string_lossy().len()-44..])
+            truncate_path(&item.path, 47),
+            format_size_as(item.size, raw_bytes),
+            if item.is_dir {
+                item.item_count.unwrap_or(0).to_string()
             } else {
-                item.path.to_string_lossy().to_string()
-            },
-            format_size(item.size),
-            item.item_count.unwrap_or(0)
-        );
+                "-".to_string()
+            }
+        )?;
+    }
+
+    if let Some(collapsed) = &collapsed {
+        outln_to!(
+            out,
+            "{:<50} {:>15} {:>10}",
+            format!("({} smaller items)", collapsed.count),
+            format_size_as(collapsed.size, raw_bytes),
+            collapsed.files
+        )?;
+    }
+
+    outln_to!(out, "{:-<75}", "")?;
+    outln_to!(out, "Total: {} items, {}", analyzed_count, format_size_as(analyzed_size, raw_bytes))?;
+    if results.len() < analyzed_count {
+        outln_to!(out, "Shown: {} items, {}", results.len(), format_size_as(total_size(&results), raw_bytes))?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// `path` relative to `root` for display, or `path` itself when there is no
+/// root or it lies outside of it.
+fn relative_to<'a>(path: &'a Path, root: Option<&Path>) -> &'a Path {
+    root.and_then(|root| path.strip_prefix(root).ok())
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .unwrap_or(path)
+}
+
+/// The entries `--collapse-below` folded into one summary row.
+#[derive(Debug, PartialEq)]
+struct Collapsed {
+    count: usize,
+    size: u64,
+    /// Files inside the collapsed directories plus the collapsed files themselves
+    files: usize,
+}
+
+/// Removes the items smaller than `threshold` and sums them up, or returns
+/// `None` when nothing was that small. With `--depth` above 1, small items
+/// inside a kept directory are already counted in its row and left out of
+/// the sum, as are small items inside another small one.
+fn collapse_small(items: &mut Vec<FileItem>, threshold: u64) -> Option<Collapsed> {
+    let (kept, small): (Vec<_>, Vec<_>) = items.drain(..).partition(|item| item.size >= threshold);
+    let kept_paths: HashSet<&Path> = kept.iter().map(|item| item.path.as_path()).collect();
+    let small: Vec<FileItem> = small
+        .into_iter()
+        .filter(|item| !item.path.ancestors().skip(1).any(|ancestor| kept_paths.contains(ancestor)))
+        .collect();
+    *items = kept;
+    if small.is_empty() {
+        return None;
+    }
+    Some(Collapsed {
+        count: small.len(),
+        size: total_size(&small),
+        files: outermost(&small).map(|item| item.item_count.unwrap_or(1)).sum(),
+    })
+}
+
+/// Drops directories holding fewer than `min_count` files; files stay.
+fn keep_min_count(items: &mut Vec<FileItem>, min_count: usize) {
+    items.retain(|item| item.item_count.is_none_or(|count| count >= min_count));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::output::create_output;
+    use std::fs;
+
+    /// Top 10 by apparent size, one level deep
+    fn options() -> ListOptions {
+        ListOptions {
+            top: 10,
+            sort: SortKey::Size,
+            reverse: false,
+            depth: 1,
+            min_count: 0,
+            collapse_below: None,
+            relative: false,
+            count_hardlinks: false,
+            use_cache: false,
+            allocated: false,
+            raw_bytes: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_total_matches_fixture_contents() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("a/b")).unwrap();
+        fs::write(root.path().join("a/b/one.bin"), vec![0u8; 300]).unwrap();
+        fs::write(root.path().join("a/two.bin"), vec![0u8; 200]).unwrap();
+        fs::write(root.path().join("three.bin"), vec![0u8; 50]).unwrap();
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        for depth in 1..=3 {
+            let items = analyzer.analyze_directory(root.path(), depth).await.unwrap();
+            assert_eq!(total_size(&items), 550, "depth {}", depth);
+        }
+
+        let mut items = analyzer.analyze_directory(root.path(), 2).await.unwrap();
+        sort_items(&mut items, SortKey::Size, false);
+        items.truncate(2);
+        // a (500) and its child a/b (300): only a counts
+        assert_eq!(total_size(&items), 500);
+    }
+
+    #[tokio::test]
+    async fn test_output_file_written() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("a")).unwrap();
+        fs::write(root.path().join("a/one.bin"), vec![0u8; 300]).unwrap();
+        fs::write(root.path().join("two.bin"), vec![0u8; 50]).unwrap();
+
+        let reports = tempfile::tempdir().unwrap();
+        let report = reports.path().join("nightly/list.csv");
+        let mut out = create_output(&report).unwrap();
+        let paths = vec![root.path().to_string_lossy().into_owned()];
+        run(paths, ListOptions { raw_bytes: true, ..options() }, WalkOptions::default(), OutputFormat::Csv, &mut out)
+            .await
+            .unwrap();
+        drop(out);
+
+        let lines: Vec<String> = fs::read_to_string(&report).unwrap().lines().map(String::from).collect();
+        assert_eq!(lines, vec![
+            "path,size_bytes,item_count,is_dir".to_string(),
+            format!("{},300,1,true", root.path().join("a").display()),
+            format!("{},50,,false", root.path().join("two.bin").display()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_plain_output_untruncated_and_tab_separated() {
+        let root = tempfile::tempdir().unwrap();
+        let long = root.path().join("a_directory_name_that_is_long_enough_to_be_truncated_in_the_table_view");
+        fs::create_dir(&long).unwrap();
+        fs::write(long.join("one.bin"), vec![0u8; 300]).unwrap();
+        fs::write(root.path().join("two.bin"), vec![0u8; 50]).unwrap();
+
+        let mut out = Vec::new();
+        let paths = vec![root.path().to_string_lossy().into_owned()];
+        run(paths, options(), WalkOptions::default(), OutputFormat::Plain, &mut out)
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<Vec<&str>> = output.lines().map(|line| line.split('\t').collect()).collect();
+        assert!(long.to_string_lossy().len() > 60);
+        assert_eq!(lines, vec![
+            vec![long.to_str().unwrap(), "300", "1"],
+            vec![root.path().join("two.bin").to_str().unwrap(), "50", ""],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_collapse_below_keeps_every_item_outside_table() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("big.bin"), vec![0u8; 300]).unwrap();
+        fs::write(root.path().join("small.bin"), vec![0u8; 50]).unwrap();
+
+        let mut out = Vec::new();
+        let paths = vec![root.path().to_string_lossy().into_owned()];
+        let options = ListOptions { collapse_below: Some("100".to_string()), ..options() };
+        run(paths, options, WalkOptions::default(), OutputFormat::Plain, &mut out)
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.contains("small.bin"));
+        assert!(!output.contains("smaller items"));
+    }
+
+    #[tokio::test]
+    async fn test_relative_applies_to_csv() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("a")).unwrap();
+        fs::write(root.path().join("a/one.bin"), vec![0u8; 300]).unwrap();
+
+        let mut out = Vec::new();
+        let paths = vec![root.path().to_string_lossy().into_owned()];
+        run(paths, ListOptions { relative: true, ..options() }, WalkOptions::default(), OutputFormat::Csv, &mut out)
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert_eq!(output.lines().nth(1), Some("a,300,1,true"));
+    }
+
+    #[test]
+    fn test_min_count_drops_sparse_dirs() {
+        let mut items = vec![
+            FileItem::test("sparse", 1000, Some(5)),
+            FileItem::test("node_modules", 1000, Some(50)),
+            FileItem::test("disk.img", 1000, None),
+        ];
+
+        keep_min_count(&mut items, 10);
+        let paths: Vec<_> = items.iter().map(|item| item.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["node_modules", "disk.img"]);
+    }
+
+    #[test]
+    fn test_collapse_below_sums_small_entries() {
+        let mut items = vec![
+            FileItem::test("target", 5000, Some(40)),
+            FileItem::test(".git", 300, Some(12)),
+            FileItem::test("README.md", 20, None),
+            FileItem::test("src", 1000, Some(8)),
+            FileItem::test(".env", 5, None),
+        ];
+
+        let collapsed = collapse_small(&mut items, 1000);
+        assert_eq!(collapsed, Some(Collapsed { count: 3, size: 325, files: 14 }));
+        let paths: Vec<_> = items.iter().map(|item| item.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["target", "src"]);
+        assert_eq!(collapse_small(&mut items, 1000), None);
+    }
+
+    #[test]
+    fn test_collapse_below_leaves_out_children_of_kept_rows() {
+        let mut items = vec![
+            FileItem::test("target", 5000, Some(40)),
+            FileItem::test("target/debug.log", 200, None),
+            FileItem::test("docs", 300, Some(3)),
+            FileItem::test("docs/notes.md", 100, None),
+            FileItem::test("README.md", 20, None),
+        ];
+
+        let collapsed = collapse_small(&mut items, 1000);
+        assert_eq!(collapsed, Some(Collapsed { count: 3, size: 320, files: 4 }));
+        assert_eq!(items.len(), 1);
+    }
+
+    #[test]
+    fn test_relative_paths_stripped_below_root() {
+        let root = Path::new("/home/me/projects/foo");
+        assert_eq!(relative_to(Path::new("/home/me/projects/foo/target"), Some(root)), Path::new("target"));
+        assert_eq!(relative_to(Path::new("/home/me/projects/foo/src/main.rs"), Some(root)), Path::new("src/main.rs"));
+        assert_eq!(relative_to(Path::new("/home/me/projects/foobar"), Some(root)), Path::new("/home/me/projects/foobar"));
+        assert_eq!(relative_to(Path::new("/var/log"), Some(root)), Path::new("/var/log"));
+        assert_eq!(relative_to(root, Some(root)), root);
+        assert_eq!(relative_to(Path::new("/home/me/projects/foo/target"), None), Path::new("/home/me/projects/foo/target"));
+        assert_eq!(relative_to(Path::new("./target"), Some(Path::new("."))), Path::new("target"));
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use crate::discovery::FileItem;
+
+/// How scan results are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned, human-readable table
+    Table,
+    /// Comma-separated values with raw byte sizes
+    Csv,
+    /// One JSON object per line; `large` prints files as it finds them, unsorted
+    Ndjson,
+    /// Full path, byte size and file count separated by tabs, without headers, for scripts
+    Plain,
+}
+
+/// Order of `list` results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// Largest first
+    Size,
+    /// Alphabetical by path
+    Name,
+    /// Most files first
+    Count,
+    /// Most recently modified first
+    Mtime,
+}
+
+/// Sorts `items` by `key` in its natural direction, or the opposite one with `reverse`.
+pub fn sort_items(items: &mut [FileItem], key: SortKey, reverse: bool) {
+    // Ties are broken by path, so the order doesn't depend on which scan
+    // finished first
+    items.sort_by(|a, b| {
+        let order = match key {
+            SortKey::Size => b.size.cmp(&a.size),
+            SortKey::Name => std::cmp::Ordering::Equal,
+            SortKey::Count => b.item_count.unwrap_or(0).cmp(&a.item_count.unwrap_or(0)),
+            SortKey::Mtime => b.mtime.cmp(&a.mtime),
+        };
+        order.then_with(|| a.path.cmp(&b.path))
+    });
+    if reverse {
+        items.reverse();
+    }
+}
+
+/// Keeps the first `top` of `items`, or all of them for 0, and returns how
+/// many there were before.
+pub fn take_top<T>(items: &mut Vec<T>, top: usize) -> usize {
+    let total = items.len();
+    if top > 0 {
+        items.truncate(top);
+    }
+    total
+}
+
+/// Footer note for output capped by `--top`; empty when nothing was left out.
+pub fn showing(shown: usize, found: usize) -> String {
+    if shown < found {
+        format!(" (showing {} of {})", shown, found)
+    } else {
+        String::new()
+    }
+}
+
+/// Creates the `--output` file, along with any missing parent directories.
+pub fn create_output(path: &Path) -> Result<BufWriter<File>> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+/// Writes `items` as CSV with a `path,size_bytes,item_count,is_dir` header.
+/// Sizes are raw bytes so the column sorts numerically in spreadsheets.
+pub fn write_csv<W: Write>(writer: W, items: &[FileItem]) -> Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(["path", "size_bytes", "item_count", "is_dir"])?;
+
+    for item in items {
+        let item_count = item.item_count.map(|count| count.to_string()).unwrap_or_default();
+        csv.write_record([
+            item.path.to_string_lossy().as_ref(),
+            item.size.to_string().as_str(),
+            item_count.as_str(),
+            if item.is_dir { "true" } else { "false" },
+        ])?;
+    }
+
+    csv.flush()?;
+    Ok(())
+}
+
+/// Writes one `path<TAB>size_bytes<TAB>item_count` line per item, with the
+/// path in full and an empty count for files.
+pub fn write_plain<W: Write>(mut writer: W, items: &[FileItem]) -> Result<()> {
+    for item in items {
+        let item_count = item.item_count.map(|count| count.to_string()).unwrap_or_default();
+        writeln!(writer, "{}\t{}\t{}", item.path.display(), item.size, item_count)?;
+    }
+    Ok(())
+}
+
+/// Writes `item` as one line of JSON with the same fields as the CSV output,
+/// plus `mtime` in seconds since the Unix epoch.
+pub fn write_ndjson<W: Write>(mut writer: W, item: &FileItem) -> Result<()> {
+    let mtime = item.mtime.duration_since(UNIX_EPOCH).map(|age| age.as_secs()).unwrap_or(0);
+    let line = serde_json::json!({
+        "path": item.path.to_string_lossy(),
+        "size_bytes": item.size,
+        "item_count": item.item_count,
+        "is_dir": item.is_dir,
+        "mtime": mtime,
+    });
+    writeln!(writer, "{}", line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_csv_round_trip_quotes_commas() {
+        let items = vec![
+            FileItem::test("/data/a,b", 1536, Some(3)),
+            FileItem::test("/data/file.bin", 42, None),
+        ];
+
+        let mut buffer = Vec::new();
+        write_csv(&mut buffer, &items).unwrap();
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert!(text.starts_with("path,size_bytes,item_count,is_dir\n"));
+        assert!(text.contains("\"/data/a,b\",1536,3,true"));
+
+        let mut reader = csv::Reader::from_reader(buffer.as_slice());
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][0], "/data/a,b");
+        assert_eq!(&rows[1][1], "42");
+        assert_eq!(&rows[1][2], "");
+        assert_eq!(&rows[1][3], "false");
+    }
+
+    fn sample_items() -> Vec<FileItem> {
+        [("b", 300, 1, 20), ("c", 100, 9, 30), ("a", 200, 5, 10)]
+            .into_iter()
+            .map(|(name, size, count, age)| FileItem {
+                mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(1000 - age),
+                ..FileItem::test(name, size, Some(count))
+            })
+            .collect()
+    }
+
+    fn names(items: &[FileItem]) -> Vec<String> {
+        items.iter().map(|item| item.path.to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let cases = [
+            (SortKey::Size, ["b", "a", "c"]),
+            (SortKey::Name, ["a", "b", "c"]),
+            (SortKey::Count, ["c", "a", "b"]),
+            (SortKey::Mtime, ["a", "b", "c"]),
+        ];
+
+        for (key, expected) in cases {
+            let mut items = sample_items();
+            sort_items(&mut items, key, false);
+            assert_eq!(names(&items), expected, "{:?}", key);
+
+            sort_items(&mut items, key, true);
+            let mut reversed = expected.to_vec();
+            reversed.reverse();
+            assert_eq!(names(&items), reversed, "{:?} reversed", key);
+        }
+    }
+}
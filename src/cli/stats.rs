@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use crate::utils::format_size_as;
+use crate::color::outln;
+
+/// Space on the filesystem holding a path, in bytes.
+#[derive(Debug)]
+struct FsUsage {
+    total: u64,
+    used: u64,
+    available: u64,
+}
+
+impl FsUsage {
+    /// Share of the space usable by unprivileged users that is taken, in
+    /// percent; like `df`, blocks reserved for root are left out.
+    fn used_percent(&self) -> f64 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            0.0
+        } else {
+            self.used as f64 * 100.0 / usable as f64
+        }
+    }
+}
+
+pub async fn run(path: Option<String>, raw_bytes: bool) -> Result<()> {
+    let target_path = path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&target_path);
+    let usage = filesystem_usage(path)?;
+
+    outln!("Filesystem usage for: {}", path.display());
+    outln!("   Total:     {}", format_size_as(usage.total, raw_bytes));
+    outln!("   Used:      {} ({:.1}%)", format_size_as(usage.used, raw_bytes), usage.used_percent());
+    outln!("   Available: {}", format_size_as(usage.available, raw_bytes));
+
+    Ok(())
+}
+
+/// Queries the filesystem holding `path`. `available` is what an unprivileged
+/// user can still write, so it can be less than `total - used`.
+#[cfg(unix)]
+fn filesystem_usage(path: &Path) -> Result<FsUsage> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is a
+    // properly sized, writable statvfs struct.
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(anyhow!("Cannot read filesystem usage of {}: {}", path.display(), std::io::Error::last_os_error()));
+    }
+
+    let fragment = stat.f_frsize as u64;
+    Ok(FsUsage {
+        total: stat.f_blocks as u64 * fragment,
+        used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * fragment,
+        available: stat.f_bavail as u64 * fragment,
+    })
+}
+
+#[cfg(not(unix))]
+fn filesystem_usage(path: &Path) -> Result<FsUsage> {
+    Err(anyhow!("Filesystem usage is not supported on this platform ({})", path.display()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_of_current_dir_is_consistent() {
+        let usage = filesystem_usage(Path::new(".")).unwrap();
+        assert!(usage.total > 0);
+        assert!(usage.total >= usage.used);
+        assert!(usage.available <= usage.total);
+        assert!((0.0..=100.0).contains(&usage.used_percent()));
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::cli::output::{sort_items, SortKey};
+use crate::discovery::{DirAnalyzer, FileItem, WalkOptions};
+use crate::utils::{format_size_as, interrupt_flag, note_skipped, note_throughput, parse_size};
+use crate::color::outln;
+
+/// One printed row of the tree: a directory, or the "(other)" aggregate of
+/// the files and small directories next to it.
+#[derive(Debug, PartialEq)]
+struct TreeLine {
+    level: usize,
+    name: String,
+    size: u64,
+}
+
+/// Work left on the depth-first walk, popped in print order.
+enum Pending {
+    Dir { path: PathBuf, level: usize, size: u64 },
+    Other { level: usize, size: u64 },
+}
+
+pub async fn run(path: Option<String>, depth: usize, threshold: Option<String>, walk: WalkOptions, raw_bytes: bool) -> Result<()> {
+    let target_path = path.unwrap_or_else(|| ".".to_string());
+    let threshold = match threshold {
+        Some(threshold) => parse_size(&threshold)?,
+        None => 0,
+    };
+
+    let started = Instant::now();
+    let mut analyzer = DirAnalyzer::new();
+    analyzer.cancel = interrupt_flag();
+    analyzer.walk = walk;
+
+    for line in build_tree(&analyzer, Path::new(&target_path), depth, threshold).await? {
+        outln!("{}", render(&line, raw_bytes));
+    }
+    note_skipped(&analyzer.skipped);
+    note_throughput(&analyzer.scanned, started);
+
+    Ok(())
+}
+
+fn render(line: &TreeLine, raw_bytes: bool) -> String {
+    format!("{:>12}  {}{}", format_size_as(line.size, raw_bytes), "  ".repeat(line.level), line.name)
+}
+
+/// Walks `root` down to `max_depth` levels, like `du --max-depth`. Children
+/// are sorted by size; files and directories smaller than `threshold` are
+/// summed into one "(other)" line per directory. The whole tree comes from a
+/// single scan, so every entry is walked once whatever the depth.
+async fn build_tree(analyzer: &DirAnalyzer, root: &Path, max_depth: usize, threshold: u64) -> Result<Vec<TreeLine>> {
+    let mut by_parent: HashMap<PathBuf, Vec<FileItem>> = HashMap::new();
+    for item in analyzer.analyze_directory(root, max_depth.max(1)).await? {
+        let parent = item.path.parent().map(Path::to_owned).unwrap_or_default();
+        by_parent.entry(parent).or_default().push(item);
+    }
+
+    let (total, top) = children(&mut by_parent, root, 1, threshold);
+    let mut lines = vec![TreeLine { level: 0, name: root.display().to_string(), size: total }];
+    let mut stack = if max_depth > 0 { top } else { Vec::new() };
+
+    while let Some(pending) = stack.pop() {
+        match pending {
+            Pending::Dir { path, level, size } => {
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                lines.push(TreeLine { level, name, size });
+                if level < max_depth {
+                    stack.extend(children(&mut by_parent, &path, level + 1, threshold).1);
+                }
+            }
+            Pending::Other { level, size } => {
+                lines.push(TreeLine { level, name: "(other)".to_string(), size });
+            }
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Takes the scanned entries of `path` out of `by_parent` and returns their
+/// total along with the work to print them at `level`, in stack order (last
+/// entry is printed first).
+fn children(by_parent: &mut HashMap<PathBuf, Vec<FileItem>>, path: &Path, level: usize, threshold: u64) -> (u64, Vec<Pending>) {
+    let mut items = by_parent.remove(path).unwrap_or_default();
+    sort_items(&mut items, SortKey::Size, false);
+
+    let total = items.iter().map(|item| item.size).sum();
+    let (shown, rest): (Vec<_>, Vec<_>) = items.into_iter().partition(|item| item.is_dir && item.size >= threshold);
+
+    let mut pending = Vec::new();
+    if !rest.is_empty() {
+        pending.push(Pending::Other { level, size: rest.iter().map(|item| item.size).sum() });
+    }
+    pending.extend(shown.into_iter().rev().map(|item| Pending::Dir { path: item.path, level, size: item.size }));
+
+    (total, pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_two_level_tree() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("a/x")).unwrap();
+        fs::create_dir_all(root.path().join("b")).unwrap();
+        fs::write(root.path().join("a/x/f1"), vec![0u8; 1000]).unwrap();
+        fs::write(root.path().join("a/f2"), vec![0u8; 200]).unwrap();
+        fs::write(root.path().join("b/f3"), vec![0u8; 50]).unwrap();
+        fs::write(root.path().join("c.txt"), vec![0u8; 5]).unwrap();
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        let lines = build_tree(&analyzer, root.path(), 2, 100).await.unwrap();
+
+        let summary: Vec<_> = lines.iter().map(|l| (l.level, l.name.as_str(), l.size)).skip(1).collect();
+        assert_eq!(lines[0].level, 0);
+        assert_eq!(lines[0].size, 1255);
+        assert_eq!(summary, vec![
+            (1, "a", 1200),
+            (2, "x", 1000),
+            (2, "(other)", 200),
+            (1, "(other)", 55),
+        ]);
+        assert!(render(&lines[2], true).ends_with("1000      x"));
+        // a, a/x, a/x/f1, a/f2, b, b/f3 and c.txt, each walked once
+        assert_eq!(analyzer.scanned.load(Ordering::Relaxed), 7);
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::discovery::{DirAnalyzer, FileItem, WalkOptions};
+use crate::utils::{format_size_as, interrupt_flag, note_skipped};
+use crate::color::outln;
+
+/// Sizes of the top-level entries from one scan, keyed by path.
+type Snapshot = HashMap<PathBuf, u64>;
+
+/// Re-scans `path` every `interval` and prints how much each top-level entry
+/// grew or shrank since the previous scan, until interrupted with Ctrl-C.
+pub async fn run(path: Option<String>, interval: Duration, allocated: bool, walk: WalkOptions, raw_bytes: bool) -> Result<()> {
+    let target_path = path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&target_path);
+    if interval.is_zero() {
+        bail!("--interval must be longer than 0s");
+    }
+
+    let mut analyzer = DirAnalyzer::new();
+    analyzer.cancel = interrupt_flag();
+    analyzer.allocated = allocated;
+    analyzer.walk = walk;
+
+    outln!("Watching: {} every {}s (Ctrl-C to stop)\n", path.display(), interval.as_secs());
+    let mut previous = snapshot(&analyzer.analyze_directory(path, 1).await?);
+    outln!("{} entries, {}", previous.len(), format_size_as(previous.values().sum(), raw_bytes));
+
+    loop {
+        tokio::time::sleep(interval).await;
+        let current = snapshot(&analyzer.analyze_directory(path, 1).await?);
+        let changes = size_changes(&previous, &current);
+
+        outln!("\n--- {} ---", chrono::Local::now().format("%H:%M:%S"));
+        if changes.is_empty() {
+            outln!("No changes");
+        }
+        for (path, delta) in &changes {
+            outln!("{:<60} {:>15}", path.display(), format_delta(*delta, raw_bytes));
+        }
+        note_skipped(&analyzer.skipped);
+        previous = current;
+    }
+}
+
+fn snapshot(items: &[FileItem]) -> Snapshot {
+    items.iter().map(|item| (item.path.clone(), item.size)).collect()
+}
+
+/// How much each path grew (positive) or shrank (negative) between two
+/// snapshots, fastest growing first. New paths count from 0 and vanished ones
+/// shrink to 0; unchanged paths are left out.
+fn size_changes(previous: &Snapshot, current: &Snapshot) -> Vec<(PathBuf, i64)> {
+    let mut changes: Vec<(PathBuf, i64)> = current
+        .iter()
+        .map(|(path, &size)| (path.clone(), size as i64 - previous.get(path).copied().unwrap_or(0) as i64))
+        .chain(
+            previous
+                .iter()
+                .filter(|(path, _)| !current.contains_key(*path))
+                .map(|(path, &size)| (path.clone(), -(size as i64))),
+        )
+        .filter(|(_, delta)| *delta != 0)
+        .collect();
+    changes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    changes
+}
+
+/// "+340.0 MiB" or "-2.0 KiB".
+fn format_delta(delta: i64, raw_bytes: bool) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, format_size_as(delta.unsigned_abs(), raw_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_of(entries: &[(&str, u64)]) -> Snapshot {
+        entries.iter().map(|&(path, size)| (PathBuf::from(path), size)).collect()
+    }
+
+    #[test]
+    fn test_size_changes_between_snapshots() {
+        let previous = snapshot_of(&[("target", 1000), ("src", 50), ("logs", 300), ("old.tmp", 20)]);
+        let current = snapshot_of(&[("target", 4000), ("src", 50), ("logs", 100), ("dist", 700)]);
+
+        let changes = size_changes(&previous, &current);
+        let summary: Vec<_> = changes.iter().map(|(path, delta)| (path.to_str().unwrap(), *delta)).collect();
+        assert_eq!(summary, [("target", 3000), ("dist", 700), ("old.tmp", -20), ("logs", -200)]);
+        assert_eq!(format_delta(3000, true), "+3000");
+        assert_eq!(format_delta(-200, true), "-200");
+    }
+}
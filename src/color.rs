@@ -0,0 +1,173 @@
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+static NO_COLOR: OnceLock<bool> = OnceLock::new();
+static PROSE_TO_STDERR: OnceLock<bool> = OnceLock::new();
+
+/// Turns off colors and emoji for the rest of the run when `--no-color` was
+/// passed or the `NO_COLOR` environment variable is set.
+pub fn set_no_color(flag: bool) {
+    let _ = NO_COLOR.set(requested(flag, std::env::var_os("NO_COLOR")));
+}
+
+pub fn no_color() -> bool {
+    NO_COLOR.get().copied().unwrap_or(false)
+}
+
+/// Sends [`outln!`] output and status messages to stderr for the rest of the
+/// run, keeping stdout free for machine-readable output such as `--format json`.
+pub fn set_prose_to_stderr(flag: bool) {
+    let _ = PROSE_TO_STDERR.set(flag);
+}
+
+pub fn prose_to_stderr() -> bool {
+    PROSE_TO_STDERR.get().copied().unwrap_or(false)
+}
+
+/// Where [`outln!`] output goes, for code that writes through `outln_to!`.
+pub fn prose_writer() -> Box<dyn Write> {
+    if prose_to_stderr() {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    }
+}
+
+/// Plain output is wanted for the flag or a non-empty `NO_COLOR`, as
+/// described at https://no-color.org.
+fn requested(flag: bool, env: Option<OsString>) -> bool {
+    flag || env.is_some_and(|value| !value.is_empty())
+}
+
+/// Returns `text` unchanged, or stripped with [`plain`] when colors are off.
+pub fn styled(text: &str) -> Cow<'_, str> {
+    if no_color() {
+        Cow::Owned(plain(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Removes ANSI escape sequences and emoji (with the spaces that separated
+/// them from the text) so the line reads well in CI logs and light terminals.
+pub fn plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences run up to a final byte in '@'..='~'
+            if chars.next_if_eq(&'[').is_some() {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else if is_emoji(c) {
+            while chars.next_if(|&c| is_emoji(c) || c == ' ').is_some() {}
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, transport, symbols
+        | 0x2600..=0x27BF // misc symbols and dingbats (✅ ❌ ⚠)
+        | 0x2B00..=0x2BFF
+        | 0xFE0F          // emoji presentation selector
+        | 0x200D          // zero width joiner
+    )
+}
+
+/// Writer that passes everything through [`plain`], for log output.
+pub struct PlainWriter<W>(pub W);
+
+impl<W: Write> Write for PlainWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_all(plain(&String::from_utf8_lossy(buf)).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// `println!` that honors `--no-color` and moves to stderr when stdout is
+/// reserved for machine-readable output.
+macro_rules! outln {
+    () => {
+        $crate::color::outln!("")
+    };
+    ($($arg:tt)*) => {
+        if $crate::color::prose_to_stderr() {
+            eprintln!("{}", $crate::color::styled(&format!($($arg)*)))
+        } else {
+            println!("{}", $crate::color::styled(&format!($($arg)*)))
+        }
+    };
+}
+
+/// [`outln!`] into an explicit writer, such as the `--output` file.
+/// Evaluates to an `io::Result`.
+macro_rules! outln_to {
+    ($out:expr) => {
+        writeln!($out)
+    };
+    ($out:expr, $($arg:tt)*) => {
+        writeln!($out, "{}", $crate::color::styled(&format!($($arg)*)))
+    };
+}
+
+/// `eprintln!` that honors `--no-color`.
+macro_rules! eoutln {
+    ($($arg:tt)*) => {
+        eprintln!("{}", $crate::color::styled(&format!($($arg)*)))
+    };
+}
+
+pub(crate) use {eoutln, outln, outln_to};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_color_env() {
+        assert!(!requested(false, None));
+        assert!(!requested(false, Some(OsString::new())));
+        assert!(requested(false, Some(OsString::from("1"))));
+        assert!(requested(true, None));
+    }
+
+    #[test]
+    fn test_plain_strips_escapes_and_emoji() {
+        let lines = [
+            "\n📊 Cleanup Summary:",
+            "   ✅ Removed: ./app/node_modules",
+            "🛠️  Development Artifacts Cleanup",
+            "⚠️  Trash is not supported on this platform",
+            "\x1b[1;36mbold cyan\x1b[0m text",
+        ];
+        let plain: Vec<String> = lines.iter().map(|line| plain(line)).collect();
+        assert_eq!(plain[0], "\nCleanup Summary:");
+        assert_eq!(plain[1], "   Removed: ./app/node_modules");
+        assert_eq!(plain[2], "Development Artifacts Cleanup");
+        assert_eq!(plain[4], "bold cyan text");
+        for line in &plain {
+            assert!(!line.contains('\x1b'));
+            assert!(!line.chars().any(is_emoji), "{:?}", line);
+        }
+
+        let mut out = PlainWriter(Vec::new());
+        let path = "x";
+        write!(out, "❌ Failed to remove {}", path).unwrap();
+        assert_eq!(String::from_utf8(out.0).unwrap(), "Failed to remove x");
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::cleanup::dev::DEFAULT_MIN_SIZE;
+use crate::safety::DEFAULT_IN_USE_WINDOW;
+
+/// Environment variable that points at an alternative config file.
+pub const CONFIG_ENV_VAR: &str = "SAFE_CLEAN_CONFIG";
+
+const DEFAULT_SIZE: &str = "100MB";
+const DEFAULT_TOP: usize = 20;
+const DEFAULT_CONFIRM_THRESHOLD: &str = "5GB";
+
+/// Defaults loaded from `config.toml`. Explicit command line flags always win.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Glob patterns excluded from every scan, in addition to `--exclude`
+    pub exclude: Vec<String>,
+    /// Large-file threshold used when `--size` is omitted
+    pub default_size: Option<String>,
+    /// Number of rows `list` shows when `--top` is omitted
+    pub default_top: Option<usize>,
+    /// Extra paths that must never be scanned or cleaned
+    pub protected_paths: Vec<PathBuf>,
+    /// Extra directory names treated as development artifacts
+    pub artifact_dirs: Vec<String>,
+    /// Seconds since the last write within which an item counts as in use
+    /// and is skipped by cleanups; 0 disables the check
+    pub in_use_window: Option<u64>,
+    /// Removal size above which cleanups ask to type "yes" instead of y/n
+    pub confirm_threshold: Option<String>,
+    /// Named sets of defaults under `[profiles.<name>]`, selected with `--profile`
+    pub profiles: BTreeMap<String, Profile>,
+    /// The profile chosen with `--profile`, consulted before the top-level keys
+    #[serde(skip)]
+    profile: Option<Profile>,
+}
+
+/// Defaults for one scan recipe. Anything left out falls back to the
+/// top-level config keys.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Glob patterns excluded in addition to the top-level `exclude`
+    pub exclude: Vec<String>,
+    /// Paths scanned by list, large and dev-clean when none are given
+    pub paths: Vec<String>,
+    /// Large-file threshold used when `--size` is omitted
+    pub default_size: Option<String>,
+    /// Number of rows `list` shows when `--top` is omitted
+    pub default_top: Option<usize>,
+    /// Smallest artifact dev-clean shows when `--min-size` is omitted
+    pub min_size: Option<String>,
+    /// Removal size above which cleanups ask to type "yes" instead of y/n
+    pub confirm_threshold: Option<String>,
+}
+
+impl Config {
+    /// Loads the config from `$SAFE_CLEAN_CONFIG` or the default location
+    /// (`~/.config/safe-clean/config.toml` on Linux). A missing default file
+    /// yields an empty config; a missing explicitly requested file is an error.
+    pub fn load() -> Result<Config> {
+        match std::env::var_os(CONFIG_ENV_VAR) {
+            Some(path) => Self::load_from(&PathBuf::from(path)),
+            None => match default_path() {
+                Some(path) if path.exists() => Self::load_from(&path),
+                _ => Ok(Config::default()),
+            },
+        }
+    }
+
+    fn load_from(path: &PathBuf) -> Result<Config> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Config> {
+        toml::from_str(contents).map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Makes the `[profiles.<name>]` defaults take precedence over the
+    /// top-level ones.
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self.profiles.get(name).cloned().ok_or_else(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                anyhow!("Unknown profile '{}': the config file defines no profiles", name)
+            } else {
+                anyhow!("Unknown profile '{}' (available: {})", name, known.join(", "))
+            }
+        })?;
+        self.profile = Some(profile);
+        Ok(())
+    }
+
+    fn profile(&self) -> Profile {
+        self.profile.clone().unwrap_or_default()
+    }
+
+    pub fn resolve_size(&self, cli_size: Option<String>) -> String {
+        cli_size
+            .or_else(|| self.profile().default_size)
+            .or_else(|| self.default_size.clone())
+            .unwrap_or_else(|| DEFAULT_SIZE.to_string())
+    }
+
+    pub fn resolve_top(&self, cli_top: Option<usize>) -> usize {
+        cli_top.or(self.profile().default_top).or(self.default_top).unwrap_or(DEFAULT_TOP)
+    }
+
+    pub fn resolve_confirm_threshold(&self, cli_threshold: Option<String>) -> String {
+        cli_threshold
+            .or_else(|| self.profile().confirm_threshold)
+            .or_else(|| self.confirm_threshold.clone())
+            .unwrap_or_else(|| DEFAULT_CONFIRM_THRESHOLD.to_string())
+    }
+
+    pub fn resolve_in_use_window(&self) -> Duration {
+        self.in_use_window.map(Duration::from_secs).unwrap_or(DEFAULT_IN_USE_WINDOW)
+    }
+
+    pub fn resolve_min_size(&self, cli_min_size: Option<String>) -> String {
+        cli_min_size
+            .or_else(|| self.profile().min_size)
+            .unwrap_or_else(|| DEFAULT_MIN_SIZE.to_string())
+    }
+
+    /// The paths given on the command line, or else the profile's.
+    pub fn resolve_paths(&self, cli_paths: Vec<String>) -> Vec<String> {
+        if cli_paths.is_empty() {
+            self.profile().paths
+        } else {
+            cli_paths
+        }
+    }
+
+    /// Config and profile excludes followed by the ones given on the command line.
+    pub fn resolve_exclude(&self, cli_exclude: Vec<String>) -> Vec<String> {
+        self.exclude.iter().cloned().chain(self.profile().exclude).chain(cli_exclude).collect()
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("safe-clean").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_size_applies_unless_overridden() {
+        let config = Config::parse("default_size = \"500MB\"").unwrap();
+        assert_eq!(config.resolve_size(None), "500MB");
+        assert_eq!(config.resolve_size(Some("1GB".to_string())), "1GB");
+        assert_eq!(Config::default().resolve_size(None), "100MB");
+    }
+
+    #[test]
+    fn test_confirm_threshold_default_and_override() {
+        let config = Config::parse("confirm_threshold = \"10GB\"").unwrap();
+        assert_eq!(config.resolve_confirm_threshold(None), "10GB");
+        assert_eq!(config.resolve_confirm_threshold(Some("1GB".to_string())), "1GB");
+        assert_eq!(Config::default().resolve_confirm_threshold(None), "5GB");
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let config = Config::parse(
+            r#"
+            exclude = ["*.git*", "**/Library"]
+            default_top = 5
+            protected_paths = ["/srv"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.resolve_top(None), 5);
+        assert_eq!(config.resolve_top(Some(8)), 8);
+        assert_eq!(
+            config.resolve_exclude(vec!["node_modules".to_string()]),
+            vec!["*.git*", "**/Library", "node_modules"]
+        );
+        assert_eq!(config.protected_paths, vec![PathBuf::from("/srv")]);
+        assert!(Config::parse("unknown_key = 1").is_err());
+    }
+
+    #[test]
+    fn test_profile_excludes_combine_with_cli() {
+        let mut config = Config::parse(
+            r#"
+            exclude = ["*.git*"]
+            default_size = "500MB"
+
+            [profiles.quick]
+            exclude = ["**/node_modules", "**/target"]
+            paths = ["/home/me/src"]
+            default_size = "1GB"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.resolve_exclude(Vec::new()), vec!["*.git*"]);
+
+        config.use_profile("quick").unwrap();
+        assert_eq!(config.resolve_exclude(Vec::new()), vec!["*.git*", "**/node_modules", "**/target"]);
+        assert_eq!(
+            config.resolve_exclude(vec!["*.log".to_string()]),
+            vec!["*.git*", "**/node_modules", "**/target", "*.log"]
+        );
+        assert_eq!(config.resolve_size(None), "1GB");
+        assert_eq!(config.resolve_size(Some("2GB".to_string())), "2GB");
+        assert_eq!(config.resolve_top(None), 20);
+        assert_eq!(config.resolve_paths(Vec::new()), vec!["/home/me/src"]);
+        assert_eq!(config.resolve_paths(vec![".".to_string()]), vec!["."]);
+        assert!(config.use_profile("aggressive").is_err());
+    }
+}
@@ -1,164 +1,2126 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::future::Future;
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
 use tokio::task;
-use walkdir::WalkDir;
+use walkdir::{DirEntry, FilterEntry, WalkDir};
+use crate::artifacts::{is_artifact_dir, project_kind, ArtifactSelection, ProjectKind};
+use crate::safety;
+use crate::size_cache::SizeCache;
 
 #[derive(Debug, Clone)]
 pub struct FileItem {
     pub path: PathBuf,
     pub size: u64,
+    /// Files below a directory at any depth, not counting subdirectories; `None` for files
     pub item_count: Option<usize>,
     pub is_dir: bool,
+    /// Last modification time, `UNIX_EPOCH` when unavailable
+    pub mtime: SystemTime,
+    /// Project type owning a development artifact; `None` for other items
+    pub project_kind: Option<ProjectKind>,
 }
 
-pub struct DirAnalyzer;
+#[cfg(test)]
+impl FileItem {
+    /// An item last modified at the epoch: a directory holding `item_count`
+    /// files, or a file when that is `None`.
+    pub fn test(path: impl Into<PathBuf>, size: u64, item_count: Option<usize>) -> FileItem {
+        FileItem {
+            path: path.into(),
+            size,
+            item_count,
+            is_dir: item_count.is_some(),
+            mtime: UNIX_EPOCH,
+            project_kind: None,
+        }
+    }
+}
+
+/// The items not nested inside another of `items`, whose sizes and counts
+/// already include everything below them.
+pub fn outermost(items: &[FileItem]) -> impl Iterator<Item = &FileItem> {
+    let paths: HashSet<&Path> = items.iter().map(|item| item.path.as_path()).collect();
+    items
+        .iter()
+        .filter(move |item| !item.path.ancestors().skip(1).any(|ancestor| paths.contains(ancestor)))
+}
+
+/// Sum of the item sizes, leaving out items nested inside another one so
+/// nothing is counted twice.
+pub fn total_size(items: &[FileItem]) -> u64 {
+    outermost(items).map(|item| item.size).sum()
+}
+
+#[derive(Clone)]
+pub struct DirAnalyzer {
+    /// How the tree is walked; `max_depth` is ignored in favor of the listing depth
+    pub walk: WalkOptions,
+    /// Report allocated disk usage instead of apparent length (see `file_size`)
+    pub allocated: bool,
+    /// Count every hardlink to a file instead of each inode once (Unix)
+    pub count_hardlinks: bool,
+    /// Reuses directory sizes from earlier runs (see `SizeCache`). Ignored
+    /// unless `walk` sees everything, since its options change what a size covers.
+    pub cache: Option<Arc<Mutex<SizeCache>>>,
+    /// Aborts the scan with an error when set
+    pub cancel: Arc<AtomicBool>,
+    /// Collects the entries the walk could not read
+    pub skipped: Skipped,
+    /// Counts the entries the walk visited, across all scans with this finder
+    pub scanned: Arc<AtomicUsize>,
+    /// Size children on the thread pool shared by every scan (see
+    /// [`set_jobs`]); off sizes them one after another
+    pub parallel: bool,
+}
 
 impl DirAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            walk: WalkOptions::default(),
+            allocated: cfg!(unix),
+            count_hardlinks: false,
+            parallel: true,
+            cache: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            skipped: Skipped::default(),
+            scanned: Arc::default(),
+        }
     }
 
-    pub async fn analyze_directory(&self, path: &Path, include_subdirs: bool) -> Result<Vec<FileItem>> {
+    /// Analyzes the entries of `path` down to `depth` levels; 1 means the
+    /// immediate children only. Deeper entries are listed alongside their
+    /// parents, so a parent's size already includes theirs.
+    ///
+    /// Each child directory is walked once, and the sizes of the directories
+    /// listed below it are summed up from that same walk, so every entry is
+    /// visited (and counted in `scanned`) once whatever the depth.
+    ///
+    /// With `parallel`, children are sized on a rayon thread pool of `--jobs`
+    /// threads shared by all scans, so wall-clock time scales down with the
+    /// number of cores when the tree has many large subdirectories.
+    /// Results come back unordered; callers sort them as they need.
+    pub async fn analyze_directory(&self, path: &Path, depth: usize) -> Result<Vec<FileItem>> {
+        safety::check_scan(path)?;
         let path = path.to_owned();
+        let walk = self.walk.clone();
+        let allocated = self.allocated;
+        let count_hardlinks = self.count_hardlinks;
+        let device = walk.device(&path);
+        let cache = self.cache.clone().filter(|_| walk.sees_everything());
+        let cancel = self.cancel.clone();
+        let skipped = self.skipped.clone();
+        let scanned = self.scanned.clone();
+        let pool = if self.parallel { job_pool() } else { None };
         
-        task::spawn_blocking(move || {
-            let mut items = Vec::new();
-            
-            if !path.exists() {
-                return Ok(items);
+        spawn_walk(move || {
+            if !path.exists() || depth == 0 {
+                return Ok(Vec::new());
             }
 
-            for entry in WalkDir::new(&path)
-                .max_depth(if include_subdirs { 1 } else { 0 })
-                .into_iter()
-                .filter_map(|e| e.ok())
-            {
-                if entry.path() == path {
-                    continue;
-                }
+            let walker = walk.filter(WalkDir::new(&path).max_depth(1), device);
+            let entries: Vec<DirEntry> = walk_entries(walker, &cancel, &skipped, &scanned)
+                .filter(|e| e.path() != path)
+                .collect();
 
+            let items = map_on(pool, &entries, |entry| -> Result<Vec<FileItem>> {
                 let metadata = match entry.metadata() {
                     Ok(meta) => meta,
-                    Err(_) => continue,
-                };
-
-                let size = if metadata.is_dir() {
-                    calculate_dir_size(entry.path())?
-                } else {
-                    metadata.len()
-                };
-
-                let item_count = if metadata.is_dir() {
-                    Some(count_items(entry.path())?)
-                } else {
-                    None
+                    Err(_) => return Ok(Vec::new()),
                 };
+                if !metadata.is_dir() {
+                    if walk.is_recent(&metadata) {
+                        return Ok(Vec::new());
+                    }
+                    return Ok(vec![FileItem {
+                        path: entry.path().to_owned(),
+                        size: file_size(&metadata, allocated),
+                        item_count: None,
+                        is_dir: false,
+                        mtime: metadata.modified().unwrap_or(UNIX_EPOCH),
+                        project_kind: None,
+                    }]);
+                }
 
-                items.push(FileItem {
-                    path: entry.path().to_owned(),
-                    size,
-                    item_count,
-                    is_dir: metadata.is_dir(),
+                // Nested entries need the walk anyway, so the cache only
+                // stands in for it when the children are all that's listed
+                let mtime = metadata.modified().unwrap_or(UNIX_EPOCH);
+                let cached = cache.as_ref().filter(|_| depth == 1).and_then(|cache| {
+                    cache.lock().unwrap().get(entry.path(), mtime, allocated, count_hardlinks)
                 });
-            }
+                if let Some((size, item_count)) = cached {
+                    return Ok(vec![FileItem {
+                        path: entry.path().to_owned(),
+                        size,
+                        item_count: Some(item_count),
+                        is_dir: true,
+                        mtime,
+                        project_kind: None,
+                    }]);
+                }
+
+                let tree = measure_tree(entry.path(), mtime, depth - 1, &walk, device, &cancel, &skipped, &scanned, allocated, count_hardlinks);
+                if let Some(cache) = &cache {
+                    let mut cache = cache.lock().unwrap();
+                    for item in tree.iter().filter(|item| item.is_dir) {
+                        cache.insert(&item.path, item.mtime, allocated, count_hardlinks, item.size, item.item_count.unwrap_or(0));
+                    }
+                }
+                ensure_not_cancelled(&cancel)?;
+                Ok(tree)
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+            ensure_not_cancelled(&cancel)?;
 
-            // Sort by size (largest first)
-            items.sort_by(|a, b| b.size.cmp(&a.size));
             Ok(items)
         }).await?
     }
 }
 
-fn calculate_dir_size(path: &Path) -> Result<u64> {
+/// Sizes the directory `root` (last modified at `mtime`) from a single walk
+/// and lists it along with the entries up to `levels` below it. The nested
+/// directories' sizes and file counts are summed up from that walk, and a
+/// hardlinked file counts towards the directories of its first link only.
+#[allow(clippy::too_many_arguments)]
+fn measure_tree(
+    root: &Path,
+    mtime: SystemTime,
+    levels: usize,
+    walk: &WalkOptions,
+    device: Option<u64>,
+    cancel: &Arc<AtomicBool>,
+    skipped: &Skipped,
+    scanned: &Arc<AtomicUsize>,
+    allocated: bool,
+    count_hardlinks: bool,
+) -> Vec<FileItem> {
+    let dir_item = |path: PathBuf, mtime| FileItem { path, size: 0, item_count: Some(0), is_dir: true, mtime, project_kind: None };
+    let mut items = vec![dir_item(root.to_owned(), mtime)];
+    // Index in `items` of each listed directory, to add the files below it to
+    let mut dirs = HashMap::from([(root.to_owned(), 0)]);
+    let mut seen_inodes = HashSet::new();
+
+    for entry in walk_entries(walk.filter(WalkDir::new(root), device), cancel, skipped, scanned) {
+        let depth = entry.depth();
+        if depth == 0 {
+            continue;
+        }
+        let metadata = entry.metadata().ok();
+        if entry.file_type().is_dir() {
+            if depth <= levels {
+                let mtime = metadata.and_then(|metadata| metadata.modified().ok()).unwrap_or(UNIX_EPOCH);
+                dirs.insert(entry.path().to_owned(), items.len());
+                items.push(dir_item(entry.path().to_owned(), mtime));
+            }
+            continue;
+        }
+        if metadata.as_ref().is_some_and(|metadata| walk.is_recent(metadata)) {
+            continue;
+        }
+
+        let size = match &metadata {
+            Some(metadata) if metadata.is_file() && (count_hardlinks || is_first_link(metadata, &mut seen_inodes)) => {
+                file_size(metadata, allocated)
+            }
+            _ => 0,
+        };
+        if let Some(metadata) = metadata.filter(|_| depth <= levels) {
+            items.push(FileItem {
+                path: entry.path().to_owned(),
+                size: file_size(&metadata, allocated),
+                item_count: None,
+                is_dir: false,
+                mtime: metadata.modified().unwrap_or(UNIX_EPOCH),
+                project_kind: None,
+            });
+        }
+        // Ancestors deeper than `levels` aren't listed and have no totals
+        for ancestor in entry.path().ancestors().skip(1 + (depth - 1).saturating_sub(levels)).take(depth.min(levels + 1)) {
+            if let Some(&index) = dirs.get(ancestor) {
+                let dir = &mut items[index];
+                dir.size += size;
+                dir.item_count = dir.item_count.map(|count| count + 1);
+            }
+        }
+    }
+
+    items
+}
+
+/// Compiles `--exclude` style glob patterns into a single matcher.
+pub fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Parses a gitignore-style file for `--exclude-from`. Patterns with a
+/// leading `/` are anchored at the file's directory, as in a `.gitignore`.
+pub fn build_ignore_file(path: &Path) -> Result<Gitignore> {
+    let path = std::path::absolute(path)?;
+    let mut builder = GitignoreBuilder::new(path.parent().unwrap_or(Path::new("/")));
+    if let Some(error) = builder.add(&path) {
+        return Err(anyhow!("Failed to read exclude file {}", error));
+    }
+    Ok(builder.build()?)
+}
+
+/// True when `ignore` excludes `entry`. Paths are made absolute first so
+/// anchored patterns line up with the file's directory.
+fn ignored(ignore: &Gitignore, entry: &DirEntry) -> bool {
+    let path = std::path::absolute(entry.path()).unwrap_or_else(|_| entry.path().to_owned());
+    ignore.matched(&path, entry.file_type().is_dir()).is_ignore()
+}
+
+/// Entries a scan could not read, usually for lack of permission. Clones
+/// share the same set, and each path is counted once however many of the
+/// scan's walks ran into it.
+#[derive(Debug, Clone, Default)]
+pub struct Skipped(Arc<Mutex<HashSet<PathBuf>>>);
+
+impl Skipped {
+    fn record(&self, error: &walkdir::Error) {
+        if let Some(path) = error.path() {
+            self.0.lock().unwrap().insert(path.to_owned());
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    fn paths(&self) -> Vec<PathBuf> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// How the finders walk a tree: which entries they skip, how deep they go
+/// and whether they follow symlinks or stay on one filesystem.
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Follow symbolic links while walking (loops are detected and skipped)
+    pub follow_symlinks: bool,
+    /// Paths matching any of these globs are skipped and not descended into
+    pub exclude: GlobSet,
+    /// Patterns from an `--exclude-from` file, applied like `exclude`
+    pub exclude_from: Option<Gitignore>,
+    /// Skip entries whose name starts with `.` without descending into them
+    pub skip_hidden: bool,
+    /// Don't descend into directories on other filesystems, like `du -x` (Unix)
+    pub one_file_system: bool,
+    /// Don't walk more than this many levels below the root. `DirAnalyzer`
+    /// gets its listing depth per call instead.
+    pub max_depth: Option<usize>,
+    /// Files modified after this time are left out (`--protect-newer-than`).
+    /// Directories are still walked; see [`WalkOptions::is_recent`]
+    pub protect_newer_than: Option<SystemTime>,
+}
+
+impl WalkOptions {
+    /// Device that every walk of a scan starting at `root` stays on, if any.
+    fn device(&self, root: &Path) -> Option<u64> {
+        root_device(root, self.one_file_system)
+    }
+
+    /// True when nothing is left out or followed, so a directory's size is
+    /// simply that of everything below it.
+    fn sees_everything(&self) -> bool {
+        self.exclude.is_empty()
+            && self.exclude_from.is_none()
+            && !self.skip_hidden
+            && !self.follow_symlinks
+            && !self.one_file_system
+            && self.protect_newer_than.is_none()
+    }
+
+    /// True when the entry with `metadata` was modified after the
+    /// `protect_newer_than` cutoff. Finders drop such files, and leave alone
+    /// directories that are or contain such an entry when removing them.
+    fn is_recent(&self, metadata: &std::fs::Metadata) -> bool {
+        self.protect_newer_than
+            .is_some_and(|cutoff| metadata.modified().is_ok_and(|mtime| mtime > cutoff))
+    }
+
+    /// True when `exclude` or `exclude_from` match `path` or a directory
+    /// above it, so a walk would never have reached it. For directories
+    /// named directly instead of found by a walk.
+    fn excludes_dir(&self, path: &Path) -> bool {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+        path.ancestors().any(|dir| self.exclude.is_match(dir))
+            || self.exclude_from.as_ref().is_some_and(|ignore| {
+                absolute.ancestors().any(|dir| ignore.matched(dir, true).is_ignore())
+            })
+    }
+
+    /// Walks `root` down to `max_depth`, see [`WalkOptions::filter`].
+    fn walker(&self, root: &Path, device: Option<u64>) -> FilterEntry<walkdir::IntoIter, impl FnMut(&DirEntry) -> bool> {
+        let mut walker = WalkDir::new(root);
+        if let Some(max_depth) = self.max_depth {
+            walker = walker.max_depth(max_depth);
+        }
+        self.filter(walker, device)
+    }
+
+    /// Applies every option but `max_depth` to `walker`. The walk can still
+    /// `skip_current_dir`.
+    ///
+    /// When following symlinks, every directory's canonical path is recorded and a
+    /// directory that resolves to an already visited location is not descended
+    /// into again, so symlink cycles cannot inflate sizes or hang the walk.
+    ///
+    /// With a `device` (see [`WalkOptions::device`]) directories on other
+    /// filesystems are pruned, like `du -x`.
+    fn filter(&self, walker: WalkDir, device: Option<u64>) -> FilterEntry<walkdir::IntoIter, impl FnMut(&DirEntry) -> bool> {
+        let mut visited = HashSet::new();
+        let exclude = self.exclude.clone();
+        let exclude_from = self.exclude_from.clone();
+        let skip_hidden = self.skip_hidden;
+        let follow_symlinks = self.follow_symlinks;
+
+        walker
+            .follow_links(follow_symlinks)
+            .into_iter()
+            .filter_entry(move |entry| {
+                if entry.depth() > 0 && exclude.is_match(entry.path()) {
+                    return false;
+                }
+                if entry.depth() > 0 && exclude_from.as_ref().is_some_and(|ignore| ignored(ignore, entry)) {
+                    return false;
+                }
+                // Only names below the root count, so scanning inside a
+                // hidden directory still works
+                if skip_hidden && entry.depth() > 0 && entry.file_name().to_string_lossy().starts_with('.') {
+                    return false;
+                }
+                if !entry.file_type().is_dir() {
+                    return true;
+                }
+                if device.is_some() && entry.depth() > 0 && entry.metadata().is_ok_and(|metadata| !on_device(&metadata, device)) {
+                    return false;
+                }
+                if !follow_symlinks {
+                    return true;
+                }
+                match entry.path().canonicalize() {
+                    Ok(canonical) => visited.insert(canonical),
+                    Err(_) => false,
+                }
+            })
+    }
+}
+
+static JOBS: OnceLock<usize> = OnceLock::new();
+
+/// Number of walks allowed at once when `--jobs` is omitted: one per CPU.
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Bounds how many directory walks run at the same time, so scanning several
+/// paths doesn't oversubscribe the blocking pool or thrash the disk, and how
+/// many children `DirAnalyzer` sizes at once. Only the first call has an effect.
+pub fn set_jobs(jobs: usize) {
+    let _ = JOBS.set(jobs.max(1));
+}
+
+/// The `--jobs` setting, or [`default_jobs`] without one.
+pub fn jobs() -> usize {
+    *JOBS.get_or_init(default_jobs)
+}
+
+fn job_slots() -> Arc<Semaphore> {
+    static SLOTS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+    SLOTS.get_or_init(|| Arc::new(Semaphore::new(jobs()))).clone()
+}
+
+/// Thread pool of `--jobs` threads that `DirAnalyzer` sizes children on. It
+/// is built once and shared, so scanning several roots at once still uses
+/// no more threads than that. `None` with a single job, or when the pool
+/// can't be built; sizing then happens on the calling thread.
+fn job_pool() -> Option<&'static rayon::ThreadPool> {
+    static POOL: OnceLock<Option<rayon::ThreadPool>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let jobs = jobs();
+        (jobs > 1).then(|| rayon::ThreadPoolBuilder::new().num_threads(jobs).build().ok()).flatten()
+    })
+    .as_ref()
+}
+
+/// Maps `f` over `items` on `pool`, keeping their order. Without a pool
+/// everything runs on the calling thread, one item after another.
+fn map_on<T: Sync, R: Send>(pool: Option<&rayon::ThreadPool>, items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    match pool {
+        Some(pool) => pool.install(|| items.par_iter().map(f).collect()),
+        None => items.iter().map(f).collect(),
+    }
+}
+
+/// Runs the blocking walk `f` on the blocking pool once a `--jobs` slot is free.
+async fn spawn_walk<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T> {
+    spawn_walk_in(job_slots(), f).await
+}
+
+async fn spawn_walk_in<T: Send + 'static>(slots: Arc<Semaphore>, f: impl FnOnce() -> T + Send + 'static) -> Result<T> {
+    let permit = slots.acquire_owned().await?;
+    Ok(task::spawn_blocking(move || {
+        let _permit = permit;
+        f()
+    })
+    .await?)
+}
+
+/// Starts `scan` for every root at once and collects the results in root
+/// order. How many of them actually walk in parallel is bounded by `--jobs`.
+pub async fn scan_each<T, F>(roots: &[PathBuf], scan: impl Fn(PathBuf) -> F) -> Result<Vec<T>>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T>> + Send + 'static,
+{
+    let handles: Vec<_> = roots.iter().cloned().map(|root| task::spawn(scan(root))).collect();
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(handle.await??);
+    }
+    Ok(results)
+}
+
+/// Iterates over the entries of `walk`, skipping unreadable ones after
+/// noting them in `skipped` and counting the rest in `scanned`. The starting
+/// path itself is not counted, since whoever found it has counted it already.
+/// The iteration simply ends once `cancel` is set.
+fn walk_entries(
+    walk: impl Iterator<Item = walkdir::Result<DirEntry>>,
+    cancel: &Arc<AtomicBool>,
+    skipped: &Skipped,
+    scanned: &Arc<AtomicUsize>,
+) -> impl Iterator<Item = DirEntry> {
+    let cancel = cancel.clone();
+    let skipped = skipped.clone();
+    let scanned = scanned.clone();
+
+    walk.take_while(move |_| !cancel.load(Ordering::Relaxed))
+        .filter_map(move |e| e.map_err(|error| skipped.record(&error)).ok())
+        .inspect(move |entry| {
+            if entry.depth() > 0 {
+                scanned.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+}
+
+/// Device id of `path` when the walk should stay on its filesystem. Always
+/// `None` on platforms without device ids, or when `path` can't be read.
+fn root_device(path: &Path, one_file_system: bool) -> Option<u64> {
+    if !one_file_system {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(path).ok().map(|metadata| metadata.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// False when `metadata` is on a different device than `device`.
+fn on_device(metadata: &std::fs::Metadata, device: Option<u64>) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        device.is_none_or(|device| metadata.dev() == device)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (metadata, device);
+        true
+    }
+}
+
+/// Turns a cancelled walk into an error, since its results would be incomplete.
+fn ensure_not_cancelled(cancel: &AtomicBool) -> Result<()> {
+    if cancel.load(Ordering::Relaxed) {
+        return Err(anyhow!("Scan cancelled"));
+    }
+    Ok(())
+}
+
+/// Size a file counts for: its apparent length, or with `allocated` the space
+/// its blocks take up on disk (smaller for sparse files, rounded up to the
+/// block size otherwise). Platforms without block counts always use the length.
+pub fn file_size(metadata: &std::fs::Metadata, allocated: bool) -> u64 {
+    #[cfg(unix)]
+    if allocated {
+        use std::os::unix::fs::MetadataExt;
+        return metadata.blocks() * 512;
+    }
+    #[cfg(not(unix))]
+    let _ = allocated;
+
+    metadata.len()
+}
+
+/// False when `metadata` belongs to a hardlinked file whose inode is already
+/// in `seen`, so each file's data is only counted once per walk.
+fn is_first_link(metadata: &std::fs::Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if metadata.nlink() > 1 {
+            return seen.insert((metadata.dev(), metadata.ino()));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (metadata, seen);
+
+    true
+}
+
+/// What [`measure_dir`] found below a directory.
+struct Measured {
+    size: u64,
+    files: usize,
+    /// Something below, or the directory itself, was modified after
+    /// `WalkOptions::protect_newer_than`. Such files are left out of the totals
+    recent: bool,
+}
+
+/// Total size and number of the files below `path`, from a single walk.
+/// Directories are walked but not counted.
+#[allow(clippy::too_many_arguments)]
+fn measure_dir(
+    path: &Path,
+    walk: &WalkOptions,
+    device: Option<u64>,
+    cancel: &Arc<AtomicBool>,
+    skipped: &Skipped,
+    scanned: &Arc<AtomicUsize>,
+    allocated: bool,
+    count_hardlinks: bool,
+) -> Result<Measured> {
     let mut total_size = 0;
+    let mut files = 0usize;
+    let mut recent = false;
+    let mut seen_inodes = HashSet::new();
     
-    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-        if let Ok(metadata) = entry.metadata() {
-            if metadata.is_file() {
-                total_size += metadata.len();
+    for entry in walk_entries(walk.filter(WalkDir::new(path), device), cancel, skipped, scanned) {
+        let metadata = entry.metadata().ok();
+        if metadata.as_ref().is_some_and(|metadata| walk.is_recent(metadata)) {
+            recent = true;
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+        }
+        if !entry.file_type().is_dir() {
+            files += 1;
+        }
+        if let Some(metadata) = metadata {
+            if metadata.is_file() && (count_hardlinks || is_first_link(&metadata, &mut seen_inodes)) {
+                total_size += file_size(&metadata, allocated);
             }
         }
     }
     
-    Ok(total_size)
+    Ok(Measured { size: total_size, files, recent })
 }
 
-fn count_items(path: &Path) -> Result<usize> {
-    Ok(WalkDir::new(path).into_iter().count().saturating_sub(1)) // Subtract 1 for the root directory
-}
+/// How many entries are walked between two progress reports.
+const PROGRESS_INTERVAL: usize = 500;
 
-pub struct LargeFileFinder;
+#[derive(Clone)]
+pub struct LargeFileFinder {
+    /// How the tree is walked
+    pub walk: WalkOptions,
+    /// Report allocated disk usage instead of apparent length (see `file_size`)
+    pub allocated: bool,
+    /// Receives the running number of scanned entries while the walk progresses
+    pub progress: Option<mpsc::Sender<usize>>,
+    /// Receives each matching file as soon as it is found, instead of
+    /// collecting them; `find_large_files` then returns an empty list
+    pub stream: Option<tokio::sync::mpsc::Sender<FileItem>>,
+    /// Only files last modified at or before this time (`--older-than`)
+    pub modified_before: Option<SystemTime>,
+    /// Only files last modified at or after this time (`--newer-than`)
+    pub modified_after: Option<SystemTime>,
+    /// Stops the walk early when set
+    pub cancel: Arc<AtomicBool>,
+    /// Collects the entries the walk could not read
+    pub skipped: Skipped,
+    /// Counts the entries the walk visited, across all scans with this finder
+    pub scanned: Arc<AtomicUsize>,
+}
 
 impl LargeFileFinder {
     pub fn new() -> Self {
-        Self
+        Self {
+            walk: WalkOptions::default(),
+            allocated: cfg!(unix),
+            progress: None,
+            stream: None,
+            modified_before: None,
+            modified_after: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            skipped: Skipped::default(),
+            scanned: Arc::default(),
+        }
     }
 
-    pub async fn find_large_files(&self, path: &Path, min_size: u64) -> Result<Vec<FileItem>> {
+    /// Finds files of at least `min_size` (and at most `max_size`, if given)
+    /// bytes below `path`. A non-empty `extensions` list keeps only files with
+    /// one of those extensions, compared case-insensitively and without the dot.
+    ///
+    /// Setting `cancel` ends the walk at the next entry; this is not an error,
+    /// the files found up to that point are returned sorted as usual.
+    pub async fn find_large_files(
+        &self,
+        path: &Path,
+        min_size: u64,
+        max_size: Option<u64>,
+        extensions: Vec<String>,
+    ) -> Result<Vec<FileItem>> {
+        if let Some(max_size) = max_size.filter(|&max_size| max_size < min_size) {
+            return Err(anyhow!(
+                "Maximum size ({} bytes) is smaller than minimum size ({} bytes)",
+                max_size,
+                min_size
+            ));
+        }
+        safety::check_scan(path)?;
         let path = path.to_owned();
+        let walk = self.walk.clone();
+        let allocated = self.allocated;
+        let device = walk.device(&path);
+        let progress = self.progress.clone();
+        let stream = self.stream.clone();
+        let (modified_before, modified_after) = (self.modified_before, self.modified_after);
+        let cancel = self.cancel.clone();
+        let skipped = self.skipped.clone();
+        let scanned = self.scanned.clone();
+        let extensions: Vec<String> = extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
         
-        task::spawn_blocking(move || {
+        spawn_walk(move || {
             let mut large_files = Vec::new();
+            let mut walked = 0usize;
             
-            for entry in WalkDir::new(&path).into_iter().filter_map(|e| e.ok()) {
+            for entry in walk_entries(walk.walker(&path, device), &cancel, &skipped, &scanned) {
+                walked += 1;
+                if walked.is_multiple_of(PROGRESS_INTERVAL) {
+                    if let Some(progress) = &progress {
+                        let _ = progress.send(walked);
+                    }
+                }
+
                 if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_file() && metadata.len() >= min_size {
-                        large_files.push(FileItem {
+                    let len = file_size(&metadata, allocated);
+                    let mtime = metadata.modified().unwrap_or(UNIX_EPOCH);
+                    if metadata.is_file()
+                        && len >= min_size
+                        && max_size.is_none_or(|max_size| len <= max_size)
+                        && modified_before.is_none_or(|before| mtime <= before)
+                        && modified_after.is_none_or(|after| mtime >= after)
+                        && has_extension(entry.path(), &extensions)
+                        && !walk.is_recent(&metadata)
+                    {
+                        let item = FileItem {
                             path: entry.path().to_owned(),
-                            size: metadata.len(),
+                            size: len,
                             item_count: None,
                             is_dir: false,
-                        });
+                            mtime,
+                            project_kind: None,
+                        };
+                        match &stream {
+                            // The receiver is gone, so nobody wants the rest
+                            Some(stream) => if stream.blocking_send(item).is_err() {
+                                break;
+                            },
+                            None => large_files.push(item),
+                        }
                     }
                 }
             }
 
+            if let Some(progress) = &progress {
+                let _ = progress.send(walked);
+            }
+
             // Sort by size (largest first)
-            large_files.sort_by(|a, b| b.size.cmp(&a.size));
+            large_files.sort_by_key(|item| std::cmp::Reverse(item.size));
             Ok(large_files)
         }).await?
     }
 }
 
-pub struct DevArtifactFinder;
+/// True when `extensions` is empty or the file's extension is one of them.
+/// `extensions` must already be lowercase.
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    extensions.is_empty()
+        || path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.contains(&ext.to_lowercase()))
+}
+
+pub struct DuplicateFinder {
+    /// How the tree is walked
+    pub walk: WalkOptions,
+    /// Aborts the scan with an error when set
+    pub cancel: Arc<AtomicBool>,
+    /// Collects the entries the walk could not read
+    pub skipped: Skipped,
+    /// Counts the entries the walk visited, across all scans with this finder
+    pub scanned: Arc<AtomicUsize>,
+    /// Receives the running number of fully hashed files while hashing progresses
+    pub progress: Option<mpsc::Sender<usize>>,
+}
+
+impl DuplicateFinder {
+    pub fn new() -> Self {
+        Self {
+            walk: WalkOptions::default(),
+            cancel: Arc::new(AtomicBool::new(false)),
+            skipped: Skipped::default(),
+            scanned: Arc::default(),
+            progress: None,
+        }
+    }
+
+    /// Finds groups of byte-identical files below `path`.
+    ///
+    /// Files are first bucketed by length, then by their first block, and
+    /// only files still sharing both with another file are hashed in full,
+    /// in parallel. Empty files are ignored. Groups are sorted by wasted
+    /// space (size of all copies but one), largest first.
+    pub async fn find_duplicates(&self, path: &Path) -> Result<Vec<Vec<FileItem>>> {
+        safety::check_scan(path)?;
+        let path = path.to_owned();
+        let walk = self.walk.clone();
+        let device = walk.device(&path);
+        let cancel = self.cancel.clone();
+        let skipped = self.skipped.clone();
+        let scanned = self.scanned.clone();
+        let progress = self.progress.clone();
+
+        spawn_walk(move || {
+            let mut by_size: HashMap<u64, Vec<FileItem>> = HashMap::new();
+
+            for entry in walk_entries(walk.walker(&path, device), &cancel, &skipped, &scanned) {
+                if let Ok(metadata) = entry.metadata() {
+                    if metadata.is_file() && metadata.len() > 0 && !walk.is_recent(&metadata) {
+                        by_size.entry(metadata.len()).or_default().push(FileItem {
+                            path: entry.path().to_owned(),
+                            size: metadata.len(),
+                            item_count: None,
+                            is_dir: false,
+                            mtime: metadata.modified().unwrap_or(UNIX_EPOCH),
+                            project_kind: None,
+                        });
+                    }
+                }
+            }
+
+            ensure_not_cancelled(&cancel)?;
+
+            let candidates = by_size.into_values().filter(|files| files.len() > 1).flatten().collect();
+            let mut groups = duplicate_groups(candidates, &cancel, progress.as_ref());
+            ensure_not_cancelled(&cancel)?;
+
+            for group in &mut groups {
+                group.sort_by(|a, b| a.path.cmp(&b.path));
+            }
+            groups.sort_by_key(|group| std::cmp::Reverse(wasted_space(group)));
+            Ok(groups)
+        }).await?
+    }
+}
+
+/// Bytes compared before a file is worth hashing in full.
+const FIRST_BLOCK: usize = 4096;
+
+/// Chunk size files are read and hashed in, so memory use stays bounded.
+const HASH_CHUNK: usize = 64 * 1024;
+
+/// Splits `files` into groups of identical content. Files are narrowed down
+/// by size and first block before any are hashed in full; files no larger
+/// than a block are already settled by the first comparison. Files that
+/// vanish or can't be read are simply not duplicates.
+fn duplicate_groups(
+    files: Vec<FileItem>,
+    cancel: &AtomicBool,
+    progress: Option<&mpsc::Sender<usize>>,
+) -> Vec<Vec<FileItem>> {
+    let (settled, to_hash): (Vec<_>, Vec<_>) = group_by_key(files, cancel, |file| {
+        read_first_block(&file.path).ok().map(|block| (file.size, block))
+    })
+    .into_iter()
+    .partition(|group| group[0].size <= FIRST_BLOCK as u64);
+
+    let hashed = AtomicUsize::new(0);
+    let mut groups = group_by_key(to_hash.into_iter().flatten().collect(), cancel, |file| {
+        let hash = hash_file(&file.path).ok()?;
+        let done = hashed.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(progress) = progress {
+            let _ = progress.send(done);
+        }
+        Some((file.size, hash))
+    });
+    groups.extend(settled);
+    groups
+}
+
+/// Groups `files` by the key computed for each in parallel, on the `--jobs`
+/// pool, keeping only groups of two or more. Files without a key are dropped.
+fn group_by_key<K, F>(files: Vec<FileItem>, cancel: &AtomicBool, key: F) -> Vec<Vec<FileItem>>
+where
+    K: std::hash::Hash + Eq + Send,
+    F: Fn(&FileItem) -> Option<K> + Sync,
+{
+    let keys = map_on(job_pool(), &files, |file| {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        key(file)
+    });
+
+    let mut groups: HashMap<K, Vec<FileItem>> = HashMap::new();
+    for (key, file) in keys.into_iter().zip(files) {
+        if let Some(key) = key {
+            groups.entry(key).or_default().push(file);
+        }
+    }
+    groups.into_values().filter(|group| group.len() > 1).collect()
+}
+
+/// Bytes that would be reclaimed by keeping a single copy of the group.
+pub fn wasted_space(group: &[FileItem]) -> u64 {
+    group.iter().skip(1).map(|item| item.size).sum()
+}
+
+/// Up to the first `FIRST_BLOCK` bytes of the file at `path`.
+fn read_first_block(path: &Path) -> Result<Vec<u8>> {
+    let mut block = Vec::with_capacity(FIRST_BLOCK);
+    File::open(path)?.take(FIRST_BLOCK as u64).read_to_end(&mut block)?;
+    Ok(block)
+}
+
+fn hash_file(path: &Path) -> Result<blake3::Hash> {
+    let mut file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut chunk = vec![0u8; HASH_CHUNK];
+    loop {
+        match file.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => {
+                hasher.update(&chunk[..read]);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(hasher.finalize())
+}
+
+pub struct EmptyDirFinder {
+    /// How the tree is walked
+    pub walk: WalkOptions,
+    /// Aborts the scan with an error when set
+    pub cancel: Arc<AtomicBool>,
+    /// Collects the entries the walk could not read
+    pub skipped: Skipped,
+    /// Counts the entries the walk visited, across all scans with this finder
+    pub scanned: Arc<AtomicUsize>,
+}
+
+impl EmptyDirFinder {
+    pub fn new() -> Self {
+        Self {
+            walk: WalkOptions::default(),
+            cancel: Arc::new(AtomicBool::new(false)),
+            skipped: Skipped::default(),
+            scanned: Arc::default(),
+        }
+    }
+
+    /// Finds directories below `path` whose subtree contains no files at all,
+    /// only other empty directories. Results are sorted deepest-first so they
+    /// can be removed in order. Anything inside a `.git` directory is left out
+    /// because git relies on some of its empty directories existing.
+    pub async fn find_empty_dirs(&self, path: &Path) -> Result<Vec<FileItem>> {
+        safety::check_scan(path)?;
+        let path = path.to_owned();
+        let walk = self.walk.clone();
+        let device = walk.device(&path);
+        let cancel = self.cancel.clone();
+        let skipped = self.skipped.clone();
+        let scanned = self.scanned.clone();
+
+        spawn_walk(move || {
+            let mut dirs = Vec::new();
+            let mut non_empty: HashSet<PathBuf> = HashSet::new();
+
+            for entry in walk_entries(walk.walker(&path, device), &cancel, &skipped, &scanned) {
+                let recent = walk.protect_newer_than.is_some() && entry.metadata().is_ok_and(|metadata| walk.is_recent(&metadata));
+                if entry.file_type().is_dir() {
+                    if entry.depth() == 0 {
+                        continue;
+                    }
+                    if !recent {
+                        dirs.push(entry.into_path());
+                        continue;
+                    }
+                }
+
+                // Any non-directory entry, or a directory too recent to
+                // remove, makes every ancestor non-empty
+                for ancestor in entry.path().ancestors().skip(1) {
+                    if !non_empty.insert(ancestor.to_owned()) || ancestor == path {
+                        break;
+                    }
+                }
+            }
+
+            // A partial walk would make unvisited directories look empty
+            ensure_not_cancelled(&cancel)?;
+
+            // Nor is a directory that couldn't be read, or anything above it
+            for unreadable in skipped.paths() {
+                for ancestor in unreadable.ancestors() {
+                    if !non_empty.insert(ancestor.to_owned()) || ancestor == path {
+                        break;
+                    }
+                }
+            }
+
+            let mut empty_dirs: Vec<FileItem> = dirs
+                .into_iter()
+                .filter(|dir| !non_empty.contains(dir))
+                .filter(|dir| !dir.components().any(|c| c.as_os_str() == ".git"))
+                .map(|dir| FileItem {
+                    mtime: dir.metadata().and_then(|m| m.modified()).unwrap_or(UNIX_EPOCH),
+                    path: dir,
+                    size: 0,
+                    item_count: Some(0),
+                    is_dir: true,
+                    project_kind: None,
+                })
+                .collect();
+
+            // Deepest first, so children are always removed before their parents
+            empty_dirs.sort_by_key(|item| std::cmp::Reverse(item.path.components().count()));
+            Ok(empty_dirs)
+        }).await?
+    }
+}
+
+/// Result of a development artifact scan.
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactScan {
+    /// Artifact directories that are safe removal candidates
+    pub artifacts: Vec<FileItem>,
+    /// Artifact-named directories that git tracks (not ignored); never removal candidates
+    pub tracked: Vec<FileItem>,
+    /// Artifacts smaller than the finder's `min_size`, left out of `artifacts`
+    pub small: Vec<FileItem>,
+    /// Paths given to `check_listed` that are not removal candidates, with the reason
+    pub rejected: Vec<(PathBuf, String)>,
+}
+
+#[derive(Clone)]
+pub struct DevArtifactFinder {
+    /// How the tree is walked
+    pub walk: WalkOptions,
+    /// Report allocated disk usage instead of apparent length (see `file_size`)
+    pub allocated: bool,
+    /// Count every hardlink to a file instead of each inode once (Unix)
+    pub count_hardlinks: bool,
+    /// Consult `.gitignore` files and set aside artifact directories git tracks
+    pub respect_gitignore: bool,
+    /// Artifacts smaller than this are set aside in `ArtifactScan::small`
+    pub min_size: u64,
+    /// Artifact names to report; the others are walked like ordinary directories
+    pub selection: ArtifactSelection,
+    /// Keep searching inside artifact directories, reporting the artifacts
+    /// nested in them too. Off by default, since those go along with the
+    /// outer directory anyway
+    pub deep: bool,
+    /// Aborts the scan with an error when set
+    pub cancel: Arc<AtomicBool>,
+    /// Collects the entries the walk could not read
+    pub skipped: Skipped,
+    /// Counts the entries the walk visited, across all scans with this finder
+    pub scanned: Arc<AtomicUsize>,
+}
 
 impl DevArtifactFinder {
     pub fn new() -> Self {
-        Self
+        Self {
+            walk: WalkOptions::default(),
+            allocated: cfg!(unix),
+            count_hardlinks: false,
+            respect_gitignore: false,
+            min_size: 0,
+            selection: ArtifactSelection::default(),
+            deep: false,
+            cancel: Arc::new(AtomicBool::new(false)),
+            skipped: Skipped::default(),
+            scanned: Arc::default(),
+        }
     }
 
+    /// Size of the artifact directory `path` measured the way a scan measures
+    /// it, for checking right before removal that it has not changed. Fails
+    /// when something in it was modified within `--protect-newer-than` since.
+    /// Blocks the calling thread.
+    pub fn measure(&self, path: &Path) -> Result<u64> {
+        let device = self.walk.device(path);
+        let measured = measure_dir(path, &self.walk, device, &self.cancel, &self.skipped, &self.scanned, self.allocated, self.count_hardlinks)?;
+        if measured.recent {
+            return Err(anyhow!("it was modified within --protect-newer-than"));
+        }
+        Ok(measured.size)
+    }
+
+    /// Measures the given directories instead of searching for artifacts,
+    /// for lists produced by other tools. Each must be an artifact directory
+    /// this run would report; the others are rejected with the reason. The
+    /// size limit and git checks don't apply to paths named explicitly.
+    pub async fn check_listed(&self, paths: Vec<PathBuf>) -> Result<ArtifactScan> {
+        let walk = self.walk.clone();
+        let allocated = self.allocated;
+        let count_hardlinks = self.count_hardlinks;
+        let selection = self.selection.clone();
+        let cancel = self.cancel.clone();
+        let skipped = self.skipped.clone();
+        let scanned = self.scanned.clone();
+
+        spawn_walk(move || {
+            let mut scan = ArtifactScan::default();
+            let mut seen = HashSet::new();
+
+            for path in paths {
+                if !seen.insert(path.clone()) {
+                    continue;
+                }
+                let rejection = if !path.is_dir() {
+                    Some("not an existing directory".to_string())
+                } else if let Err(e) = safety::check_scan(&path) {
+                    Some(e.to_string())
+                } else if !is_artifact_dir(&path) {
+                    Some("not a known artifact directory".to_string())
+                } else if !selection.allows(&path) {
+                    Some("left out by --only-artifact/--exclude-artifact".to_string())
+                } else if walk.excludes_dir(&path) {
+                    Some("left out by --exclude/--exclude-from".to_string())
+                } else {
+                    None
+                };
+                if let Some(reason) = rejection {
+                    scan.rejected.push((path, reason));
+                    continue;
+                }
+
+                let metadata = fs::metadata(&path)?;
+                let device = walk.device(&path);
+                let measured = measure_dir(&path, &walk, device, &cancel, &skipped, &scanned, allocated, count_hardlinks)?;
+                if measured.recent {
+                    scan.rejected.push((path, "modified within --protect-newer-than".to_string()));
+                    continue;
+                }
+                scan.artifacts.push(FileItem {
+                    project_kind: project_kind(&path),
+                    path,
+                    size: measured.size,
+                    item_count: Some(measured.files),
+                    is_dir: true,
+                    mtime: metadata.modified().unwrap_or(UNIX_EPOCH),
+                });
+            }
+
+            ensure_not_cancelled(&cancel)?;
+            scan.artifacts.sort_by_key(|item| std::cmp::Reverse(item.size));
+            Ok(scan)
+        }).await?
+    }
+
+    /// Artifact directories below `path`. The walk does not descend into a
+    /// directory once it is recognized, so nested artifacts (e.g. the
+    /// `node_modules` of dependencies) are not reported separately, unless
+    /// `deep` is set.
     pub async fn find_artifacts(&self, path: &Path) -> Result<Vec<FileItem>> {
+        Ok(self.scan(path).await?.artifacts)
+    }
+
+    /// Scans each of `paths` and merges the results, largest first. An
+    /// artifact reached from more than one root is reported once.
+    pub async fn scan_all(&self, paths: &[PathBuf]) -> Result<ArtifactScan> {
+        let scans = scan_each(paths, |path| {
+            let finder = self.clone();
+            async move { finder.scan(&path).await }
+        })
+        .await?;
+        let mut merged = ArtifactScan::default();
+        for scan in scans {
+            merged.artifacts.extend(scan.artifacts);
+            merged.tracked.extend(scan.tracked);
+            merged.small.extend(scan.small);
+        }
+
+        for items in [&mut merged.artifacts, &mut merged.tracked, &mut merged.small] {
+            items.sort_by(|a, b| a.path.cmp(&b.path));
+            items.dedup_by(|a, b| a.path == b.path);
+            items.sort_by_key(|item| std::cmp::Reverse(item.size));
+        }
+        Ok(merged)
+    }
+
+    pub async fn scan(&self, path: &Path) -> Result<ArtifactScan> {
+        safety::check_scan(path)?;
         let path = path.to_owned();
+        let walk = self.walk.clone();
+        let allocated = self.allocated;
+        let count_hardlinks = self.count_hardlinks;
+        let respect_gitignore = self.respect_gitignore;
+        let min_size = self.min_size;
+        let selection = self.selection.clone();
+        let deep = self.deep;
+        let device = walk.device(&path);
+        let cancel = self.cancel.clone();
+        let skipped = self.skipped.clone();
+        let scanned = self.scanned.clone();
         
-        task::spawn_blocking(move || {
-            let mut artifacts = Vec::new();
-            let target_dirs = ["node_modules", ".venv", "venv", "__pycache__", ".tox", "target", "build", "dist"];
+        spawn_walk(move || {
+            let mut scan = ArtifactScan::default();
+            // With --deep the walk itself visits everything inside an
+            // artifact, so measuring it must not count those entries again
+            let measure_scanned = if deep { Arc::new(AtomicUsize::new(0)) } else { scanned.clone() };
+
+            let tracked_dirs = if respect_gitignore {
+                find_tracked_dirs(&path, walk.follow_symlinks)
+            } else {
+                HashSet::new()
+            };
             
-            for entry in WalkDir::new(&path).into_iter().filter_map(|e| e.ok()) {
+            let mut entries = walk.walker(&path, device);
+            while let Some(entry) = entries.next() {
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => {
+                        skipped.record(&error);
+                        continue;
+                    }
+                };
+                if entry.depth() > 0 {
+                    scanned.fetch_add(1, Ordering::Relaxed);
+                }
+
                 if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_dir() {
-                        if let Some(dir_name) = entry.path().file_name() {
-                            if let Some(name_str) = dir_name.to_str() {
-                                if target_dirs.contains(&name_str) {
-                                    let size = calculate_dir_size(entry.path())?;
-                                    let item_count = count_items(entry.path())?;
-                                    
-                                    artifacts.push(FileItem {
-                                        path: entry.path().to_owned(),
-                                        size,
-                                        item_count: Some(item_count),
-                                        is_dir: true,
-                                    });
-                                }
-                            }
+                    if metadata.is_dir() && is_artifact_dir(entry.path()) && selection.allows(entry.path()) {
+                        // Anything nested inside goes away with this directory
+                        if !deep {
+                            entries.skip_current_dir();
+                        }
+
+                        let measured = measure_dir(entry.path(), &walk, device, &cancel, &skipped, &measure_scanned, allocated, count_hardlinks)?;
+                        // Removing it would take the recent files along
+                        if measured.recent {
+                            continue;
+                        }
+                        
+                        let item = FileItem {
+                            path: entry.path().to_owned(),
+                            size: measured.size,
+                            item_count: Some(measured.files),
+                            is_dir: true,
+                            mtime: metadata.modified().unwrap_or(UNIX_EPOCH),
+                            project_kind: project_kind(entry.path()),
+                        };
+
+                        if tracked_dirs.contains(entry.path()) {
+                            scan.tracked.push(item);
+                        } else if item.size < min_size {
+                            scan.small.push(item);
+                        } else {
+                            scan.artifacts.push(item);
                         }
                     }
                 }
             }
 
+            ensure_not_cancelled(&cancel)?;
+
             // Sort by size (largest first)
-            artifacts.sort_by(|a, b| b.size.cmp(&a.size));
-            Ok(artifacts)
+            scan.artifacts.sort_by_key(|item| std::cmp::Reverse(item.size));
+            scan.tracked.sort_by_key(|item| std::cmp::Reverse(item.size));
+            Ok(scan)
         }).await?
     }
-}
\ No newline at end of file
+}
+
+/// Collects artifact-named directories inside a git repository that are not
+/// ignored by any `.gitignore`, i.e. directories the user keeps under version
+/// control. Ignored subtrees are pruned by the walker, so this pass stays cheap.
+fn find_tracked_dirs(root: &Path, follow_symlinks: bool) -> HashSet<PathBuf> {
+    WalkBuilder::new(root)
+        .hidden(false)
+        .parents(true)
+        .require_git(true)
+        .follow_links(follow_symlinks)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_dir()))
+        .filter(|entry| is_artifact_dir(entry.path()))
+        .filter(|entry| is_inside_git_repo(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+fn is_inside_git_repo(path: &Path) -> bool {
+    path.ancestors().any(|dir| dir.join(".git").exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_file(path: &Path, len: usize) {
+        fs::write(path, vec![0u8; len]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_parallel_sizes_match_sequential_walk() {
+        let root = tempfile::tempdir().unwrap();
+        for i in 0..8 {
+            let child = root.path().join(format!("child{}", i));
+            fs::create_dir_all(child.join("nested/deeper")).unwrap();
+            write_file(&child.join("a.bin"), 100 * (i + 1));
+            write_file(&child.join("nested/b.bin"), 10 * (i + 1));
+            write_file(&child.join("nested/deeper/c.bin"), i + 1);
+        }
+        write_file(&root.path().join("top.bin"), 4096);
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        let items = analyzer.analyze_directory(root.path(), 1).await.unwrap();
+        let parallel_total: u64 = items.iter().map(|item| item.size).sum();
+
+        assert_eq!(items.len(), 9);
+        assert_eq!(parallel_total, measure_dir(root.path(), &WalkOptions::default(), None, &Arc::default(), &Skipped::default(), &Arc::default(), false, false).unwrap().size);
+        assert_eq!(items.iter().map(|item| item.size).max(), Some(4096));
+
+        analyzer.parallel = false;
+        let sequential = analyzer.analyze_directory(root.path(), 2).await.unwrap();
+        analyzer.parallel = true;
+        let parallel = analyzer.analyze_directory(root.path(), 2).await.unwrap();
+        let summary = |items: &[FileItem]| items.iter().map(|item| (item.path.clone(), item.size, item.item_count)).collect::<Vec<_>>();
+        assert_eq!(summary(&parallel), summary(&sequential));
+    }
+
+    #[test]
+    fn test_no_pool_maps_on_calling_thread() {
+        let items: Vec<usize> = (0..64).collect();
+        let threads = Mutex::new(HashSet::new());
+        let square = |n: &usize| {
+            threads.lock().unwrap().insert(std::thread::current().id());
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            n * n
+        };
+
+        let sequential = map_on(None, &items, square);
+        assert_eq!(*threads.lock().unwrap(), HashSet::from([std::thread::current().id()]));
+
+        threads.lock().unwrap().clear();
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let parallel = map_on(Some(&pool), &items, square);
+        assert_eq!(parallel, sequential);
+        let used = threads.lock().unwrap();
+        assert!(!used.contains(&std::thread::current().id()) && used.len() <= 4);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_cycle_terminates() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        write_file(&nested.join("data.bin"), 1000);
+        std::os::unix::fs::symlink(root.path().join("a"), nested.join("loop")).unwrap();
+
+        assert_eq!(measure_dir(root.path(), &WalkOptions { follow_symlinks: true, ..WalkOptions::default() }, None, &Arc::default(), &Skipped::default(), &Arc::default(), false, false).unwrap().size, 1000);
+        assert_eq!(measure_dir(root.path(), &WalkOptions::default(), None, &Arc::default(), &Skipped::default(), &Arc::default(), false, false).unwrap().size, 1000);
+
+        let mut finder = LargeFileFinder::new();
+        finder.walk.follow_symlinks = true;
+        let files = finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap();
+        assert_eq!(files.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_large_file_behind_symlink_needs_follow() {
+        let media = tempfile::tempdir().unwrap();
+        write_file(&media.path().join("movie.mkv"), 1000);
+        let root = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(media.path(), root.path().join("media")).unwrap();
+
+        let mut finder = LargeFileFinder::new();
+        finder.allocated = false;
+        assert!(finder.find_large_files(root.path(), 500, None, Vec::new()).await.unwrap().is_empty());
+
+        finder.walk.follow_symlinks = true;
+        let files = finder.find_large_files(root.path(), 500, None, Vec::new()).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, root.path().join("media/movie.mkv"));
+    }
+
+    #[tokio::test]
+    async fn test_gitignore_tracked_artifacts_are_set_aside() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join(".git")).unwrap();
+        fs::write(root.path().join(".gitignore"), "node_modules/\n").unwrap();
+        fs::create_dir_all(root.path().join("build")).unwrap();
+        fs::create_dir_all(root.path().join("node_modules")).unwrap();
+        write_file(&root.path().join("build/keep.txt"), 10);
+        write_file(&root.path().join("node_modules/dep.js"), 20);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.respect_gitignore = true;
+        let scan = finder.scan(root.path()).await.unwrap();
+
+        assert_eq!(scan.artifacts.len(), 1);
+        assert!(scan.artifacts[0].path.ends_with("node_modules"));
+        assert_eq!(scan.tracked.len(), 1);
+        assert!(scan.tracked[0].path.ends_with("build"));
+
+        finder.respect_gitignore = false;
+        assert_eq!(finder.find_artifacts(root.path()).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_exclude_globs_prune_and_combine() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in [".git", "src", "Library"] {
+            fs::create_dir(root.path().join(dir)).unwrap();
+            write_file(&root.path().join(dir).join("file.bin"), 100);
+        }
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.walk.exclude = build_glob_set(&["*.git*".to_string()]).unwrap();
+        let items = analyzer.analyze_directory(root.path(), 1).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| !item.path.ends_with(".git")));
+
+        analyzer.walk.exclude = build_glob_set(&["*.git*".to_string(), "**/Library".to_string()]).unwrap();
+        let items = analyzer.analyze_directory(root.path(), 1).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].path.ends_with("src"));
+
+        let mut finder = LargeFileFinder::new();
+        finder.walk.exclude = build_glob_set(&["*.git*".to_string()]).unwrap();
+        let files = finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap();
+        assert_eq!(files.len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unreadable_dir_counted_as_skipped() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Permissions don't stop root, so there is nothing to observe
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+        let root = tempfile::tempdir().unwrap();
+        let locked = root.path().join("locked");
+        fs::create_dir_all(root.path().join("open")).unwrap();
+        fs::create_dir(&locked).unwrap();
+        write_file(&root.path().join("open/a.bin"), 100);
+        write_file(&locked.join("b.bin"), 100);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        let items = analyzer.analyze_directory(root.path(), 2).await;
+        let finder = EmptyDirFinder::new();
+        let empty = finder.find_empty_dirs(root.path()).await;
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let items = items.unwrap();
+        let open = items.iter().find(|item| item.path.ends_with("open")).unwrap();
+        assert_eq!(open.size, 100);
+        assert!(items.iter().any(|item| item.path == locked));
+        assert_eq!(analyzer.skipped.count(), 1);
+
+        // What's inside can't be seen, so it mustn't look empty
+        assert!(empty.unwrap().is_empty());
+        assert_eq!(finder.skipped.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_skip_hidden_prunes_dot_entries() {
+        // tempdir names start with a dot themselves, so this also checks the root is kept
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join(".secret/keys/node_modules")).unwrap();
+        fs::create_dir(root.path().join("src")).unwrap();
+        write_file(&root.path().join(".secret/keys/id.bin"), 500);
+        write_file(&root.path().join(".secret/keys/node_modules/dep.bin"), 500);
+        write_file(&root.path().join("src/main.bin"), 100);
+        write_file(&root.path().join(".env"), 100);
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        analyzer.walk.skip_hidden = true;
+        let items = analyzer.analyze_directory(root.path(), 3).await.unwrap();
+        let mut paths: Vec<&Path> = items.iter().map(|item| item.path.as_path()).collect();
+        paths.sort();
+        assert_eq!(paths, vec![root.path().join("src"), root.path().join("src/main.bin")]);
+
+        let mut finder = LargeFileFinder::new();
+        finder.walk.skip_hidden = true;
+        let files = finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap();
+        assert_eq!(files.len(), 1);
+
+        let mut artifacts = DevArtifactFinder::new();
+        artifacts.walk.skip_hidden = true;
+        assert!(artifacts.find_artifacts(root.path()).await.unwrap().is_empty());
+        artifacts.walk.skip_hidden = false;
+        assert_eq!(artifacts.find_artifacts(root.path()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_large_file_progress_and_cancel() {
+        let root = tempfile::tempdir().unwrap();
+        for i in 0..5 {
+            write_file(&root.path().join(format!("file{}.bin", i)), 10);
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let mut finder = LargeFileFinder::new();
+        finder.progress = Some(tx);
+        let files = finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap();
+        assert_eq!(files.len(), 5);
+        assert_eq!(rx.try_iter().last(), Some(6)); // five files plus the root
+
+        finder.cancel.store(true, Ordering::Relaxed);
+        let files = finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap();
+        assert!(files.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_large_files_within_age_window() {
+        let root = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        for (name, age_days) in [("old.bin", 30), ("week.bin", 5), ("new.bin", 0)] {
+            let path = root.path().join(name);
+            write_file(&path, 10);
+            filetime::set_file_mtime(&path, filetime::FileTime::from_system_time(now - day * age_days)).unwrap();
+        }
+        let names = |files: Vec<FileItem>| {
+            let mut names: Vec<String> = files.iter().map(|f| f.path.file_name().unwrap().to_string_lossy().into_owned()).collect();
+            names.sort();
+            names
+        };
+
+        let mut finder = LargeFileFinder::new();
+        finder.modified_before = Some(now - day * 7);
+        assert_eq!(names(finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap()), ["old.bin"]);
+
+        finder.modified_before = None;
+        finder.modified_after = Some(now - day * 7);
+        assert_eq!(names(finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap()), ["new.bin", "week.bin"]);
+
+        finder.modified_before = Some(now - day);
+        assert_eq!(names(finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap()), ["week.bin"]);
+    }
+
+    #[tokio::test]
+    async fn test_protect_newer_than_leaves_out_recent_files() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("app")).unwrap();
+        write_file(&root.path().join("app/data.bin"), 100);
+        write_file(&root.path().join("old.bin"), 200);
+        crate::safety::backdate(root.path());
+        write_file(&root.path().join("app/fresh.log"), 50);
+        write_file(&root.path().join("new.bin"), 300);
+        let walk = WalkOptions { protect_newer_than: Some(SystemTime::now() - std::time::Duration::from_secs(60 * 60)), ..WalkOptions::default() };
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        analyzer.walk = walk.clone();
+        let mut items = analyzer.analyze_directory(root.path(), 1).await.unwrap();
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+        let sizes: Vec<(PathBuf, u64, Option<usize>)> = items.into_iter().map(|item| (item.path, item.size, item.item_count)).collect();
+        // The directory is still listed, just without its recent file
+        assert_eq!(sizes, vec![(root.path().join("app"), 100, Some(1)), (root.path().join("old.bin"), 200, None)]);
+
+        let mut finder = LargeFileFinder::new();
+        finder.allocated = false;
+        finder.walk = walk;
+        let files = finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap();
+        let names: Vec<&Path> = files.iter().map(|file| file.path.strip_prefix(root.path()).unwrap()).collect();
+        assert_eq!(names, [Path::new("old.bin"), Path::new("app/data.bin")]);
+    }
+
+    #[tokio::test]
+    async fn test_protect_newer_than_sets_aside_artifacts_with_recent_files() {
+        let root = tempfile::tempdir().unwrap();
+        for project in ["idle", "busy", "logged"] {
+            fs::create_dir_all(root.path().join(project).join("node_modules/logs")).unwrap();
+            write_file(&root.path().join(project).join("node_modules/pkg.js"), 100);
+        }
+        crate::safety::backdate(root.path());
+        write_file(&root.path().join("busy/node_modules/pkg.js"), 100);
+        write_file(&root.path().join("logged/node_modules/logs/fresh.log"), 10);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.walk.protect_newer_than = Some(SystemTime::now() - std::time::Duration::from_secs(60 * 60));
+        // Only entries the walk visits count, so an excluded recent log doesn't hold its artifact back
+        finder.walk.exclude = build_glob_set(&["**/logs".to_string()]).unwrap();
+        let mut found: Vec<PathBuf> = finder.find_artifacts(root.path()).await.unwrap().into_iter().map(|item| item.path).collect();
+        found.sort();
+        assert_eq!(found, [root.path().join("idle/node_modules"), root.path().join("logged/node_modules")]);
+
+        let busy = root.path().join("busy/node_modules");
+        let scan = finder.check_listed(vec![busy.clone()]).await.unwrap();
+        assert!(scan.artifacts.is_empty());
+        assert_eq!(scan.rejected, [(busy.clone(), "modified within --protect-newer-than".to_string())]);
+        assert!(finder.measure(&busy).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_protect_newer_than_keeps_recent_empty_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("old_empty")).unwrap();
+        fs::create_dir(root.path().join("parent")).unwrap();
+        crate::safety::backdate(root.path());
+        fs::create_dir(root.path().join("parent/new_empty")).unwrap();
+        filetime::set_file_mtime(root.path().join("parent"), filetime::FileTime::from_unix_time(1_000_000, 0)).unwrap();
+
+        let mut finder = EmptyDirFinder::new();
+        finder.walk.protect_newer_than = Some(SystemTime::now() - std::time::Duration::from_secs(60 * 60));
+        let found: Vec<PathBuf> = finder.find_empty_dirs(root.path()).await.unwrap().into_iter().map(|item| item.path).collect();
+        // parent would only be empty once new_empty is gone
+        assert_eq!(found, [root.path().join("old_empty")]);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_grouped_by_content() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("nested")).unwrap();
+        fs::write(root.path().join("a.txt"), b"same content").unwrap();
+        fs::write(root.path().join("nested/b.txt"), b"same content").unwrap();
+        fs::write(root.path().join("c.txt"), b"diff content").unwrap();
+
+        let groups = DuplicateFinder::new().find_duplicates(root.path()).await.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 2);
+        assert!(groups[0].iter().all(|item| !item.path.ends_with("c.txt")));
+        assert_eq!(wasted_space(&groups[0]), 12);
+    }
+
+    #[tokio::test]
+    async fn test_deep_finds_artifacts_nested_in_artifacts() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("web/node_modules/pkg/node_modules/dep")).unwrap();
+        fs::create_dir_all(root.path().join("api/.venv/lib")).unwrap();
+        write_file(&root.path().join("web/node_modules/pkg/index.js"), 100);
+        write_file(&root.path().join("web/node_modules/pkg/node_modules/dep/index.js"), 50);
+        write_file(&root.path().join("api/.venv/lib/site.py"), 10);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.allocated = false;
+        let found = |scan: ArtifactScan| {
+            let mut paths: Vec<PathBuf> = scan
+                .artifacts
+                .iter()
+                .map(|item| item.path.strip_prefix(root.path()).unwrap().to_owned())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let outer = found(finder.scan(root.path()).await.unwrap());
+        assert_eq!(outer, [PathBuf::from("api/.venv"), PathBuf::from("web/node_modules")]);
+
+        finder.deep = true;
+        let scan = finder.scan(root.path()).await.unwrap();
+        let nested = scan.artifacts.iter().find(|item| item.path.ends_with("pkg/node_modules")).unwrap();
+        assert_eq!(nested.size, 50);
+        assert_eq!(found(scan), [
+            PathBuf::from("api/.venv"),
+            PathBuf::from("web/node_modules"),
+            PathBuf::from("web/node_modules/pkg/node_modules"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_visited_count_matches_fixture_entries() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("web/node_modules/lib")).unwrap();
+        fs::create_dir_all(root.path().join("docs/img")).unwrap();
+        write_file(&root.path().join("web/package.json"), 10);
+        write_file(&root.path().join("web/node_modules/lib/index.js"), 10);
+        write_file(&root.path().join("docs/img/logo.png"), 10);
+        write_file(&root.path().join("notes.txt"), 10);
+        // web, web/node_modules, web/node_modules/lib, docs, docs/img and the four files
+        let entries = 9;
+
+        let analyzer = DirAnalyzer::new();
+        analyzer.analyze_directory(root.path(), 1).await.unwrap();
+        assert_eq!(analyzer.scanned.load(Ordering::Relaxed), entries);
+
+        let analyzer = DirAnalyzer::new();
+        let items = analyzer.analyze_directory(root.path(), 2).await.unwrap();
+        assert_eq!(items.len(), 6);
+        assert_eq!(analyzer.scanned.load(Ordering::Relaxed), entries);
+
+        let finder = LargeFileFinder::new();
+        finder.find_large_files(root.path(), 0, None, Vec::new()).await.unwrap();
+        assert_eq!(finder.scanned.load(Ordering::Relaxed), entries);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.min_size = 0;
+        let scan = finder.scan(root.path()).await.unwrap();
+        assert_eq!(scan.artifacts.len(), 1);
+        assert_eq!(finder.scanned.load(Ordering::Relaxed), entries);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.min_size = 0;
+        finder.deep = true;
+        finder.scan(root.path()).await.unwrap();
+        assert_eq!(finder.scanned.load(Ordering::Relaxed), entries);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_duplicates_match_serial_reference() {
+        let root = tempfile::tempdir().unwrap();
+        let block = vec![7u8; FIRST_BLOCK];
+        let large = |tail: &[u8]| [block.as_slice(), &vec![1u8; HASH_CHUNK], tail].concat();
+        let fixtures: Vec<(&str, Vec<u8>)> = vec![
+            ("small/a", b"same".to_vec()),
+            ("small/b", b"same".to_vec()),
+            ("small/c", b"diff".to_vec()),
+            ("block/a", block.clone()),
+            ("block/b", block.clone()),
+            ("large/a", large(b"end")),
+            ("large/b", large(b"end")),
+            ("large/c", large(b"end")),
+            ("large/tail", large(b"END")),
+            ("large/head", [b"x".as_slice(), &large(b"end")[1..]].concat()),
+        ];
+        for (name, contents) in &fixtures {
+            let path = root.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        let mut serial: HashMap<(u64, blake3::Hash), Vec<PathBuf>> = HashMap::new();
+        for (name, contents) in &fixtures {
+            let key = (contents.len() as u64, blake3::hash(contents));
+            serial.entry(key).or_default().push(root.path().join(name));
+        }
+        let mut expected: Vec<Vec<PathBuf>> = serial.into_values().filter(|group| group.len() > 1).collect();
+        for group in &mut expected {
+            group.sort();
+        }
+        expected.sort();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut finder = DuplicateFinder::new();
+        finder.progress = Some(sender);
+        let groups = finder.find_duplicates(root.path()).await.unwrap();
+        let mut found: Vec<Vec<PathBuf>> =
+            groups.iter().map(|group| group.iter().map(|item| item.path.clone()).collect()).collect();
+        found.sort();
+        assert_eq!(found, expected);
+        assert_eq!(found.len(), 3);
+        // Only the four large files that share a first block are hashed in full
+        drop(finder);
+        assert_eq!(receiver.iter().max(), Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_empty_dirs_only_reports_truly_empty() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("empty/inner/deepest")).unwrap();
+        fs::create_dir_all(root.path().join("full/sub")).unwrap();
+        fs::create_dir_all(root.path().join("full/blank")).unwrap();
+        write_file(&root.path().join("full/sub/file.txt"), 1);
+
+        let dirs = EmptyDirFinder::new().find_empty_dirs(root.path()).await.unwrap();
+        let found: Vec<PathBuf> = dirs
+            .iter()
+            .map(|item| item.path.strip_prefix(root.path()).unwrap().to_owned())
+            .collect();
+
+        assert_eq!(found.len(), 4);
+        assert_eq!(found[0], PathBuf::from("empty/inner/deepest"));
+        assert!(found.contains(&PathBuf::from("empty/inner")));
+        assert!(found.contains(&PathBuf::from("empty")));
+        assert!(found.contains(&PathBuf::from("full/blank")));
+        assert!(dirs.iter().all(|item| item.size == 0 && item.item_count == Some(0)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_protected_paths_are_not_scanned() {
+        assert!(DirAnalyzer::new().analyze_directory(Path::new("/"), 1).await.is_err());
+        assert!(LargeFileFinder::new().find_large_files(Path::new("/etc"), 1, None, Vec::new()).await.is_err());
+        assert!(DevArtifactFinder::new().find_artifacts(Path::new("/usr")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_analysis_returns_promptly() {
+        let root = tempfile::tempdir().unwrap();
+        for i in 0..50 {
+            let dir = root.path().join(format!("dir{}", i));
+            fs::create_dir_all(&dir).unwrap();
+            for j in 0..20 {
+                write_file(&dir.join(format!("{}.bin", j)), 10);
+            }
+        }
+
+        let analyzer = DirAnalyzer::new();
+        analyzer.cancel.store(true, Ordering::Relaxed);
+
+        let started = std::time::Instant::now();
+        let result = analyzer.analyze_directory(root.path(), 1).await;
+        assert!(result.unwrap_err().to_string().contains("cancelled"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        let mut finder = EmptyDirFinder::new();
+        finder.cancel = analyzer.cancel.clone();
+        assert!(finder.find_empty_dirs(root.path()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_large_files_size_band() {
+        let root = tempfile::tempdir().unwrap();
+        write_file(&root.path().join("below.bin"), 50);
+        write_file(&root.path().join("within.bin"), 500);
+        write_file(&root.path().join("above.bin"), 5000);
+
+        let mut finder = LargeFileFinder::new();
+        finder.allocated = false;
+        let files = finder.find_large_files(root.path(), 100, Some(1000), Vec::new()).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path.ends_with("within.bin"));
+
+        assert_eq!(finder.find_large_files(root.path(), 100, None, Vec::new()).await.unwrap().len(), 2);
+        assert!(finder.find_large_files(root.path(), 1000, Some(100), Vec::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_large_files_extension_filter() {
+        let root = tempfile::tempdir().unwrap();
+        write_file(&root.path().join("app.log"), 100);
+        write_file(&root.path().join("OLD.LOG"), 100);
+        write_file(&root.path().join("notes.txt"), 100);
+
+        let finder = LargeFileFinder::new();
+        let files = finder.find_large_files(root.path(), 1, None, vec!["log".to_string()]).await.unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|file| file.path.extension().unwrap().eq_ignore_ascii_case("log")));
+
+        assert_eq!(finder.find_large_files(root.path(), 1, None, Vec::new()).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_depth_includes_grandchildren() {
+        let root = tempfile::tempdir().unwrap();
+        let grandchild = root.path().join("child/grandchild");
+        fs::create_dir_all(&grandchild).unwrap();
+        write_file(&grandchild.join("data.bin"), 100);
+
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        let shallow = analyzer.analyze_directory(root.path(), 1).await.unwrap();
+        assert_eq!(shallow.len(), 1);
+        assert!(shallow.iter().all(|item| item.path != grandchild));
+
+        let deep = analyzer.analyze_directory(root.path(), 2).await.unwrap();
+        assert_eq!(deep.len(), 2);
+        assert!(deep.iter().any(|item| item.path == grandchild && item.size == 100));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sparse_file_allocated_size() {
+        let root = tempfile::tempdir().unwrap();
+        let sparse = File::create(root.path().join("sparse.img")).unwrap();
+        sparse.set_len(64 * 1024 * 1024).unwrap();
+
+        let metadata = fs::metadata(root.path().join("sparse.img")).unwrap();
+        assert_eq!(file_size(&metadata, false), 64 * 1024 * 1024);
+        assert!(file_size(&metadata, true) < 1024 * 1024);
+
+        let mut finder = LargeFileFinder::new();
+        assert!(finder.find_large_files(root.path(), 1024 * 1024, None, Vec::new()).await.unwrap().is_empty());
+        finder.allocated = false;
+        assert_eq!(finder.find_large_files(root.path(), 1024 * 1024, None, Vec::new()).await.unwrap().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hardlinks_counted_once() {
+        let root = tempfile::tempdir().unwrap();
+        write_file(&root.path().join("original.bin"), 1000);
+        fs::hard_link(root.path().join("original.bin"), root.path().join("link.bin")).unwrap();
+
+        let no_cancel = Arc::default();
+        assert_eq!(measure_dir(root.path(), &WalkOptions::default(), None, &no_cancel, &Skipped::default(), &Arc::default(), false, false).unwrap().size, 1000);
+        assert_eq!(measure_dir(root.path(), &WalkOptions::default(), None, &no_cancel, &Skipped::default(), &Arc::default(), false, true).unwrap().size, 2000);
+    }
+
+    #[tokio::test]
+    async fn test_config_artifact_dirs_found_and_removable() {
+        crate::artifacts::add_artifact_dirs(vec!["zz-generated".to_string()]);
+        let root = tempfile::tempdir().unwrap();
+        let generated = root.path().join("project/zz-generated");
+        fs::create_dir_all(&generated).unwrap();
+        write_file(&generated.join("out.bin"), 10);
+
+        let artifacts = DevArtifactFinder::new().find_artifacts(root.path()).await.unwrap();
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].path, generated);
+
+        crate::safety::backdate(&generated);
+        crate::cleanup::dev::remove_dir_all_safe(&generated, false).unwrap();
+        assert!(!generated.exists());
+    }
+
+    #[tokio::test]
+    async fn test_only_artifact_ignores_other_kinds() {
+        let root = tempfile::tempdir().unwrap();
+        let app = root.path().join("app");
+        fs::create_dir_all(app.join("node_modules")).unwrap();
+        fs::create_dir_all(app.join("target")).unwrap();
+        write_file(&app.join("Cargo.toml"), 10);
+        write_file(&app.join("node_modules/index.js"), 10);
+        write_file(&app.join("target/app.bin"), 10);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.selection = ArtifactSelection::new(vec!["node_modules".to_string()], Vec::new()).unwrap();
+        let artifacts = finder.find_artifacts(root.path()).await.unwrap();
+        assert_eq!(artifacts.iter().map(|a| &a.path).collect::<Vec<_>>(), [&app.join("node_modules")]);
+
+        finder.selection = ArtifactSelection::new(Vec::new(), vec!["node_modules".to_string()]).unwrap();
+        let artifacts = finder.find_artifacts(root.path()).await.unwrap();
+        assert_eq!(artifacts.iter().map(|a| &a.path).collect::<Vec<_>>(), [&app.join("target")]);
+        assert!(ArtifactSelection::new(vec!["node_module".to_string()], Vec::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nested_artifacts_not_reported() {
+        let root = tempfile::tempdir().unwrap();
+        let outer = root.path().join("app/node_modules");
+        fs::create_dir_all(outer.join("left-pad/node_modules/dep")).unwrap();
+        write_file(&outer.join("left-pad/node_modules/dep/index.js"), 10);
+
+        let artifacts = DevArtifactFinder::new().find_artifacts(root.path()).await.unwrap();
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].path, outer);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.walk.max_depth = Some(1);
+        assert!(finder.find_artifacts(root.path()).await.unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_one_file_system_prunes_other_devices() {
+        use std::os::unix::fs::MetadataExt;
+
+        let root = tempfile::tempdir().unwrap();
+        let mount = root.path().join("mnt");
+        fs::create_dir(&mount).unwrap();
+        write_file(&mount.join("remote.bin"), 10);
+
+        let dev = fs::metadata(&mount).unwrap().dev();
+        let metadata = fs::metadata(&mount).unwrap();
+        assert!(on_device(&metadata, None));
+        assert!(on_device(&metadata, Some(dev)));
+        assert!(!on_device(&metadata, Some(dev + 1)));
+
+        assert_eq!(root_device(root.path(), false), None);
+        assert_eq!(root_device(root.path(), true), Some(dev));
+
+        // Pretend the root lives on another device: everything below it is pruned
+        let walked: Vec<_> = walk_entries(WalkOptions::default().walker(root.path(), Some(dev + 1)), &Arc::default(), &Skipped::default(), &Arc::default())
+            .map(|e| e.into_path())
+            .collect();
+        assert_eq!(walked, vec![root.path().to_owned()]);
+
+        let walked = walk_entries(WalkOptions::default().walker(root.path(), Some(dev)), &Arc::default(), &Skipped::default(), &Arc::default()).count();
+        assert_eq!(walked, 3);
+    }
+
+    #[tokio::test]
+    async fn test_scan_all_merges_roots() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let small = first.path().join("web/node_modules");
+        let large = second.path().join("api/.venv");
+        fs::create_dir_all(&small).unwrap();
+        fs::create_dir_all(&large).unwrap();
+        write_file(&small.join("index.js"), 10);
+        write_file(&large.join("lib.so"), 100);
+
+        let mut finder = DevArtifactFinder::new();
+        finder.allocated = false;
+        let roots = vec![first.path().to_owned(), second.path().to_owned(), first.path().to_owned()];
+        let scan = finder.scan_all(&roots).await.unwrap();
+
+        let paths: Vec<_> = scan.artifacts.iter().map(|item| item.path.clone()).collect();
+        assert_eq!(paths, vec![large, small]);
+    }
+
+    #[tokio::test]
+    async fn test_item_count_is_files_only() {
+        let root = tempfile::tempdir().unwrap();
+        let modules = root.path().join("node_modules");
+        fs::create_dir_all(modules.join("left-pad/lib/util")).unwrap();
+        fs::create_dir_all(modules.join("empty/nested")).unwrap();
+        write_file(&modules.join("left-pad/package.json"), 10);
+        write_file(&modules.join("left-pad/lib/index.js"), 10);
+        write_file(&modules.join("left-pad/lib/util/pad.js"), 10);
+
+        let items = DirAnalyzer::new().analyze_directory(root.path(), 1).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].item_count, Some(3));
+
+        let artifacts = DevArtifactFinder::new().find_artifacts(root.path()).await.unwrap();
+        assert_eq!(artifacts[0].item_count, Some(3));
+    }
+
+    #[test]
+    fn test_walk_options_honor_exclude_and_depth() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("src/deep/deeper")).unwrap();
+        fs::create_dir_all(root.path().join("build")).unwrap();
+        write_file(&root.path().join("src/main.rs"), 10);
+        write_file(&root.path().join("src/deep/deeper/mod.rs"), 10);
+        write_file(&root.path().join("build/out.o"), 10);
+
+        let options = WalkOptions {
+            exclude: build_glob_set(&["**/build".to_string()]).unwrap(),
+            max_depth: Some(2),
+            ..WalkOptions::default()
+        };
+        let mut walked: Vec<PathBuf> = options
+            .walker(root.path(), None)
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().strip_prefix(root.path()).unwrap().to_owned())
+            .collect();
+        walked.sort();
+        assert_eq!(walked, ["", "src", "src/deep", "src/main.rs"].map(PathBuf::from));
+    }
+
+    #[test]
+    fn test_exclude_from_file_with_negation() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        fs::create_dir_all(project.join("logs")).unwrap();
+        fs::create_dir_all(project.join("build")).unwrap();
+        fs::create_dir_all(project.join("src/build")).unwrap();
+        for file in ["logs/app.log", "logs/keep.log", "build/out.o", "src/build/gen.rs", "src/main.rs"] {
+            write_file(&project.join(file), 10);
+        }
+        // Anchored at the file's directory, so only the top-level build/ goes
+        let ignore_file = root.path().join("shared.ignore");
+        fs::write(&ignore_file, "# shared excludes\n*.log\n!keep.log\n/project/build/\n").unwrap();
+
+        let options = WalkOptions {
+            exclude: build_glob_set(&["**/main.rs".to_string()]).unwrap(),
+            exclude_from: Some(build_ignore_file(&ignore_file).unwrap()),
+            ..WalkOptions::default()
+        };
+        let mut files: Vec<PathBuf> = options
+            .walker(&project, None)
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.path().strip_prefix(&project).unwrap().to_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["logs/keep.log", "src/build/gen.rs"].map(PathBuf::from));
+        assert!(build_ignore_file(&root.path().join("missing.ignore")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_listed_paths_honor_excludes() {
+        let root = tempfile::tempdir().unwrap();
+        let kept = root.path().join("web/node_modules");
+        let globbed = root.path().join("archive/app/node_modules");
+        let ignored = root.path().join("vendor/lib/node_modules");
+        for dir in [&kept, &globbed, &ignored] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let ignore_file = root.path().join("shared.ignore");
+        fs::write(&ignore_file, "/vendor/\n").unwrap();
+
+        let mut finder = DevArtifactFinder::new();
+        finder.walk.exclude = build_glob_set(&["**/archive".to_string()]).unwrap();
+        finder.walk.exclude_from = Some(build_ignore_file(&ignore_file).unwrap());
+        let scan = finder.check_listed(vec![kept.clone(), globbed.clone(), ignored.clone()]).await.unwrap();
+        assert_eq!(scan.artifacts.iter().map(|item| &item.path).collect::<Vec<_>>(), [&kept]);
+        let rejected: Vec<&PathBuf> = scan.rejected.iter().map(|(path, _)| path).collect();
+        assert_eq!(rejected, [&globbed, &ignored]);
+        assert!(scan.rejected.iter().all(|(_, reason)| reason == "left out by --exclude/--exclude-from"));
+    }
+
+    #[tokio::test]
+    async fn test_jobs_bound_concurrent_walks() {
+        // Returns the most walks that were ever in flight at once
+        async fn peak_in_flight(slots: usize, barrier: Option<Arc<std::sync::Barrier>>) -> usize {
+            let slots = Arc::new(Semaphore::new(slots));
+            let in_flight = Arc::new(AtomicUsize::new(0));
+            let peak = Arc::new(AtomicUsize::new(0));
+            let walk = || {
+                let (in_flight, peak, barrier) = (in_flight.clone(), peak.clone(), barrier.clone());
+                spawn_walk_in(slots.clone(), move || {
+                    peak.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    match barrier {
+                        Some(barrier) => drop(barrier.wait()),
+                        None => std::thread::sleep(std::time::Duration::from_millis(50)),
+                    }
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            };
+            let (first, second) = tokio::join!(walk(), walk());
+            first.unwrap();
+            second.unwrap();
+            peak.load(Ordering::SeqCst)
+        }
+
+        assert_eq!(peak_in_flight(1, None).await, 1);
+        // Both walks must be inside at once to get past the barrier
+        assert_eq!(peak_in_flight(2, Some(Arc::new(std::sync::Barrier::new(2)))).await, 2);
+    }
+}
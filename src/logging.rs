@@ -0,0 +1,105 @@
+use std::io::Write;
+use tracing::Level;
+use crate::color::PlainWriter;
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Log level for the `--quiet` / `-v` flags: errors only, info (default),
+/// debug or trace.
+pub fn level(quiet: bool, verbose: u8) -> Level {
+    match (quiet, verbose) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    }
+}
+
+/// Installs the global subscriber. Status messages go to stdout like the
+/// rest of the output (stderr with `prose_to_stderr`); warnings and errors go
+/// to stderr. With `no_color` the messages are stripped of emoji and escape
+/// sequences.
+pub fn init(level: Level, no_color: bool, prose_to_stderr: bool) {
+    let status = move || -> Box<dyn Write> {
+        if prose_to_stderr {
+            Box::new(std::io::stderr())
+        } else {
+            Box::new(std::io::stdout())
+        }
+    };
+    let result = if no_color {
+        let writer = (|| PlainWriter(std::io::stderr()))
+            .with_max_level(Level::WARN)
+            .or_else(move || PlainWriter(status()));
+        tracing::subscriber::set_global_default(subscriber(level, writer))
+    } else {
+        let writer = std::io::stderr
+            .with_max_level(Level::WARN)
+            .or_else(status);
+        tracing::subscriber::set_global_default(subscriber(level, writer))
+    };
+    result.expect("logging initialized twice");
+}
+
+/// Plain message-only formatting, since the messages carry their own emoji markers.
+fn subscriber<W>(level: Level, writer: W) -> impl tracing::Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(writer)
+        .without_time()
+        .with_target(false)
+        .with_level(false)
+        .finish()
+}
+
+#[cfg(test)]
+pub mod capture {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    /// Collects log output in memory so tests can inspect it.
+    #[derive(Clone, Default)]
+    pub struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Captured {
+        pub fn text(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs `f` on the current thread with logging at `level` into the returned buffer.
+    pub fn with_level<T>(level: tracing::Level, f: impl FnOnce() -> T) -> (T, String) {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = super::subscriber(level, move || writer.clone());
+        let result = tracing::subscriber::with_default(subscriber, f);
+        (result, captured.text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_flags() {
+        assert_eq!(level(true, 2), Level::ERROR);
+        assert_eq!(level(false, 0), Level::INFO);
+        assert_eq!(level(false, 1), Level::DEBUG);
+        assert_eq!(level(false, 3), Level::TRACE);
+    }
+}
@@ -1,10 +1,27 @@
-use clap::{Parser, Subcommand};
-use anyhow::Result;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::{anyhow, Result};
+use std::sync::atomic::Ordering;
+use config::Config;
+use discovery::WalkOptions;
+use cleanup::report::{CleanupReport, ReportFormat};
+use cli::output::{create_output, OutputFormat, SortKey};
+use color::outln;
 
+mod artifacts;
 mod cli;
+mod color;
+mod config;
 mod tui;
 mod cleanup;
 mod discovery;
+mod logging;
+mod safety;
+mod size_cache;
 mod utils;
 
 #[derive(Parser)]
@@ -14,6 +31,51 @@ mod utils;
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Print sizes in human-readable units (default)
+    #[arg(long, global = true, overrides_with = "bytes")]
+    human: bool,
+    /// Print sizes as exact byte counts
+    #[arg(long, global = true, overrides_with = "human")]
+    bytes: bool,
+    /// Digits after the decimal point in human-readable sizes [default: 1]
+    #[arg(long, global = true, value_name = "DIGITS", value_parser = clap::value_parser!(u8).range(0..=6))]
+    precision: Option<u8>,
+    /// Count human-readable sizes in powers of 1000 instead of 1024
+    #[arg(long, global = true)]
+    si: bool,
+    /// Only print errors and final summaries
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Print more detail; repeat for trace output (-vv)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Move removed items to the system trash instead of deleting them permanently
+    #[arg(long, global = true)]
+    trash: bool,
+    /// Stay on the filesystem of the starting path, like `du -x` (list, tree, large, histogram, dupes, empty-dirs, dev-clean, watch)
+    #[arg(short = 'x', long, global = true)]
+    one_file_system: bool,
+    /// Skip hidden files and directories (names starting with `.`) without descending into them (list, tree, large, histogram, dupes, empty-dirs, dev-clean, watch)
+    #[arg(long, global = true)]
+    skip_hidden: bool,
+    /// Plain output without colors or emoji (also enabled by the NO_COLOR env var)
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// Write the results to this file instead of stdout (list, large, dupes)
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+    /// Never report or remove files modified more recently than this, nor remove directories holding one (e.g. 24h, 7d; list, tree, large, histogram, dupes, empty-dirs, dev-clean, watch, temp)
+    #[arg(long, global = true, value_parser = utils::parse_duration)]
+    protect_newer_than: Option<std::time::Duration>,
+    /// Walk at most this many directory trees at once [default: number of CPUs]
+    #[arg(short, long, global = true, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    jobs: Option<u64>,
+    /// Also skip paths matching the gitignore-style patterns in this file (list, tree, large, histogram, dupes, empty-dirs, dev-clean, watch)
+    #[arg(long, global = true, value_name = "FILE")]
+    exclude_from: Option<PathBuf>,
+    /// Use the defaults from [profiles.NAME] in the config file; flags still override them
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -25,67 +87,670 @@ enum Commands {
         /// Show what would be cleaned without actually removing
         #[arg(long)]
         dry_run: bool,
+        /// Skip confirmation prompts and proceed with removal
+        #[arg(short, long)]
+        yes: bool,
+        /// Prune the entire build cache, not just dangling layers
+        #[arg(long)]
+        all: bool,
+        /// Only remove stopped containers older than this (e.g. 24h, 7d)
+        #[arg(long, value_parser = utils::parse_duration)]
+        until: Option<std::time::Duration>,
+        /// Only report how much space each kind of resource could free; removes nothing
+        #[arg(long, conflicts_with_all = ["dry_run", "yes", "all", "until"])]
+        analyze: bool,
+        /// Output format of the cleanup summary
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
     },
     /// Cleanup system temporary folders
     Temp {
         /// Show what would be cleaned without actually removing
         #[arg(long)]
         dry_run: bool,
+        /// Skip confirmation prompts and proceed with removal
+        #[arg(short, long)]
+        yes: bool,
+        /// Ask about each item separately and only remove the confirmed ones
+        #[arg(short, long)]
+        interactive: bool,
+        /// Only print the totals, not every directory and file found
+        #[arg(long, conflicts_with = "interactive")]
+        summary_only: bool,
+        /// Only clean files last modified longer ago than this (e.g. 12h, 2w; a bare number is days)
+        #[arg(long, value_parser = utils::parse_age)]
+        older_than: Option<std::time::Duration>,
+        /// How many directory levels to search inside each temp folder
+        #[arg(long, default_value_t = 2)]
+        depth: usize,
+        /// Also clear browser caches (Chrome, Chromium, Brave, Edge, Firefox)
+        #[arg(long)]
+        browser: bool,
+        /// Also clear Windows Update downloads and Prefetch (Windows only, needs administrator rights)
+        #[arg(long)]
+        system: bool,
+        /// Also treat file names matching this glob as temp files, e.g. '*.bak' (repeatable)
+        #[arg(long)]
+        pattern: Vec<String>,
+        /// Only use the --pattern globs, not the built-in temp file name heuristics
+        #[arg(long, requires = "pattern")]
+        only_pattern: bool,
+        /// Ask to type "yes" instead of y/n when removing more than this [default: 5GB]
+        #[arg(long, value_name = "SIZE")]
+        confirm_threshold: Option<String>,
+        /// Output format of the cleanup summary
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
     },
     /// List directories with sizes for selective cleanup
     List {
+        /// Paths to analyze (default: current directory)
+        paths: Vec<String>,
+        /// Show top N largest items [default: 20]
+        #[arg(short, long)]
+        top: Option<usize>,
+        /// Order results by this key
+        #[arg(long, value_enum, default_value_t = SortKey::Size)]
+        sort: SortKey,
+        /// Reverse the sort order
+        #[arg(long)]
+        reverse: bool,
+        /// How many directory levels to break down
+        #[arg(short, long, default_value_t = 1)]
+        depth: usize,
+        /// Hide directories holding fewer than N files
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        min_count: usize,
+        /// Fold entries smaller than this (e.g. "100MB") into one "(N smaller items)" row (table format only)
+        #[arg(long, value_name = "SIZE")]
+        collapse_below: Option<String>,
+        /// Show paths relative to the listed path (only when listing a single path)
+        #[arg(long)]
+        relative: bool,
+        /// Count every hardlink to a file separately instead of once
+        #[arg(long)]
+        count_hardlinks: bool,
+        /// Descend into symlinked directories (link cycles are skipped)
+        #[arg(short = 'L', long)]
+        follow_symlinks: bool,
+        /// Reuse directory sizes from earlier runs while the directory's mtime is unchanged
+        #[arg(long)]
+        cache: bool,
+        /// Measure apparent file length instead of allocated disk usage
+        #[arg(long, overrides_with = "allocated")]
+        apparent: bool,
+        /// Measure allocated disk usage (default on Unix)
+        #[arg(long, overrides_with = "apparent")]
+        allocated: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+    /// Print a du-style tree of directory sizes
+    Tree {
         /// Path to analyze (default: current directory)
         path: Option<String>,
-        /// Show top N largest items
-        #[arg(short, long, default_value = "20")]
-        top: usize,
+        /// How many directory levels to show
+        #[arg(short, long, default_value_t = 2)]
+        depth: usize,
+        /// Collapse entries smaller than this (e.g. "100MB") into an "(other)" line
+        #[arg(long)]
+        threshold: Option<String>,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+    /// Show how files are spread across size buckets
+    Histogram {
+        /// Path to analyze (default: current directory)
+        path: Option<String>,
+        /// Comma-separated upper bounds of the buckets; the last bucket holds everything bigger
+        #[arg(long, default_value = cli::histogram::DEFAULT_BUCKETS)]
+        buckets: String,
+        /// Draw a bar per bucket, scaled to the bucket with the most bytes
+        #[arg(long)]
+        bars: bool,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+    /// Re-scan a directory on an interval and show which entries grow
+    Watch {
+        /// Path to watch (default: current directory)
+        path: Option<String>,
+        /// Time between scans (e.g. 30s, 5m)
+        #[arg(long, default_value = "30s", value_parser = utils::parse_duration)]
+        interval: std::time::Duration,
+        /// Measure apparent file length instead of allocated disk usage
+        #[arg(long)]
+        apparent: bool,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
     },
     /// Find large files and directories
     Large {
+        /// Paths to search (default: current directory)
+        paths: Vec<String>,
+        /// Minimum size threshold (e.g., "100MB", "1GB") [default: 100MB]
+        #[arg(short, long, alias = "min-size")]
+        size: Option<String>,
+        /// Maximum size threshold (e.g., "1GB"); only files within the range are shown
+        #[arg(long)]
+        max_size: Option<String>,
+        /// Only include files with this extension, e.g. "log" (repeatable)
+        #[arg(long = "ext")]
+        extensions: Vec<String>,
+        /// Only include files last modified longer ago than this (e.g. 90d; a bare number is days)
+        #[arg(long, value_parser = utils::parse_age)]
+        older_than: Option<std::time::Duration>,
+        /// Only include files modified within this long (e.g. 7d); combine with --older-than for a window
+        #[arg(long, value_parser = utils::parse_age)]
+        newer_than: Option<std::time::Duration>,
+        /// Show each containing directory once, with the count and total size of its large files
+        #[arg(long)]
+        group_by_dir: bool,
+        /// Only show the N largest files (or directories with --group-by-dir); 0 shows all
+        #[arg(short, long, default_value_t = 0)]
+        top: usize,
+        /// Add a "Last Modified" column with each file's age, e.g. "3 months ago"
+        #[arg(long)]
+        show_age: bool,
+        /// Afterwards, pick a result and open its folder in the file manager
+        #[arg(long)]
+        reveal: bool,
+        /// Descend into symlinked directories (link cycles are skipped)
+        #[arg(short = 'L', long)]
+        follow_symlinks: bool,
+        /// Measure apparent file length instead of allocated disk usage
+        #[arg(long, overrides_with = "allocated")]
+        apparent: bool,
+        /// Measure allocated disk usage (default on Unix)
+        #[arg(long, overrides_with = "apparent")]
+        allocated: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+    },
+    /// Show total, used and available space of the filesystem holding a path
+    #[command(alias = "df")]
+    Stats {
+        /// Path on the filesystem to report (default: current directory)
+        path: Option<String>,
+    },
+    /// Find duplicate files by content
+    Dupes {
         /// Path to search (default: current directory)
         path: Option<String>,
-        /// Minimum size threshold (e.g., "100MB", "1GB")
-        #[arg(short, long, default_value = "100MB")]
-        size: String,
+        /// Only show the N groups wasting the most space; 0 shows all
+        #[arg(short, long, default_value_t = 0)]
+        top: usize,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
     },
-    /// Discover and cleanup development artifacts (node_modules, .venv)
-    DevClean {
+    /// Find and remove directories that contain no files
+    EmptyDirs {
         /// Path to search (default: current directory)
         path: Option<String>,
         /// Show what would be cleaned without actually removing
         #[arg(long)]
         dry_run: bool,
+        /// Skip confirmation prompts and proceed with removal
+        #[arg(short, long)]
+        yes: bool,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+        /// Output format of the cleanup summary
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Restore (or list) what the last temp/dev-clean run removed
+    Undo,
+    /// Find and remove global package caches (cargo, npm, pip, go; maven on request)
+    Cache {
+        /// Show what would be cleaned without actually removing
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip confirmation prompts and proceed with removal
+        #[arg(short, long)]
+        yes: bool,
+        /// Only clean this cache, e.g. npm (repeatable); maven is only cleaned when named here
+        #[arg(long = "only-cache", value_name = "NAME", conflicts_with = "exclude_caches")]
+        only_caches: Vec<String>,
+        /// Leave this cache alone, e.g. go (repeatable)
+        #[arg(long = "exclude-cache", value_name = "NAME")]
+        exclude_caches: Vec<String>,
+        /// Ask to type "yes" instead of y/n when removing more than this [default: 5GB]
+        #[arg(long, value_name = "SIZE")]
+        confirm_threshold: Option<String>,
+        /// Output format of the cleanup summary
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Show how much space the system trash takes, or empty it
+    Trash {
+        /// Show the trash size (the default)
+        #[arg(long)]
+        size: bool,
+        /// Permanently delete everything in the trash after confirmation
+        #[arg(long, conflicts_with = "size")]
+        empty: bool,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Discover and cleanup development artifacts (node_modules, .venv)
+    #[command(group(clap::ArgGroup::new("unattended").args(["dry_run", "yes"]).multiple(true)))]
+    DevClean {
+        /// Paths to search (default: current directory)
+        paths: Vec<String>,
+        /// Check and remove the artifact directories listed in this file, one per line, instead of searching
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["paths", "stdin"])]
+        from_file: Option<PathBuf>,
+        /// Like --from-file, reading the list from standard input (needs --yes or --dry-run)
+        #[arg(long, conflicts_with = "paths", requires = "unattended")]
+        stdin: bool,
+        /// Show what would be cleaned without actually removing
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip confirmation prompts and proceed with removal
+        #[arg(short, long)]
+        yes: bool,
+        /// When removal fails for lack of permissions, offer to retry with sudo (Unix) after a separate confirmation
+        #[arg(long)]
+        elevate: bool,
+        /// Skip artifacts whose size changed by more than this percentage since the scan
+        #[arg(long, value_name = "PERCENT", default_value_t = 10.0, value_parser = utils::parse_percent)]
+        max_size_change: f64,
+        /// Ask separately before removing any single artifact larger than this (e.g. 50GB), even with --yes
+        #[arg(long, value_name = "SIZE")]
+        max_artifact_size: Option<String>,
+        /// Skip artifacts larger than --max-artifact-size instead of asking about them
+        #[arg(long, requires = "max_artifact_size")]
+        skip_oversize: bool,
+        /// Ask about each item separately and only remove the confirmed ones
+        #[arg(short, long)]
+        interactive: bool,
+        /// Only print the totals, not every artifact found
+        #[arg(long, conflicts_with = "interactive")]
+        summary_only: bool,
+        /// List this many of the largest artifacts right above the confirmation prompt (0 to turn off)
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        preview: usize,
+        /// Never remove artifact directories that git tracks (not covered by .gitignore)
+        #[arg(long)]
+        respect_gitignore: bool,
+        /// Only look this many directory levels deep for artifacts
+        #[arg(long)]
+        max_depth: Option<usize>,
+        /// Hide artifacts smaller than this (e.g. 10MB); 0 shows everything [default: 1MB]
+        #[arg(long)]
+        min_size: Option<String>,
+        /// Also search inside artifact directories and report the artifacts nested in them
+        #[arg(long)]
+        deep: bool,
+        /// Only look for artifact directories with this name, e.g. node_modules (repeatable)
+        #[arg(long = "only-artifact", value_name = "NAME", conflicts_with = "exclude_artifacts")]
+        only_artifacts: Vec<String>,
+        /// Leave artifact directories with this name alone, e.g. dist (repeatable)
+        #[arg(long = "exclude-artifact", value_name = "NAME")]
+        exclude_artifacts: Vec<String>,
+        /// Ask to type "yes" instead of y/n when removing more than this [default: 5GB]
+        #[arg(long, value_name = "SIZE")]
+        confirm_threshold: Option<String>,
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+        /// Output format of the cleanup summary
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Print a shell completion script to stdout
+    #[command(hide = true)]
+    Completions {
+        shell: Shell,
     },
 }
 
+impl Commands {
+    /// The `--format` of cleanup commands that have one.
+    fn report_format(&self) -> Option<ReportFormat> {
+        match self {
+            Commands::Docker { format, .. }
+            | Commands::Temp { format, .. }
+            | Commands::EmptyDirs { format, .. }
+            | Commands::Cache { format, .. }
+            | Commands::DevClean { format, .. } => Some(*format),
+            _ => None,
+        }
+    }
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() -> Result<ExitCode> {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            err.print()?;
+            return Ok(ExitCode::from(parse_error_exit_code(&err)));
+        }
+    };
+    color::set_no_color(cli.no_color);
+    color::set_prose_to_stderr(cli.command.as_ref().and_then(Commands::report_format) == Some(ReportFormat::Json));
+    logging::init(logging::level(cli.quiet, cli.verbose), color::no_color(), color::prose_to_stderr());
+    let mut config = Config::load()?;
+    if let Some(name) = &cli.profile {
+        config.use_profile(name)?;
+    }
+    safety::add_protected_paths(config.protected_paths.clone());
+    safety::set_in_use_window(config.resolve_in_use_window());
+    if cli.precision.is_some() || cli.si {
+        let defaults = utils::FormatOptions::default();
+        utils::set_size_format(utils::FormatOptions {
+            decimals: cli.precision.map_or(defaults.decimals, usize::from),
+            base: if cli.si { utils::SizeBase::Decimal } else { utils::SizeBase::Binary },
+            ..defaults
+        });
+    }
+    if let Some(jobs) = cli.jobs {
+        discovery::set_jobs(jobs as usize);
+    }
+    artifacts::add_artifact_dirs(config.artifact_dirs.clone());
+
+    tokio::select! {
+        result = run_command(cli, config) => result.map(ExitCode::from),
+        Ok(()) = tokio::signal::ctrl_c() => {
+            // A failed handler registration disables this branch instead of
+            // passing for an interrupt. Running walks see the flag and stop;
+            // the runtime waits for them on exit
+            utils::interrupt_flag().store(true, Ordering::Relaxed);
+            tui::restore_terminal()?;
+            Err(anyhow!("Interrupted"))
+        }
+    }
+}
+
+async fn run_command(cli: Cli, config: Config) -> Result<u8> {
+    if cli.output.is_some() && !matches!(cli.command, Some(Commands::List { .. } | Commands::Large { .. } | Commands::Dupes { .. })) {
+        return Err(anyhow!("--output is only supported by list, large and dupes"));
+    }
+    if cli.exclude_from.is_some() && !matches!(cli.command, Some(
+        Commands::List { .. } | Commands::Tree { .. } | Commands::Large { .. } | Commands::Histogram { .. }
+        | Commands::Dupes { .. } | Commands::EmptyDirs { .. } | Commands::DevClean { .. } | Commands::Watch { .. }
+    )) {
+        return Err(anyhow!("--exclude-from is only supported by list, tree, large, histogram, dupes, empty-dirs, dev-clean and watch"));
+    }
+    let exclude_from = cli.exclude_from.as_deref().map(discovery::build_ignore_file).transpose()?;
+    if cli.protect_newer_than.is_some() && !matches!(cli.command, Some(
+        Commands::List { .. } | Commands::Tree { .. } | Commands::Large { .. } | Commands::Histogram { .. } | Commands::Dupes { .. }
+        | Commands::EmptyDirs { .. } | Commands::DevClean { .. } | Commands::Watch { .. } | Commands::Temp { .. }
+    )) {
+        return Err(anyhow!("--protect-newer-than is only supported by list, tree, large, histogram, dupes, empty-dirs, dev-clean, watch and temp"));
+    }
+    // Anything modified after this is left alone; an age reaching back
+    // before the epoch covers everything
+    let protect_newer_than = cli.protect_newer_than.map(|age| SystemTime::now().checked_sub(age).unwrap_or(UNIX_EPOCH));
+    let base_walk = WalkOptions {
+        exclude_from,
+        skip_hidden: cli.skip_hidden,
+        one_file_system: cli.one_file_system,
+        protect_newer_than,
+        ..WalkOptions::default()
+    };
+    // The global walk flags plus a command's own --exclude globs
+    let walk = |exclude: Vec<String>| -> Result<WalkOptions> {
+        Ok(WalkOptions { exclude: discovery::build_glob_set(&config.resolve_exclude(exclude))?, ..base_walk.clone() })
+    };
 
     match cli.command {
         Some(Commands::Tui) => {
-            tui::run().await?;
+            tui::run(cli.trash).await?;
+        }
+        Some(Commands::Docker { analyze: true, .. }) => {
+            cleanup::docker::analyze(cli.bytes).await?;
+        }
+        Some(Commands::Docker { dry_run, yes, all, until, format, .. }) => {
+            let report = cleanup::docker::cleanup(dry_run, all, until, yes).await?;
+            print_report(&report, "Docker resources", "docker", format, cli.bytes)?;
+            return Ok(exit_code(&report));
+        }
+        Some(Commands::Temp { dry_run, yes, interactive, summary_only, older_than, depth, browser, system, pattern, only_pattern, confirm_threshold, format }) => {
+            let report = cleanup::temp::cleanup(dry_run, older_than, protect_newer_than, depth, browser, system, pattern, only_pattern, cli.trash, yes, interactive, summary_only, config.resolve_confirm_threshold(confirm_threshold)).await?;
+            print_report(&report, "temporary files", "temp", format, cli.bytes)?;
+            return Ok(exit_code(&report));
+        }
+        Some(Commands::List { paths, top, sort, reverse, depth, min_count, collapse_below, relative, count_hardlinks, follow_symlinks, cache, apparent, format, exclude, .. }) => {
+            let mut out = open_output(cli.output.as_deref())?;
+            let options = cli::list::ListOptions {
+                top: config.resolve_top(top),
+                sort,
+                reverse,
+                depth,
+                min_count,
+                collapse_below,
+                relative,
+                count_hardlinks,
+                use_cache: cache,
+                allocated: !apparent,
+                raw_bytes: cli.bytes,
+            };
+            cli::list::run(config.resolve_paths(paths), options, WalkOptions { follow_symlinks, ..walk(exclude)? }, format, &mut out).await?;
+            finish_output(out, cli.output.as_deref())?;
+        }
+        Some(Commands::Tree { path, depth, threshold, exclude }) => {
+            cli::tree::run(path, depth, threshold, walk(exclude)?, cli.bytes).await?;
         }
-        Some(Commands::Docker { dry_run }) => {
-            cleanup::docker::cleanup(dry_run).await?;
+        Some(Commands::Histogram { path, buckets, bars, exclude }) => {
+            cli::histogram::run(path, buckets, bars, walk(exclude)?, cli.bytes).await?;
         }
-        Some(Commands::Temp { dry_run }) => {
-            cleanup::temp::cleanup(dry_run).await?;
+        Some(Commands::Watch { path, interval, apparent, exclude }) => {
+            cli::watch::run(path, interval, !apparent, walk(exclude)?, cli.bytes).await?;
         }
-        Some(Commands::List { path, top }) => {
-            cli::list::run(path, top).await?;
+        Some(Commands::Large { paths, size, max_size, extensions, older_than, newer_than, group_by_dir, top, show_age, reveal, follow_symlinks, apparent, format, exclude, .. }) => {
+            let mut out = open_output(cli.output.as_deref())?;
+            cli::large::run(config.resolve_paths(paths), config.resolve_size(size), max_size, extensions, older_than, newer_than, group_by_dir, top, show_age, reveal, !apparent, cli.bytes, WalkOptions { follow_symlinks, ..walk(exclude)? }, format, &mut out).await?;
+            finish_output(out, cli.output.as_deref())?;
         }
-        Some(Commands::Large { path, size }) => {
-            cli::large::run(path, size).await?;
+        Some(Commands::Stats { path }) => {
+            cli::stats::run(path, cli.bytes).await?;
         }
-        Some(Commands::DevClean { path, dry_run }) => {
-            cleanup::dev::cleanup(path, dry_run).await?;
+        Some(Commands::Dupes { path, top, exclude }) => {
+            let mut out = open_output(cli.output.as_deref())?;
+            cli::dupes::run(path, top, cli.bytes, walk(exclude)?, &mut out).await?;
+            finish_output(out, cli.output.as_deref())?;
+        }
+        Some(Commands::EmptyDirs { path, dry_run, yes, exclude, format }) => {
+            let report = cleanup::empty::cleanup(path, dry_run, walk(exclude)?, cli.trash, yes).await?;
+            print_report(&report, "empty directories", "empty-dirs", format, cli.bytes)?;
+            return Ok(exit_code(&report));
+        }
+        Some(Commands::Completions { shell }) => {
+            write_completions(shell, &mut std::io::stdout())?;
+        }
+        Some(Commands::Undo) => {
+            cleanup::undo::run(cli.bytes).await?;
+        }
+        Some(Commands::Cache { dry_run, yes, only_caches, exclude_caches, confirm_threshold, format }) => {
+            let report = cleanup::caches::cleanup(dry_run, only_caches, exclude_caches, cli.trash, yes, config.resolve_confirm_threshold(confirm_threshold), cli.bytes).await?;
+            print_report(&report, "tool caches", "cache", format, cli.bytes)?;
+            return Ok(exit_code(&report));
+        }
+        Some(Commands::Trash { empty, yes, .. }) => {
+            cleanup::trash_bin::run(empty, yes, cli.bytes).await?;
+        }
+        Some(Commands::DevClean { paths, from_file, stdin, dry_run, yes, elevate, max_size_change, max_artifact_size, skip_oversize, interactive, summary_only, preview, respect_gitignore, max_depth, min_size, deep, only_artifacts, exclude_artifacts, confirm_threshold, exclude, format }) => {
+            let listed = cleanup::dev::listed_paths(from_file.as_deref(), stdin)?;
+            let options = cleanup::dev::DevCleanOptions {
+                dry_run,
+                respect_gitignore,
+                min_size: config.resolve_min_size(min_size),
+                deep,
+                only_artifacts,
+                exclude_artifacts,
+                use_trash: cli.trash,
+                elevate,
+                max_size_change,
+                max_artifact_size,
+                skip_oversize,
+                assume_yes: yes,
+                interactive,
+                summary_only,
+                preview,
+                confirm_threshold: config.resolve_confirm_threshold(confirm_threshold),
+            };
+            let report = cleanup::dev::cleanup(config.resolve_paths(paths), listed, WalkOptions { max_depth, ..walk(exclude)? }, options).await?;
+            print_report(&report, "development artifacts", "dev", format, cli.bytes)?;
+            return Ok(exit_code(&report));
         }
         None => {
             // No subcommand provided, launch TUI by default
-            tui::run().await?;
+            tui::run(cli.trash).await?;
+        }
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// Exit status of a run. Errors exit with 1 through `main` returning `Err`.
+const EXIT_SUCCESS: u8 = 0;
+/// An error, including a command line clap could not parse.
+const EXIT_ERROR: u8 = 1;
+/// A cleanup found nothing to clean.
+const EXIT_NOTHING_FOUND: u8 = 2;
+/// A cleanup could not remove some of what it attempted to.
+const EXIT_PARTIAL_FAILURE: u8 = 3;
+
+/// Exit status for the outcome of a cleanup. A dry run or a declined prompt
+/// that did find something still counts as success.
+fn exit_code(report: &CleanupReport) -> u8 {
+    if !report.failed.is_empty() {
+        EXIT_PARTIAL_FAILURE
+    } else if report.nothing_found {
+        EXIT_NOTHING_FOUND
+    } else {
+        EXIT_SUCCESS
+    }
+}
+
+/// Exit status for a command line that did not parse. Clap would exit with 2,
+/// which already means nothing was found; `--help` and `--version` stay at 0.
+fn parse_error_exit_code(err: &clap::Error) -> u8 {
+    if err.exit_code() == 0 {
+        EXIT_SUCCESS
+    } else {
+        EXIT_ERROR
+    }
+}
+
+/// Where list, large and dupes print their results: the `--output` file or stdout.
+fn open_output(path: Option<&Path>) -> Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(create_output(path)?),
+        None => Box::new(std::io::stdout()),
+    })
+}
+
+/// Flushes the results and says where they went when that wasn't stdout.
+fn finish_output(mut out: Box<dyn Write>, path: Option<&Path>) -> Result<()> {
+    out.flush()?;
+    if let Some(path) = path {
+        outln!("📝 Results written to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Prints the summary of a cleanup run, or with `--format json` the whole
+/// report tagged with `kind`. The text summary is skipped when no removal was
+/// attempted.
+fn print_report(report: &CleanupReport, what: &str, kind: &str, format: ReportFormat, raw_bytes: bool) -> Result<()> {
+    if format == ReportFormat::Json {
+        return report.write_json(std::io::stdout().lock(), kind);
+    }
+    if report.is_empty() {
+        return Ok(());
+    }
+
+    outln!("\n📊 Cleanup Summary:");
+    outln!("   Removed {} {}", report.removed.len(), what);
+    if report.bytes_freed > 0 {
+        outln!("   Freed up {}", utils::format_size_as(report.bytes_freed, raw_bytes));
+    }
+    if !report.skipped.is_empty() {
+        outln!("   Skipped {}:", report.skipped.len());
+        for (path, reason) in &report.skipped {
+            outln!("      {}: {}", path.display(), reason);
+        }
+    }
+    if report.failed.is_empty() {
+        outln!("\n✅ Cleanup completed!");
+    } else {
+        outln!("   Failed to remove {}:", report.failed.len());
+        for (path, reason) in &report.failed {
+            outln!("      {}: {}", path.display(), reason);
         }
     }
+    Ok(())
+}
 
+fn write_completions(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bash_completions_list_subcommands() {
+        let mut buffer = Vec::new();
+        write_completions(Shell::Bash, &mut buffer).unwrap();
+        let script = String::from_utf8(buffer).unwrap();
+        assert!(script.contains("docker"));
+        assert!(script.contains("dev-clean"));
+    }
+
+    #[test]
+    fn test_stdin_needs_yes_or_dry_run() {
+        assert!(Cli::try_parse_from(["safe-clean", "dev-clean", "--stdin"]).is_err());
+        assert!(Cli::try_parse_from(["safe-clean", "dev-clean", "--stdin", "--yes"]).is_ok());
+        assert!(Cli::try_parse_from(["safe-clean", "dev-clean", "--stdin", "--dry-run"]).is_ok());
+        assert!(Cli::try_parse_from(["safe-clean", "dev-clean", "--dry-run", "--yes"]).is_ok());
+    }
+
+    #[test]
+    fn test_usage_errors_exit_with_1() {
+        let code = |args: &[&str]| parse_error_exit_code(&Cli::try_parse_from(args).err().unwrap());
+        assert_eq!(code(&["safe-clean", "list", "--no-such-flag"]), EXIT_ERROR);
+        assert_eq!(code(&["safe-clean", "watch", "--interval", "5x"]), EXIT_ERROR);
+        assert_eq!(code(&["safe-clean", "--help"]), EXIT_SUCCESS);
+        assert_eq!(code(&["safe-clean", "--version"]), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn test_exit_code_for_cleanup_outcome() {
+        let mut report = CleanupReport::default();
+        report.removed.push((PathBuf::from("/tmp/a.tmp"), 100));
+        assert_eq!(exit_code(&report), EXIT_SUCCESS);
+
+        report.skipped.push((PathBuf::from("/tmp/c.tmp"), "it changed since scanning".to_string()));
+        assert_eq!(exit_code(&report), EXIT_SUCCESS);
+
+        report.failed.push((PathBuf::from("/tmp/b.tmp"), "Permission denied".to_string()));
+        assert_eq!(exit_code(&report), EXIT_PARTIAL_FAILURE);
+        assert_eq!(EXIT_PARTIAL_FAILURE, 3);
+
+        assert_eq!(exit_code(&CleanupReport::nothing_found(0)), EXIT_NOTHING_FOUND);
+        assert_eq!(exit_code(&CleanupReport::after_scan(0)), EXIT_SUCCESS);
+    }
+}
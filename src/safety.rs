@@ -0,0 +1,287 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// System directories that are never scanned or cleaned, including everything below them.
+#[cfg(unix)]
+const SYSTEM_PATHS: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc", "/sbin", "/sys", "/usr",
+    "/System", "/Library", "/private/etc", "/private/var",
+];
+
+#[cfg(windows)]
+const SYSTEM_PATHS: &[&str] = &[
+    "C:\\Windows",
+    "C:\\Program Files",
+    "C:\\Program Files (x86)",
+    "C:\\ProgramData",
+];
+
+#[cfg(not(any(unix, windows)))]
+const SYSTEM_PATHS: &[&str] = &[];
+
+/// Cache directories inside the system paths whose contents may still be
+/// cleaned by `temp` (the directories themselves stay protected).
+#[cfg(windows)]
+const CLEANABLE_SYSTEM_DIRS: &[&str] = &[
+    "C:\\Windows\\Temp",
+    "C:\\Windows\\Prefetch",
+    "C:\\Windows\\SoftwareDistribution\\Download",
+];
+
+/// macOS keeps per-user temp directories and `/var/tmp` under `/private/var`.
+#[cfg(unix)]
+const CLEANABLE_SYSTEM_DIRS: &[&str] = &["/private/var/folders", "/private/var/tmp"];
+
+#[cfg(not(any(unix, windows)))]
+const CLEANABLE_SYSTEM_DIRS: &[&str] = &[];
+
+static EXTRA_PROTECTED: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+/// Anything modified this recently is assumed to still be written to.
+pub const DEFAULT_IN_USE_WINDOW: Duration = Duration::from_secs(60);
+
+static IN_USE_WINDOW: OnceLock<Duration> = OnceLock::new();
+
+/// Adds the config file's `protected_paths` to the built-in list. Only the
+/// first call has an effect.
+pub fn add_protected_paths(paths: Vec<PathBuf>) {
+    let _ = EXTRA_PROTECTED.set(paths);
+}
+
+/// Refuses to scan a filesystem root or anything inside a protected path.
+pub fn check_scan(path: &Path) -> Result<()> {
+    let path = resolve(path);
+
+    if path.parent().is_none() {
+        return Err(anyhow!("Refusing to scan filesystem root {}", path.display()));
+    }
+    check_protected(&path)
+}
+
+/// Refuses to delete a filesystem root, the home directory itself, or
+/// anything inside a protected path.
+pub fn check_delete(path: &Path) -> Result<()> {
+    let path = resolve(path);
+
+    if path.parent().is_none() {
+        return Err(anyhow!("Refusing to remove filesystem root {}", path.display()));
+    }
+    if dirs::home_dir().is_some_and(|home| same_path(&resolve(&home), &path)) {
+        return Err(anyhow!("Refusing to remove the home directory {}", path.display()));
+    }
+    check_protected(&path)
+}
+
+fn check_protected(path: &Path) -> Result<()> {
+    let extra = EXTRA_PROTECTED.get().map(Vec::as_slice).unwrap_or_default();
+    let cleanable = CLEANABLE_SYSTEM_DIRS
+        .iter()
+        .map(Path::new)
+        .any(|dir| starts_with(path, dir) && !same_path(path, dir));
+    let system = SYSTEM_PATHS.iter().filter(|_| !cleanable).map(PathBuf::from);
+    let protected = system.chain(extra.iter().map(|p| resolve(p)));
+
+    for protected_path in protected {
+        if starts_with(path, &protected_path) {
+            return Err(anyhow!(
+                "Refusing to operate on {}: it is inside the protected path {}",
+                path.display(),
+                protected_path.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Sets the staleness window used by [`check_in_use`]; zero turns the check
+/// off. Only the first call has an effect.
+pub fn set_in_use_window(window: Duration) {
+    let _ = IN_USE_WINDOW.set(window);
+}
+
+/// Refuses to delete `path` while it looks in use, i.e. when it or anything
+/// inside it was modified within the staleness window. A `cargo build` writing
+/// into `target/` is the typical case; deleting under it corrupts the build.
+pub fn check_in_use(path: &Path) -> Result<()> {
+    let window = IN_USE_WINDOW.get().copied().unwrap_or(DEFAULT_IN_USE_WINDOW);
+    match recently_modified(path, window) {
+        Some(modified) => Err(InUse { path: path.to_owned(), modified, window }.into()),
+        None => Ok(()),
+    }
+}
+
+/// Error from [`check_in_use`]. Removal reports it as a skip rather than a
+/// failure: the item is fine to delete later, just not right now.
+#[derive(Debug)]
+pub struct InUse {
+    path: PathBuf,
+    modified: PathBuf,
+    window: Duration,
+}
+
+impl InUse {
+    /// Why the item was left alone, for [`crate::cleanup::report::CleanupReport::skipped`].
+    pub fn reason(&self) -> String {
+        format!("it {}", self.detail())
+    }
+
+    fn detail(&self) -> String {
+        format!(
+            "appears to be in use ({} was modified in the last {}s; is a build running?)",
+            self.modified.display(),
+            self.window.as_secs()
+        )
+    }
+}
+
+impl std::fmt::Display for InUse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.path.display(), self.detail())
+    }
+}
+
+impl std::error::Error for InUse {}
+
+/// First entry under `path` (including itself) modified less than `window` ago.
+fn recently_modified(path: &Path, window: Duration) -> Option<PathBuf> {
+    if window.is_zero() {
+        return None;
+    }
+    let cutoff = SystemTime::now().checked_sub(window)?;
+
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .find(|entry| {
+            entry
+                .metadata()
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .is_some_and(|mtime| mtime > cutoff)
+        })
+        .map(|entry| entry.into_path())
+}
+
+/// Makes every entry under `path` look long untouched, so freshly created
+/// fixtures pass [`check_in_use`].
+#[cfg(test)]
+pub fn backdate(path: &Path) {
+    let old = filetime::FileTime::from_unix_time(1_000_000, 0);
+    for entry in WalkDir::new(path).contents_first(true).into_iter().filter_map(|entry| entry.ok()) {
+        filetime::set_file_mtime(entry.path(), old).unwrap();
+    }
+}
+
+/// Absolute, symlink-free form of `path` where possible, so `.` or `../..`
+/// can't sneak past the checks. Windows verbatim (`\\?\`) prefixes are
+/// stripped so the result compares against the plain system paths.
+fn resolve(path: &Path) -> PathBuf {
+    dunce::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_owned())
+}
+
+/// `Path::starts_with`, ignoring case on Windows where paths are case-insensitive.
+fn starts_with(path: &Path, prefix: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        fold_case(path).starts_with(fold_case(prefix))
+    }
+    #[cfg(not(windows))]
+    {
+        path.starts_with(prefix)
+    }
+}
+
+/// Path equality, ignoring case on Windows.
+fn same_path(a: &Path, b: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        fold_case(a) == fold_case(b)
+    }
+    #[cfg(not(windows))]
+    {
+        a == b
+    }
+}
+
+#[cfg(windows)]
+fn fold_case(path: &Path) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_paths_rejected() {
+        assert!(check_scan(Path::new("/")).is_err());
+        assert!(check_scan(Path::new("/etc")).is_err());
+        assert!(check_scan(Path::new("/usr/share")).is_err());
+        assert!(check_delete(Path::new("/")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_links_into_system_paths_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("etc");
+        std::os::unix::fs::symlink("/etc", &link).unwrap();
+        assert!(check_scan(&link).is_err());
+        assert!(check_delete(&link.join("hosts")).is_err());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_private_system_paths_rejected() {
+        assert!(check_scan(Path::new("/private/etc")).is_err());
+        assert!(check_scan(Path::new("/var/db")).is_err());
+        assert!(check_scan(&std::env::temp_dir()).is_ok());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_existing_windows_paths_rejected() {
+        let windows = PathBuf::from(std::env::var_os("SystemRoot").unwrap());
+        assert!(check_scan(&windows).is_err());
+        assert!(check_scan(&windows.join("System32")).is_err());
+        assert!(check_scan(Path::new(&windows.to_string_lossy().to_lowercase())).is_err());
+        assert!(check_scan(&std::env::temp_dir()).is_ok());
+    }
+
+    #[test]
+    fn test_normal_directory_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(check_scan(dir.path()).is_ok());
+        assert!(check_delete(&dir.path().join("node_modules")).is_ok());
+    }
+
+    #[test]
+    fn test_home_cannot_be_deleted() {
+        if let Some(home) = dirs::home_dir() {
+            assert!(check_delete(&home).is_err());
+        }
+    }
+
+    #[test]
+    fn test_recently_modified_dir_is_in_use() {
+        let root = tempfile::tempdir().unwrap();
+        let active = root.path().join("active");
+        let idle = root.path().join("idle");
+        for dir in [&active, &idle] {
+            std::fs::create_dir_all(dir.join("debug")).unwrap();
+            std::fs::write(dir.join("debug/out.o"), b"data").unwrap();
+        }
+        backdate(&idle);
+
+        let window = Duration::from_secs(60);
+        assert!(recently_modified(&active, window).is_some());
+        assert_eq!(recently_modified(&idle, window), None);
+        assert_eq!(recently_modified(&active, Duration::ZERO), None);
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Directory sizes remembered between runs for `list --cache`.
+///
+/// An entry is reused while the directory's own mtime is unchanged. Most
+/// filesystems only bump that mtime when an entry directly inside is added,
+/// removed or renamed, so files growing deeper down go unnoticed until then.
+/// That is why the cache is opt-in.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SizeCache {
+    entries: HashMap<PathBuf, CachedSize>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSize {
+    size: u64,
+    /// Caches from before this field counted every entry, not just files,
+    /// and fail to load, so they start over
+    file_count: usize,
+    mtime: SystemTime,
+    /// Measurement settings the size was taken with
+    allocated: bool,
+    count_hardlinks: bool,
+}
+
+/// Location of the cache, `~/.cache/safe-clean/sizes.json` on Linux.
+pub fn cache_path() -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir().ok_or_else(|| anyhow!("Could not determine the user cache directory"))?;
+    Ok(cache_dir.join("safe-clean").join("sizes.json"))
+}
+
+impl SizeCache {
+    /// Reads the cache at `path`, starting empty when there is none yet or
+    /// it can't be parsed.
+    pub fn load_from(path: &Path) -> SizeCache {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the cache back to `path` if anything changed.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Size and item count of `dir` if it was measured with the same settings
+    /// and has not been modified since.
+    pub fn get(&self, dir: &Path, mtime: SystemTime, allocated: bool, count_hardlinks: bool) -> Option<(u64, usize)> {
+        self.entries
+            .get(&key(dir))
+            .filter(|cached| cached.mtime == mtime && cached.allocated == allocated && cached.count_hardlinks == count_hardlinks)
+            .map(|cached| (cached.size, cached.file_count))
+    }
+
+    pub fn insert(&mut self, dir: &Path, mtime: SystemTime, allocated: bool, count_hardlinks: bool, size: u64, file_count: usize) {
+        let cached = CachedSize { size, file_count, mtime, allocated, count_hardlinks };
+        self.entries.insert(key(dir), cached);
+        self.dirty = true;
+    }
+}
+
+/// Entries are keyed by absolute path so runs from other directories share them.
+fn key(dir: &Path) -> PathBuf {
+    fs::canonicalize(dir).unwrap_or_else(|_| dir.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::DirAnalyzer;
+    use filetime::FileTime;
+    use std::sync::{Arc, Mutex};
+
+    async fn sizes(analyzer: &DirAnalyzer, root: &Path) -> u64 {
+        analyzer.analyze_directory(root, 1).await.unwrap()[0].size
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_dir_hits_cache_and_mutated_dir_recomputes() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("project");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("a.bin"), vec![0u8; 100]).unwrap();
+
+        let cache = Arc::new(Mutex::new(SizeCache::default()));
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.allocated = false;
+        analyzer.cache = Some(cache.clone());
+        assert_eq!(sizes(&analyzer, root.path()).await, 100);
+
+        // Round-trip through disk, then tamper with the entry so a hit is visible
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cache_file = cache_dir.path().join("safe-clean/sizes.json");
+        cache.lock().unwrap().save_to(&cache_file).unwrap();
+        let mut reloaded = SizeCache::load_from(&cache_file);
+        let mtime = fs::metadata(&dir).unwrap().modified().unwrap();
+        assert_eq!(reloaded.get(&dir, mtime, false, false), Some((100, 1)));
+        reloaded.insert(&dir, mtime, false, false, 12345, 1);
+        *cache.lock().unwrap() = reloaded;
+        assert_eq!(sizes(&analyzer, root.path()).await, 12345);
+
+        // Adding a file changes the directory's mtime
+        fs::write(dir.join("b.bin"), vec![0u8; 50]).unwrap();
+        filetime::set_file_mtime(&dir, FileTime::from_unix_time(1_000_000, 0)).unwrap();
+        assert_eq!(sizes(&analyzer, root.path()).await, 150);
+
+        // Measuring with other settings doesn't reuse the entry either
+        analyzer.allocated = true;
+        assert_ne!(sizes(&analyzer, root.path()).await, 150);
+    }
+}
@@ -1,19 +1,61 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    cursor::Show,
+    terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Wrap,
+    },
     Frame, Terminal,
 };
-use std::{io, path::Path};
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+    sync::{atomic::{AtomicBool, Ordering}, mpsc, Arc},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use crate::cleanup::dev::remove_dir_all_safe;
+use crate::cleanup::report::CleanupReport;
+use crate::cli::output::{sort_items, SortKey};
 use crate::discovery::{DirAnalyzer, LargeFileFinder, DevArtifactFinder, FileItem};
-use crate::utils::format_size;
+use crate::color::{no_color, styled};
+use crate::utils::{format_size, interrupt_flag, open_in_file_manager, parent_dir, truncate_path};
+
+/// Styles used across the TUI, so they can be swapped as a set.
+#[derive(Debug, Clone, Copy)]
+struct Theme {
+    header: Style,
+    footer: Style,
+    highlight: Style,
+}
+
+impl Theme {
+    fn colored() -> Theme {
+        Theme {
+            header: Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            footer: Style::default().fg(Color::Yellow),
+            highlight: Style::default().bg(Color::Blue).add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// Relies on text attributes only, for `--no-color`.
+    fn monochrome() -> Theme {
+        Theme {
+            header: Style::default().add_modifier(Modifier::BOLD),
+            footer: Style::default(),
+            highlight: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 enum MenuOption {
@@ -46,6 +88,35 @@ struct App {
     items_state: ListState,
     message: Option<String>,
     show_help: bool,
+    marked: HashSet<usize>,
+    pending_delete: Option<Vec<usize>>,
+    scan: Option<LargeFileScan>,
+    tick: usize,
+    use_trash: bool,
+    /// Bytes removed by deletions so far this session, across all views
+    freed_bytes: u64,
+    /// Directory shown in the directory view; parents we drilled down from
+    /// are kept in `breadcrumb`, most recent last.
+    current_path: PathBuf,
+    breadcrumb: Vec<PathBuf>,
+    /// Case-insensitive path filter for item lists; `filter_input` is set
+    /// while the user is typing it. `items_state` indexes the filtered list.
+    filter: String,
+    filter_input: bool,
+    /// Rows visible in the item list at the last draw; PageUp/PageDown move
+    /// by this much.
+    page_size: usize,
+    theme: Theme,
+}
+
+/// A large file scan running in the background while the TUI shows progress.
+struct LargeFileScan {
+    handle: JoinHandle<Result<Vec<FileItem>>>,
+    progress: mpsc::Receiver<usize>,
+    cancel: Arc<AtomicBool>,
+    scanned: usize,
+    /// Row to select once the results are in, when refreshing
+    selected: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -57,8 +128,28 @@ enum AppView {
     Loading,
 }
 
+/// What re-fills an item view when it is refreshed with 'r'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Loader {
+    Directories,
+    LargeFiles,
+    DevArtifacts,
+}
+
+impl AppView {
+    /// The loader behind this view; `None` for views without items.
+    fn loader(&self) -> Option<Loader> {
+        match self {
+            AppView::DirectoryList => Some(Loader::Directories),
+            AppView::LargeFiles => Some(Loader::LargeFiles),
+            AppView::DevArtifacts => Some(Loader::DevArtifacts),
+            AppView::Menu | AppView::Loading => None,
+        }
+    }
+}
+
 impl App {
-    fn new() -> App {
+    fn new(use_trash: bool) -> App {
         let mut app = App {
             menu_state: ListState::default(),
             menu_options: vec![
@@ -74,6 +165,18 @@ impl App {
             items_state: ListState::default(),
             message: None,
             show_help: false,
+            marked: HashSet::new(),
+            pending_delete: None,
+            scan: None,
+            tick: 0,
+            use_trash,
+            freed_bytes: 0,
+            current_path: PathBuf::from("."),
+            breadcrumb: Vec::new(),
+            filter: String::new(),
+            filter_input: false,
+            page_size: 10,
+            theme: if no_color() { Theme::monochrome() } else { Theme::colored() },
         };
         app.menu_state.select(Some(0));
         app
@@ -108,12 +211,13 @@ impl App {
     }
 
     fn next_item(&mut self) {
-        if self.items.is_empty() {
+        let len = self.visible_indices().len();
+        if len == 0 {
             return;
         }
         let selected = match self.items_state.selected() {
             Some(i) => {
-                if i >= self.items.len() - 1 {
+                if i >= len - 1 {
                     0
                 } else {
                     i + 1
@@ -125,13 +229,14 @@ impl App {
     }
 
     fn previous_item(&mut self) {
-        if self.items.is_empty() {
+        let len = self.visible_indices().len();
+        if len == 0 {
             return;
         }
         let selected = match self.items_state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.items.len() - 1
+                    len - 1
                 } else {
                     i - 1
                 }
@@ -141,6 +246,36 @@ impl App {
         self.items_state.select(Some(selected));
     }
 
+    fn page_down(&mut self) {
+        let len = self.visible_indices().len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.items_state.selected().unwrap_or(0);
+        self.items_state.select(Some((selected + self.page_size).min(len - 1)));
+    }
+
+    fn page_up(&mut self) {
+        if self.visible_indices().is_empty() {
+            return;
+        }
+        let selected = self.items_state.selected().unwrap_or(0);
+        self.items_state.select(Some(selected.saturating_sub(self.page_size)));
+    }
+
+    fn first_item(&mut self) {
+        if !self.visible_indices().is_empty() {
+            self.items_state.select(Some(0));
+        }
+    }
+
+    fn last_item(&mut self) {
+        let len = self.visible_indices().len();
+        if len > 0 {
+            self.items_state.select(Some(len - 1));
+        }
+    }
+
     async fn execute_menu_action(&mut self) -> Result<bool> {
         if let Some(selected) = self.menu_state.selected() {
             match &self.menu_options[selected] {
@@ -152,9 +287,7 @@ impl App {
                 }
                 MenuOption::FindLargeFiles => {
                     self.current_view = AppView::Loading;
-                    self.load_large_files().await?;
-                    self.current_view = AppView::LargeFiles;
-                    self.items_state.select(Some(0));
+                    self.start_large_file_scan();
                 }
                 MenuOption::FindDevArtifacts => {
                     self.current_view = AppView::Loading;
@@ -177,19 +310,166 @@ impl App {
     }
 
     async fn load_directories(&mut self) -> Result<()> {
-        let analyzer = DirAnalyzer::new();
-        self.items = analyzer.analyze_directory(Path::new("."), true).await?;
+        let mut analyzer = DirAnalyzer::new();
+        analyzer.cancel = interrupt_flag();
+        self.items = analyzer.analyze_directory(&self.current_path, 1).await?;
+        sort_items(&mut self.items, SortKey::Size, false);
         Ok(())
     }
 
-    async fn load_large_files(&mut self) -> Result<()> {
-        let finder = LargeFileFinder::new();
-        self.items = finder.find_large_files(Path::new("."), 100 * 1024 * 1024).await?; // 100MB threshold
+    /// Makes `path` the current directory, remembering where we came from.
+    fn enter_dir(&mut self, path: PathBuf) {
+        let parent = std::mem::replace(&mut self.current_path, path);
+        self.breadcrumb.push(parent);
+    }
+
+    /// Goes back to the directory we drilled down from. Returns false when
+    /// already at the top.
+    fn leave_dir(&mut self) -> bool {
+        match self.breadcrumb.pop() {
+            Some(parent) => {
+                self.current_path = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-analyzes the selected directory and shows its contents. When it
+    /// can't be scanned the listing stays where it was and says why.
+    async fn drill_down(&mut self) {
+        let path = match self.selected_index().and_then(|i| self.items.get(i)) {
+            Some(item) if item.is_dir => item.path.clone(),
+            _ => return,
+        };
+        self.enter_dir(path);
+        if let Err(e) = self.reload_directories().await {
+            self.message = Some(format!("Could not open {}: {:#}", self.current_path.display(), e));
+            self.leave_dir();
+        }
+    }
+
+    /// Returns to the parent directory, or to the menu when at the top. When
+    /// the parent can't be scanned the listing stays where it was and says why.
+    async fn drill_up(&mut self) {
+        let child = self.current_path.clone();
+        if !self.leave_dir() {
+            self.back_to_menu();
+            return;
+        }
+        if let Err(e) = self.reload_directories().await {
+            self.message = Some(format!("Could not open {}: {:#}", self.current_path.display(), e));
+            self.enter_dir(child);
+        }
+    }
+
+    async fn reload_directories(&mut self) -> Result<()> {
+        self.load_directories().await?;
+        self.marked.clear();
+        self.filter.clear();
+        self.items_state.select(if self.items.is_empty() { None } else { Some(0) });
         Ok(())
     }
 
+    /// Starts the large file scan on a background task so the event loop keeps
+    /// drawing progress and can cancel it. `poll_scan` picks up the result.
+    fn start_large_file_scan(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        let mut finder = LargeFileFinder::new();
+        finder.progress = Some(tx);
+        let cancel = finder.cancel.clone();
+
+        let handle = tokio::spawn(async move {
+            finder.find_large_files(Path::new("."), 100 * 1024 * 1024, None, Vec::new()).await // 100MB threshold
+        });
+
+        self.scan = Some(LargeFileScan {
+            handle,
+            progress: rx,
+            cancel,
+            scanned: 0,
+            selected: None,
+        });
+    }
+
+    async fn poll_scan(&mut self) -> Result<()> {
+        let finished = match &mut self.scan {
+            Some(scan) => {
+                if let Some(count) = scan.progress.try_iter().last() {
+                    scan.scanned = count;
+                }
+                scan.handle.is_finished()
+            }
+            None => return Ok(()),
+        };
+
+        if finished {
+            if let Some(scan) = self.scan.take() {
+                let cancelled = scan.cancel.load(Ordering::Relaxed);
+                self.items = scan.handle.await??;
+                self.current_view = AppView::LargeFiles;
+                self.restore_selection(scan.selected);
+                if cancelled {
+                    self.message = Some(format!(
+                        "Scan cancelled after {} entries; showing partial results.",
+                        scan.scanned
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn cancel_scan(&mut self) {
+        if let Some(scan) = &self.scan {
+            scan.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Re-runs `loader` for the current path, keeping the selected row where
+    /// the list is still long enough. Large files are rescanned in the
+    /// background, like the first time.
+    /// Reloads the current view with `loader`. When that fails the previous
+    /// items stay on screen and the error is shown instead.
+    async fn refresh(&mut self, loader: Loader) {
+        let selected = self.items_state.selected();
+        self.marked.clear();
+        self.pending_delete = None;
+        let result = match loader {
+            Loader::Directories => {
+                let result = self.load_directories().await;
+                self.current_view = AppView::DirectoryList;
+                result
+            }
+            Loader::DevArtifacts => {
+                let result = self.load_dev_artifacts().await;
+                self.current_view = AppView::DevArtifacts;
+                result
+            }
+            Loader::LargeFiles => {
+                self.current_view = AppView::Loading;
+                self.start_large_file_scan();
+                if let Some(scan) = &mut self.scan {
+                    scan.selected = selected;
+                }
+                return;
+            }
+        };
+        if let Err(e) = result {
+            self.message = Some(format!("Refresh failed: {:#}", e));
+        }
+        self.restore_selection(selected);
+    }
+
+    /// Selects row `selected`, or the last row when the list got shorter.
+    fn restore_selection(&mut self, selected: Option<usize>) {
+        let len = self.visible_indices().len();
+        self.items_state.select(if len == 0 { None } else { Some(selected.unwrap_or(0).min(len - 1)) });
+    }
+
     async fn load_dev_artifacts(&mut self) -> Result<()> {
-        let finder = DevArtifactFinder::new();
+        let mut finder = DevArtifactFinder::new();
+        finder.cancel = interrupt_flag();
         self.items = finder.find_artifacts(Path::new(".")).await?;
         Ok(())
     }
@@ -199,6 +479,177 @@ impl App {
         self.items.clear();
         self.items_state = ListState::default();
         self.message = None;
+        self.marked.clear();
+        self.pending_delete = None;
+        self.current_path = PathBuf::from(".");
+        self.breadcrumb.clear();
+        self.filter.clear();
+        self.filter_input = false;
+    }
+
+    fn request_delete(&mut self) {
+        if !matches!(self.current_view, AppView::DevArtifacts) {
+            return;
+        }
+        if let Some(selected) = self.selected_index() {
+            self.pending_delete = Some(vec![selected]);
+        }
+    }
+
+    fn request_delete_marked(&mut self) {
+        if self.marked.is_empty() {
+            return;
+        }
+        if !matches!(self.current_view, AppView::DevArtifacts) {
+            self.message = Some("Batch delete is only available for development artifacts.".to_string());
+            return;
+        }
+        let mut indices: Vec<usize> = self.marked.iter().copied().collect();
+        indices.sort_unstable();
+        self.pending_delete = Some(indices);
+    }
+
+    /// Shows the folder containing the selected item in the file manager.
+    fn open_selected_parent(&mut self) {
+        let Some(item) = self.selected_index().and_then(|i| self.items.get(i)) else {
+            return;
+        };
+        let dir = parent_dir(&item.path);
+        if let Err(e) = open_in_file_manager(&dir) {
+            self.message = Some(format!("Could not open {}: {:#}", dir.display(), e));
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(selected) = self.selected_index() {
+            if !self.marked.remove(&selected) {
+                self.marked.insert(selected);
+            }
+        }
+    }
+
+    fn marked_size(&self) -> u64 {
+        self.marked
+            .iter()
+            .filter_map(|&index| self.items.get(index))
+            .map(|item| item.size)
+            .sum()
+    }
+
+    /// Combined size of the items the (filtered) list shows.
+    fn listed_size(&self) -> u64 {
+        self.visible_indices().iter().map(|&index| self.items[index].size).sum()
+    }
+
+    async fn confirm_delete(&mut self) -> Result<()> {
+        let indices: Vec<usize> = match self.pending_delete.take() {
+            Some(indices) => indices.into_iter().filter(|&i| i < self.items.len()).collect(),
+            None => return Ok(()),
+        };
+        if indices.is_empty() {
+            return Ok(());
+        }
+
+        let paths: Vec<_> = indices.iter().map(|&i| self.items[i].path.clone()).collect();
+        let use_trash = self.use_trash;
+        let results = tokio::task::spawn_blocking(move || {
+            paths.iter().map(|path| remove_dir_all_safe(path, use_trash)).collect::<Vec<_>>()
+        }).await?;
+
+        let mut removed = Vec::new();
+        let mut report = CleanupReport::default();
+        for (&index, result) in indices.iter().zip(results) {
+            if result.is_ok() {
+                removed.push(index);
+            }
+            report.record(&self.items[index], result);
+        }
+        self.freed_bytes += report.bytes_freed;
+
+        let freed = format_size(report.bytes_freed);
+        self.message = Some(if report.removed.len() == 1 && report.failed.is_empty() {
+            format!("✅ Removed {} ({})", report.removed[0].0.display(), freed)
+        } else if report.failed.is_empty() {
+            format!("✅ Removed {} items ({})", report.removed.len(), freed)
+        } else {
+            let failures: Vec<String> = report.failed.iter()
+                .map(|(path, reason)| format!("{}: {}", path.display(), reason))
+                .collect();
+            format!(
+                "Removed {} items ({}). ❌ Failed to remove {}",
+                report.removed.len(),
+                freed,
+                failures.join("; ")
+            )
+        });
+
+        self.remove_items(&removed);
+        Ok(())
+    }
+
+    /// Drops the items at `indices` and shifts the remaining marks so they keep
+    /// pointing at the same entries.
+    fn remove_items(&mut self, indices: &[usize]) {
+        let mut sorted = indices.to_vec();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        for &index in &sorted {
+            self.items.remove(index);
+        }
+
+        self.marked = self
+            .marked
+            .iter()
+            .filter(|index| !indices.contains(index))
+            .map(|&index| index - indices.iter().filter(|&&removed| removed < index).count())
+            .collect();
+        self.clamp_selection();
+    }
+
+    fn clamp_selection(&mut self) {
+        let len = self.visible_indices().len();
+        if len == 0 {
+            self.items_state.select(None);
+        } else {
+            let selected = self.items_state.selected().unwrap_or(0);
+            self.items_state.select(Some(selected.min(len - 1)));
+        }
+    }
+
+    /// Indices into `items` of the entries that pass the current filter.
+    fn visible_indices(&self) -> Vec<usize> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| matches_filter(item, &self.filter))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Index into `items` of the selected entry of the filtered list.
+    fn selected_index(&self) -> Option<usize> {
+        self.items_state
+            .selected()
+            .and_then(|selected| self.visible_indices().get(selected).copied())
+    }
+
+    fn start_filter(&mut self) {
+        self.filter_input = true;
+    }
+
+    fn push_filter_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.clamp_selection();
+    }
+
+    fn pop_filter_char(&mut self) {
+        self.filter.pop();
+        self.clamp_selection();
+    }
+
+    fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.filter_input = false;
+        self.clamp_selection();
     }
 
     fn toggle_help(&mut self) {
@@ -206,7 +657,14 @@ impl App {
     }
 }
 
-pub async fn run() -> Result<()> {
+/// True when `item`'s path contains `filter`, ignoring case. An empty filter
+/// matches everything.
+fn matches_filter(item: &FileItem, filter: &str) -> bool {
+    filter.is_empty()
+        || item.path.to_string_lossy().to_lowercase().contains(&filter.to_lowercase())
+}
+
+pub async fn run(use_trash: bool) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -214,49 +672,122 @@ pub async fn run() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Run app
-    let result = run_app(&mut terminal).await;
-
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    // Run app; Ctrl-C arrives as a key event in raw mode, but an external
+    // SIGINT still has to leave the terminal usable.
+    let result = tokio::select! {
+        result = run_app(&mut terminal, use_trash) => result,
+        _ = tokio::signal::ctrl_c() => {
+            interrupt_flag().store(true, Ordering::Relaxed);
+            Err(anyhow::anyhow!("Interrupted"))
+        }
+    };
 
+    restore_terminal()?;
     result
 }
 
-async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
-    let mut app = App::new();
+/// Leaves raw mode and the alternate screen and shows the cursor again.
+/// Does nothing when the terminal is not in raw mode, so it is safe to call
+/// from the interrupt handler whether or not the TUI is running.
+pub fn restore_terminal() -> Result<()> {
+    if !is_raw_mode_enabled()? {
+        return Ok(());
+    }
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show)?;
+    Ok(())
+}
+
+async fn run_app<B: Backend>(terminal: &mut Terminal<B>, use_trash: bool) -> Result<()> {
+    let mut app = App::new(use_trash);
 
     loop {
+        app.poll_scan().await?;
+        app.tick = app.tick.wrapping_add(1);
         terminal.draw(|f| ui(f, &mut app))?;
 
+        // Poll with a timeout so a running scan keeps the screen updating
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+
         if let Event::Key(key) = event::read()? {
             if key.kind == KeyEventKind::Press {
+                if app.pending_delete.is_some() {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_delete().await?,
+                        _ => app.pending_delete = None,
+                    }
+                    continue;
+                }
+
+                // Any key dismisses an open message popup
+                if app.message.is_some() {
+                    app.message = None;
+                    continue;
+                }
+
+                if app.filter_input {
+                    match key.code {
+                        KeyCode::Esc => app.clear_filter(),
+                        KeyCode::Enter => app.filter_input = false,
+                        KeyCode::Backspace => app.pop_filter_char(),
+                        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => app.push_filter_char(c),
+                        _ => {}
+                    }
+                    continue;
+                }
+
                 match key.code {
-                    KeyCode::Char('q') => break,
+                    KeyCode::Char('q') => {
+                        app.cancel_scan();
+                        break;
+                    }
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.cancel_scan();
+                        break;
+                    }
                     KeyCode::Char('h') => app.toggle_help(),
                     KeyCode::Esc => {
-                        if matches!(app.current_view, AppView::Menu) {
+                        if app.scan.is_some() {
+                            // The scan stops at its next entry and poll_scan shows the partial results
+                            app.cancel_scan();
+                        } else if matches!(app.current_view, AppView::Menu) {
                             break;
+                        } else if !app.filter.is_empty() {
+                            app.clear_filter();
+                        } else if matches!(app.current_view, AppView::DirectoryList) {
+                            app.drill_up().await;
                         } else {
                             app.back_to_menu();
                         }
                     }
+                    KeyCode::Backspace if matches!(app.current_view, AppView::DirectoryList) => {
+                        app.drill_up().await;
+                    }
                     KeyCode::Enter => {
-                        match app.current_view {
-                            AppView::Menu => {
-                                if app.execute_menu_action().await? {
-                                    break;
-                                }
-                            }
-                            _ => {}
+                        if matches!(app.current_view, AppView::DevArtifacts) {
+                            app.request_delete();
+                        } else if matches!(app.current_view, AppView::DirectoryList) {
+                            app.drill_down().await;
+                        } else if matches!(app.current_view, AppView::Menu)
+                            && app.execute_menu_action().await?
+                        {
+                            break;
                         }
                     }
+                    KeyCode::Char('d') => app.request_delete(),
+                    KeyCode::Char('D') => app.request_delete_marked(),
+                    KeyCode::Char(' ') if !matches!(app.current_view, AppView::Menu) => app.toggle_mark(),
+                    KeyCode::Char('o') if !matches!(app.current_view, AppView::Menu | AppView::Loading) => app.open_selected_parent(),
+                    KeyCode::Char('r') if app.scan.is_none() => {
+                        if let Some(loader) = app.current_view.loader() {
+                            app.current_view = AppView::Loading;
+                            terminal.draw(|f| ui(f, &mut app))?;
+                            app.refresh(loader).await;
+                        }
+                    }
+                    KeyCode::Char('/') if !matches!(app.current_view, AppView::Menu | AppView::Loading) => app.start_filter(),
                     KeyCode::Up => {
                         match app.current_view {
                             AppView::Menu => app.previous_menu_item(),
@@ -269,6 +800,10 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
                             _ => app.next_item(),
                         }
                     }
+                    KeyCode::PageUp if !matches!(app.current_view, AppView::Menu) => app.page_up(),
+                    KeyCode::PageDown if !matches!(app.current_view, AppView::Menu) => app.page_down(),
+                    KeyCode::Home if !matches!(app.current_view, AppView::Menu) => app.first_item(),
+                    KeyCode::End if !matches!(app.current_view, AppView::Menu) => app.last_item(),
                     _ => {}
                 }
             }
@@ -289,8 +824,29 @@ fn ui(f: &mut Frame, app: &mut App) {
         .split(f.size());
 
     // Header
-    let header = Paragraph::new("Safe Clean - Disk Cleanup Tool")
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    let mut header_text = "Safe Clean - Disk Cleanup Tool".to_string();
+    if matches!(app.current_view, AppView::DirectoryList) {
+        header_text.push_str(&format!(" | {}", app.current_path.display()));
+    }
+    if !matches!(app.current_view, AppView::Menu | AppView::Loading) {
+        header_text.push_str(&format!(
+            " | Listed: {} items ({})",
+            app.visible_indices().len(),
+            format_size(app.listed_size())
+        ));
+    }
+    if !app.marked.is_empty() {
+        header_text.push_str(&format!(
+            " | Marked: {} items ({})",
+            app.marked.len(),
+            format_size(app.marked_size())
+        ));
+    }
+    if app.freed_bytes > 0 {
+        header_text.push_str(&format!(" | Freed: {}", format_size(app.freed_bytes)));
+    }
+    let header = Paragraph::new(header_text)
+        .style(app.theme.header)
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(header, chunks[0]);
@@ -301,24 +857,37 @@ fn ui(f: &mut Frame, app: &mut App) {
         AppView::DirectoryList => render_items_list(f, app, chunks[1], "Directories by Size"),
         AppView::LargeFiles => render_items_list(f, app, chunks[1], "Large Files"),
         AppView::DevArtifacts => render_items_list(f, app, chunks[1], "Development Artifacts"),
-        AppView::Loading => render_loading(f, chunks[1]),
+        AppView::Loading => render_loading(f, app, chunks[1]),
     }
 
     // Footer
     let footer_text = if app.show_help {
-        "ESC: Back/Exit | ↑↓: Navigate | Enter: Select | h: Toggle Help | q: Quit"
+        "ESC/Backspace: Back/Exit | ↑↓/PgUp/PgDn/Home/End: Navigate | Enter: Select/Open dir | Space: Mark | /: Filter | o: Open containing folder | r: Refresh | d: Delete artifact | D: Delete marked | h: Toggle Help | q: Quit"
     } else {
         "h: Help | q: Quit"
     };
     
     let footer = Paragraph::new(footer_text)
-        .style(Style::default().fg(Color::Yellow))
+        .style(app.theme.footer)
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(footer, chunks[2]);
 
-    // Show message popup if any
-    if let Some(message) = &app.message {
+    // Show delete confirmation or message popup if any
+    if let Some(indices) = &app.pending_delete {
+        let pending: Vec<&FileItem> = indices.iter().filter_map(|&i| app.items.get(i)).collect();
+        let size: u64 = pending.iter().map(|item| item.size).sum();
+        let target = match pending.as_slice() {
+            [item] => item.path.display().to_string(),
+            _ => format!("{} marked items", pending.len()),
+        };
+        let prompt = format!(
+            "Delete {} ({})?\n\ny: Yes | any other key: Cancel",
+            target,
+            format_size(size)
+        );
+        render_message_popup(f, &prompt);
+    } else if let Some(message) = &app.message {
         render_message_popup(f, message);
     }
 }
@@ -327,19 +896,26 @@ fn render_menu(f: &mut Frame, app: &mut App, area: Rect) {
     let items: Vec<ListItem> = app
         .menu_options
         .iter()
-        .map(|option| ListItem::new(option.as_str()))
+        .map(|option| ListItem::new(styled(option.as_str())))
         .collect();
 
     let list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title("Main Menu"))
-        .highlight_style(Style::default().bg(Color::Blue).add_modifier(Modifier::BOLD))
+        .highlight_style(app.theme.highlight)
         .highlight_symbol("► ");
 
     f.render_stateful_widget(list, area, &mut app.menu_state);
 }
 
 fn render_items_list(f: &mut Frame, app: &mut App, area: Rect, title: &str) {
-    if app.items.is_empty() {
+    let title = if app.filter_input || !app.filter.is_empty() {
+        format!("{} [/{}]", title, app.filter)
+    } else {
+        title.to_string()
+    };
+
+    let visible = app.visible_indices();
+    if visible.is_empty() {
         let paragraph = Paragraph::new("No items found.")
             .block(Block::default().borders(Borders::ALL).title(title))
             .alignment(Alignment::Center);
@@ -347,22 +923,26 @@ fn render_items_list(f: &mut Frame, app: &mut App, area: Rect, title: &str) {
         return;
     }
 
-    let items: Vec<ListItem> = app
-        .items
-        .iter()
-        .map(|item| {
-            let path_str = item.path.to_string_lossy();
-            let display_path = if path_str.len() > 60 {
-                format!("...{}", &path_str[path_str.len()-57..])
-            } else {
-                path_str.to_string()
-            };
-            
+    let visible_len = visible.len();
+    // The block's borders take the first and last row
+    app.page_size = (area.height.saturating_sub(2) as usize).max(1);
+
+    let items: Vec<ListItem> = visible
+        .into_iter()
+        .map(|index| {
+            let item = &app.items[index];
+            let marker = if app.marked.contains(&index) { "[x]" } else { "[ ]" };
+            let display_path = truncate_path(&item.path, 60);
+
             let size_str = format_size(item.size);
             let line = if let Some(count) = item.item_count {
-                format!("{:<60} {:>10} {:>8} items", display_path, size_str, count)
+                format!("{} {:<60} {:>10} {:>8} files", marker, display_path, size_str, count)
             } else {
-                format!("{:<60} {:>10}", display_path, size_str)
+                format!("{} {:<60} {:>10}", marker, display_path, size_str)
+            };
+            let line = match item.project_kind {
+                Some(kind) => format!("{}  [{}]", line, kind),
+                None => line,
             };
             
             ListItem::new(line)
@@ -371,14 +951,32 @@ fn render_items_list(f: &mut Frame, app: &mut App, area: Rect, title: &str) {
 
     let list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title(title))
-        .highlight_style(Style::default().bg(Color::Blue).add_modifier(Modifier::BOLD))
+        .highlight_style(app.theme.highlight)
         .highlight_symbol("► ");
 
     f.render_stateful_widget(list, area, &mut app.items_state);
+
+    let mut scrollbar_state = ScrollbarState::new(visible_len)
+        .position(app.items_state.selected().unwrap_or(0));
+    f.render_stateful_widget(
+        Scrollbar::new(ScrollbarOrientation::VerticalRight),
+        area.inner(&Margin { vertical: 1, horizontal: 0 }),
+        &mut scrollbar_state,
+    );
 }
 
-fn render_loading(f: &mut Frame, area: Rect) {
-    let paragraph = Paragraph::new("Loading... Please wait.")
+fn render_loading(f: &mut Frame, app: &App, area: Rect) {
+    const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+    let text = match &app.scan {
+        Some(scan) => format!(
+            "{} Scanning... {} entries scanned\n\nPress Esc to cancel",
+            SPINNER[app.tick % SPINNER.len()],
+            scan.scanned
+        ),
+        None => "Loading... Please wait.".to_string(),
+    };
+    let paragraph = Paragraph::new(text)
         .block(Block::default().borders(Borders::ALL).title("Processing"))
         .alignment(Alignment::Center);
     f.render_widget(paragraph, area);
@@ -388,7 +986,7 @@ fn render_message_popup(f: &mut Frame, message: &str) {
     let area = centered_rect(60, 20, f.size());
     f.render_widget(Clear, area);
     
-    let paragraph = Paragraph::new(message)
+    let paragraph = Paragraph::new(styled(message))
         .wrap(Wrap { trim: true })
         .block(Block::default().borders(Borders::ALL).title("Information"))
         .alignment(Alignment::Center);
@@ -413,4 +1011,147 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
             Constraint::Percentage((100 - percent_x) / 2),
         ])
         .split(popup_layout[1])[1]
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigation_stack_push_pop() {
+        let mut app = App::new(false);
+        assert_eq!(app.current_path, PathBuf::from("."));
+        assert!(!app.leave_dir());
+
+        app.enter_dir(PathBuf::from("./a"));
+        app.enter_dir(PathBuf::from("./a/b"));
+        assert_eq!(app.current_path, PathBuf::from("./a/b"));
+        assert_eq!(app.breadcrumb, vec![PathBuf::from("."), PathBuf::from("./a")]);
+
+        assert!(app.leave_dir());
+        assert_eq!(app.current_path, PathBuf::from("./a"));
+        assert!(app.leave_dir());
+        assert_eq!(app.current_path, PathBuf::from("."));
+        assert!(!app.leave_dir());
+        assert_eq!(app.current_path, PathBuf::from("."));
+    }
+
+    fn item(path: &str) -> FileItem {
+        FileItem::test(path, 0, None)
+    }
+
+    #[test]
+    fn test_filter_matches_path_case_insensitively() {
+        let items = [item("./Videos/holiday.MP4"), item("./src/main.rs"), item("./videos.txt")];
+        let matching: Vec<_> = items.iter().filter(|i| matches_filter(i, "video")).map(|i| &i.path).collect();
+        assert_eq!(matching, vec![&items[0].path, &items[2].path]);
+        assert!(items.iter().all(|i| matches_filter(i, "")));
+        assert!(!items.iter().any(|i| matches_filter(i, "nope")));
+
+        let mut app = App::new(false);
+        app.items = items.to_vec();
+        app.items_state.select(Some(2));
+        app.push_filter_char('M');
+        app.push_filter_char('p');
+        assert_eq!(app.items_state.selected(), Some(0));
+        assert_eq!(app.selected_index(), Some(0));
+    }
+
+    #[test]
+    fn test_page_jumps_clamp_to_list() {
+        let mut app = App::new(false);
+        app.items = (0..25).map(|i| item(&format!("./{}", i))).collect();
+        app.page_size = 10;
+        app.items_state.select(Some(0));
+
+        app.page_down();
+        assert_eq!(app.items_state.selected(), Some(10));
+        app.page_down();
+        app.page_down();
+        assert_eq!(app.items_state.selected(), Some(24));
+        app.page_up();
+        assert_eq!(app.items_state.selected(), Some(14));
+        app.page_up();
+        app.page_up();
+        assert_eq!(app.items_state.selected(), Some(0));
+
+        app.last_item();
+        assert_eq!(app.items_state.selected(), Some(24));
+        app.first_item();
+        assert_eq!(app.items_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_parent_dir_of_item() {
+        assert_eq!(parent_dir(Path::new("./Videos/holiday.mp4")), PathBuf::from("./Videos"));
+        assert_eq!(parent_dir(Path::new("/data/big.iso")), PathBuf::from("/data"));
+        assert_eq!(parent_dir(Path::new("big.iso")), PathBuf::from("."));
+    }
+
+    #[tokio::test]
+    async fn test_freed_bytes_tally_across_deletions() {
+        let root = tempfile::tempdir().unwrap();
+        let mut app = App::new(false);
+        for name in ["a", "b"] {
+            let artifact = root.path().join(name).join("node_modules");
+            std::fs::create_dir_all(&artifact).unwrap();
+            std::fs::write(artifact.join("index.js"), "x").unwrap();
+            crate::safety::backdate(&artifact);
+            app.items.push(FileItem::test(&artifact, 1000, Some(1)));
+        }
+        // Not an artifact directory, so its removal is refused
+        let src = root.path().join("a/src");
+        std::fs::create_dir_all(&src).unwrap();
+        app.items.push(FileItem::test(&src, 500, Some(0)));
+        assert_eq!(app.listed_size(), 2500);
+
+        app.pending_delete = Some(vec![0]);
+        app.confirm_delete().await.unwrap();
+        assert_eq!(app.freed_bytes, 1000);
+
+        app.pending_delete = Some(vec![0, 1]);
+        app.confirm_delete().await.unwrap();
+        assert_eq!(app.freed_bytes, 2000);
+        assert!(src.exists());
+        assert_eq!(app.listed_size(), 500);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_uses_the_views_loader() {
+        assert_eq!(AppView::DirectoryList.loader(), Some(Loader::Directories));
+        assert_eq!(AppView::LargeFiles.loader(), Some(Loader::LargeFiles));
+        assert_eq!(AppView::DevArtifacts.loader(), Some(Loader::DevArtifacts));
+        assert_eq!(AppView::Menu.loader(), None);
+        assert_eq!(AppView::Loading.loader(), None);
+
+        let root = tempfile::tempdir().unwrap();
+        for name in ["a", "b", "c"] {
+            std::fs::write(root.path().join(name), name).unwrap();
+        }
+        let mut app = App::new(false);
+        app.current_path = root.path().to_owned();
+        app.refresh(Loader::Directories).await;
+        app.items_state.select(Some(2));
+
+        std::fs::remove_file(root.path().join("c")).unwrap();
+        app.refresh(Loader::Directories).await;
+        assert!(matches!(app.current_view, AppView::DirectoryList));
+        assert_eq!(app.items.len(), 2);
+        assert_eq!(app.items_state.selected(), Some(1));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_drill_down_into_protected_dir_stays_put() {
+        let mut app = App::new(false);
+        app.current_view = AppView::DirectoryList;
+        app.items = vec![FileItem::test("/etc", 0, Some(0))];
+        app.items_state.select(Some(0));
+
+        app.drill_down().await;
+        assert_eq!(app.current_path, PathBuf::from("."));
+        assert!(app.breadcrumb.is_empty());
+        assert!(app.message.as_deref().is_some_and(|message| message.contains("/etc")));
+        assert_eq!(app.items.len(), 1);
+    }
+}
@@ -1,20 +1,115 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use dialoguer::{Confirm, Input};
+use std::io::{self, IsTerminal};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, warn};
+use crate::discovery::{FileItem, Skipped};
 
+/// Which unit multiple `format_size_opts` steps by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeBase {
+    /// Powers of 1024, labelled KiB, MiB, ... as [`parse_size`] reads them
+    #[default]
+    Binary,
+    /// Powers of 1000, labelled KB, MB, ... as drive vendors count
+    Decimal,
+}
+
+/// How `format_size_opts` renders a size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Digits after the decimal point; byte counts are always whole
+    pub decimals: usize,
+    /// Right-align the unit to the width of the base's longest one
+    /// ("512   B" in binary) so columns line up
+    pub pad_unit: bool,
+    pub base: SizeBase,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self { decimals: 1, pad_unit: false, base: SizeBase::Binary }
+    }
+}
+
+static SIZE_FORMAT: OnceLock<FormatOptions> = OnceLock::new();
+
+/// Sets how [`format_size`] renders sizes for the rest of the run, e.g. from
+/// `--precision`. Only the first call has an effect.
+pub fn set_size_format(options: FormatOptions) {
+    let _ = SIZE_FORMAT.set(options);
+}
+
+/// Human-readable size, one decimal place unless [`set_size_format`] said otherwise.
 pub fn format_size(bytes: u64) -> String {
-    let units = ["B", "KB", "MB", "GB", "TB"];
+    format_size_opts(bytes, SIZE_FORMAT.get().copied().unwrap_or_default())
+}
+
+/// Human-readable size rendered with `options`: the largest unit that keeps
+/// the number at 1 or more, rounded to `decimals` digits. Plain byte counts
+/// are never given decimals.
+pub fn format_size_opts(bytes: u64, options: FormatOptions) -> String {
+    let (units, step) = match options.base {
+        SizeBase::Binary => (["B", "KiB", "MiB", "GiB", "TiB"], 1024.0),
+        SizeBase::Decimal => (["B", "KB", "MB", "GB", "TB"], 1000.0),
+    };
     let mut size = bytes as f64;
     let mut unit_index = 0;
 
-    while size >= 1024.0 && unit_index < units.len() - 1 {
-        size /= 1024.0;
+    while size >= step && unit_index < units.len() - 1 {
+        size /= step;
         unit_index += 1;
     }
 
+    let width = units.iter().map(|unit| unit.len()).max().unwrap_or(0);
+    let unit = if options.pad_unit { format!("{:>width$}", units[unit_index]) } else { units[unit_index].to_string() };
     if unit_index == 0 {
-        format!("{} {}", size as u64, units[unit_index])
+        format!("{} {}", size as u64, unit)
     } else {
-        format!("{:.1} {}", size, units[unit_index])
+        format!("{:.*} {}", options.decimals, size, unit)
+    }
+}
+
+/// Formats a size without rounding, in the largest unit that divides it
+/// evenly, e.g. "100 MB", "3 GiB" or "1536 B". [`parse_size`] reads the
+/// result back to the same number of bytes, so it suits echoing thresholds the
+/// user typed. Only beyond 2^53 bytes (8 PiB) can that lose precision, since
+/// `parse_size` goes through `f64`; [`format_size`] always rounds.
+pub fn format_size_exact(bytes: u64) -> String {
+    // Largest multiplier first; binary and decimal units interleave
+    let units: [(&str, u64); 8] = [
+        ("TiB", 1024u64.pow(4)),
+        ("TB", 1000u64.pow(4)),
+        ("GiB", 1024u64.pow(3)),
+        ("GB", 1000u64.pow(3)),
+        ("MiB", 1024u64.pow(2)),
+        ("MB", 1000u64.pow(2)),
+        ("KiB", 1024),
+        ("KB", 1000),
+    ];
+    match units.iter().find(|(_, multiplier)| bytes > 0 && bytes.is_multiple_of(*multiplier)) {
+        Some((unit, multiplier)) => format!("{} {}", bytes / multiplier, unit),
+        None => format!("{} B", bytes),
+    }
+}
+
+/// Formats a size as an exact byte count, suitable for summing in scripts.
+pub fn format_size_bytes(bytes: u64) -> String {
+    bytes.to_string()
+}
+
+/// Formats a size either as raw bytes or in the human-readable form.
+pub fn format_size_as(bytes: u64, raw_bytes: bool) -> String {
+    if raw_bytes {
+        format_size_bytes(bytes)
+    } else {
+        format_size(bytes)
     }
 }
 
@@ -25,52 +120,557 @@ pub fn parse_size(size_str: &str) -> Result<u64> {
         return Ok(bytes);
     }
 
-    let (number_part, unit_part) = if size_str.ends_with("TB") {
-        size_str.split_at(size_str.len() - 2)
-    } else if size_str.ends_with("GB") {
-        size_str.split_at(size_str.len() - 2)
-    } else if size_str.ends_with("MB") {
-        size_str.split_at(size_str.len() - 2)
-    } else if size_str.ends_with("KB") {
-        size_str.split_at(size_str.len() - 2)
-    } else if size_str.ends_with("B") {
-        size_str.split_at(size_str.len() - 1)
-    } else {
-        return Err(anyhow!("Invalid size format: {}. Use formats like '100MB', '1GB', etc.", size_str));
+    // Binary suffixes (KiB, MiB, ...) are powers of 1024, decimal suffixes
+    // (KB, MB, ...) are powers of 1000, and the single letters K, M, G and T
+    // are shorthand for the binary ones, like `du` and `sort -h` use them.
+    // Longer suffixes must be checked first.
+    let units: [(&str, u64); 13] = [
+        ("TIB", 1024u64.pow(4)),
+        ("GIB", 1024u64.pow(3)),
+        ("MIB", 1024u64.pow(2)),
+        ("KIB", 1024),
+        ("TB", 1000u64.pow(4)),
+        ("GB", 1000u64.pow(3)),
+        ("MB", 1000u64.pow(2)),
+        ("KB", 1000),
+        ("B", 1),
+        ("T", 1024u64.pow(4)),
+        ("G", 1024u64.pow(3)),
+        ("M", 1024u64.pow(2)),
+        ("K", 1024),
+    ];
+
+    let (number_part, multiplier) = match units
+        .iter()
+        .find(|(suffix, _)| size_str.ends_with(suffix))
+    {
+        Some((suffix, multiplier)) => (&size_str[..size_str.len() - suffix.len()], *multiplier),
+        None => {
+            return Err(anyhow!(
+                "Invalid size format: {}. Use formats like '100MB', '1GiB', etc. (accepted suffixes: B, KB, MB, GB, TB, KiB, MiB, GiB, TiB, K, M, G, T)",
+                size_str
+            ))
+        }
     };
 
-    let number: f64 = number_part.parse()
+    let number: f64 = number_part.trim().parse()
         .map_err(|_| anyhow!("Invalid number in size: {}", number_part))?;
+    if !number.is_finite() || number < 0.0 {
+        return Err(anyhow!("Size must be a number of at least 0, got {}", size_str));
+    }
+
+    // `as` would saturate anything from 2^64 up to u64::MAX
+    let bytes = number * multiplier as f64;
+    if bytes >= u64::MAX as f64 {
+        return Err(anyhow!("Size too large: {}", size_str));
+    }
+    Ok(bytes as u64)
+}
+
+/// Parses durations like `30m`, `24h` or `7d` (units: s, m, h, d, w).
+pub fn parse_duration(duration_str: &str) -> Result<Duration> {
+    let duration_str = duration_str.trim().to_lowercase();
 
-    let multiplier = match unit_part {
-        "B" => 1,
-        "KB" => 1024,
-        "MB" => 1024 * 1024,
-        "GB" => 1024 * 1024 * 1024,
-        "TB" => 1024u64.pow(4),
-        _ => return Err(anyhow!("Invalid size unit: {}", unit_part)),
+    let units: [(&str, u64); 5] = [
+        ("s", 1),
+        ("m", 60),
+        ("h", 60 * 60),
+        ("d", 24 * 60 * 60),
+        ("w", 7 * 24 * 60 * 60),
+    ];
+
+    let (number_part, multiplier) = match units
+        .iter()
+        .find(|(suffix, _)| duration_str.ends_with(suffix))
+    {
+        Some((suffix, multiplier)) => (&duration_str[..duration_str.len() - suffix.len()], *multiplier),
+        None => {
+            return Err(anyhow!(
+                "Invalid duration format: {}. Use formats like '30m', '24h', '7d' (accepted units: s, m, h, d, w)",
+                duration_str
+            ))
+        }
     };
 
-    Ok((number * multiplier as f64) as u64)
+    let number = u64::from_str(number_part.trim())
+        .map_err(|_| anyhow!("Invalid number in duration: {}", number_part))?;
+
+    number
+        .checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow!("Duration too large: {}", duration_str))
+}
+
+/// Parses an age for `--older-than` and `--newer-than`: a duration like
+/// `12h` or `2w` as [`parse_duration`] reads it, or a bare number of days.
+pub fn parse_age(age_str: &str) -> Result<Duration> {
+    match u64::from_str(age_str.trim()) {
+        Ok(days) => parse_duration(&format!("{}d", days)),
+        Err(_) => parse_duration(age_str),
+    }
+}
+
+/// Parses a percentage like `10` or `2.5`; negative and non-finite values
+/// are rejected.
+pub fn parse_percent(percent_str: &str) -> Result<f64> {
+    let percent = f64::from_str(percent_str.trim()).map_err(|_| anyhow!("Invalid percentage: {}", percent_str))?;
+    if !percent.is_finite() || percent < 0.0 {
+        return Err(anyhow!("Percentage must be a number of at least 0, got {}", percent_str));
+    }
+    Ok(percent)
+}
+
+/// Paths given on the command line, or the current directory when there are none.
+pub fn scan_roots(paths: Vec<String>) -> Vec<PathBuf> {
+    if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.into_iter().map(PathBuf::from).collect()
+    }
+}
+
+/// Shortens `path` to at most `max_width` characters for a table column by
+/// replacing part of the middle with "...". The last component is always kept
+/// whole, even when that alone is wider than `max_width`.
+pub fn truncate_path(path: &Path, max_width: usize) -> String {
+    let text = path.to_string_lossy();
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= max_width {
+        return text.into_owned();
+    }
+
+    let name_len = path.file_name().map_or(0, |name| name.to_string_lossy().chars().count());
+    let budget = max_width.saturating_sub(3);
+    if name_len + 1 > budget {
+        return format!("...{}", chars[chars.len() - (name_len + 1).min(chars.len())..].iter().collect::<String>());
+    }
+    // Up to a third of the room goes to the start of the path, the rest to
+    // the end, which always covers the separator and the last component
+    let head_len = (budget - name_len - 1).min(budget / 3);
+    let tail_len = budget - head_len;
+    let head: String = chars[..head_len].iter().collect();
+    let tail: String = chars[chars.len() - tail_len..].iter().collect();
+    format!("{}...{}", head, tail)
+}
+
+/// Comma-separated list of `roots` for headers like "Searching in: ...".
+pub fn describe_roots(roots: &[PathBuf]) -> String {
+    roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Flag set by the Ctrl-C handler; scans started from the command line use it
+/// as their cancel flag so an interrupt stops the walk instead of waiting it out.
+pub fn interrupt_flag() -> Arc<AtomicBool> {
+    static INTERRUPTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    INTERRUPTED.get_or_init(Arc::default).clone()
+}
+
+/// Warns that a scan's results are incomplete because some entries could not be read.
+pub fn note_skipped(skipped: &Skipped) {
+    let count = skipped.count();
+    if count > 0 {
+        warn!("⚠️  Note: {} entries skipped due to permission errors", count);
+    }
+}
+
+/// With `-v`, reports how many entries a scan visited since `started` and how fast.
+pub fn note_throughput(scanned: &AtomicUsize, started: Instant) {
+    debug!("{}", format_throughput(scanned.load(Ordering::Relaxed), started.elapsed()));
+}
+
+/// With `-v`, reports how many entries a scan visited against how many of
+/// them it reported, to gauge its coverage.
+pub fn note_coverage(scanned: &AtomicUsize, matched: usize) {
+    debug!("Visited {} entries, matched {}", format_count(scanned.load(Ordering::Relaxed)), format_count(matched));
+}
+
+/// `count` with thousands separators, e.g. "124,301".
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// "Scanned N items in X.Xs (Y items/s)".
+fn format_throughput(count: usize, elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64();
+    let rate = if seconds > 0.0 { count as f64 / seconds } else { 0.0 };
+    format!("Scanned {} items in {:.1}s ({:.0} items/s)", count, seconds, rate)
+}
+
+/// Asks the user to confirm a destructive action.
+///
+/// With `assume_yes` the prompt is skipped entirely. Without it, a missing
+/// terminal on stdin is an error instead of a prompt that can never be answered.
+pub fn confirm(prompt: String, assume_yes: bool) -> Result<bool> {
+    confirm_with(assume_yes, io::stdin().is_terminal(), || {
+        Ok(Confirm::new().with_prompt(prompt).interact()?)
+    })
+}
+
+/// Word that must be typed to confirm a removal above `--confirm-threshold`.
+const CONFIRM_WORD: &str = "yes";
+
+/// How a bulk removal is confirmed.
+#[derive(Debug, PartialEq)]
+enum PromptStyle {
+    /// A plain y/N question
+    YesNo,
+    /// The user has to type [`CONFIRM_WORD`]
+    TypeWord,
+}
+
+/// Removing more than `threshold` bytes needs the stronger prompt.
+fn prompt_style(total: u64, threshold: u64) -> PromptStyle {
+    if total > threshold {
+        PromptStyle::TypeWord
+    } else {
+        PromptStyle::YesNo
+    }
+}
+
+/// Like [`confirm`], but when `total` exceeds `threshold` the user has to
+/// type "yes" instead of answering y/n. `assume_yes` still skips the prompt.
+pub fn confirm_removal(prompt: String, total: u64, threshold: u64, assume_yes: bool) -> Result<bool> {
+    if prompt_style(total, threshold) == PromptStyle::YesNo {
+        return confirm(prompt, assume_yes);
+    }
+    confirm_with(assume_yes, io::stdin().is_terminal(), || {
+        let answer: String = Input::new()
+            .with_prompt(format!(
+                "{} This is more than {}; type \"{}\" to continue",
+                prompt,
+                format_size_exact(threshold),
+                CONFIRM_WORD
+            ))
+            .allow_empty(true)
+            .interact_text()?;
+        Ok(answer.trim() == CONFIRM_WORD)
+    })
+}
+
+fn confirm_with(assume_yes: bool, interactive: bool, ask: impl FnOnce() -> Result<bool>) -> Result<bool> {
+    if assume_yes {
+        return Ok(true);
+    }
+    if !interactive {
+        return Err(anyhow!("stdin is not a terminal; pass --yes to proceed without confirmation"));
+    }
+    ask()
+}
+
+/// Asks about each item in turn (`--interactive`) and keeps the ones the user
+/// confirmed. With `assume_yes` every item is kept without asking.
+pub fn confirm_each(items: Vec<FileItem>, assume_yes: bool) -> Result<Vec<FileItem>> {
+    if assume_yes {
+        return Ok(items);
+    }
+    select_confirmed(items, |item| {
+        confirm(format!("Remove {} ({})?", item.path.display(), format_size(item.size)), false)
+    })
+}
+
+fn select_confirmed(items: Vec<FileItem>, mut ask: impl FnMut(&FileItem) -> Result<bool>) -> Result<Vec<FileItem>> {
+    let mut confirmed = Vec::new();
+    for item in items {
+        if ask(&item)? {
+            confirmed.push(item);
+        }
+    }
+    Ok(confirmed)
+}
+
+/// How long ago something happened, like "3 months ago". Months are
+/// counted as 30 days and years as 365.
+pub fn format_age(age: Duration) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    let secs = age.as_secs();
+    let (count, unit) = match secs {
+        s if s < MINUTE => return "just now".to_string(),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < 30 * DAY => (s / DAY, "day"),
+        s if s < 365 * DAY => (s / (30 * DAY), "month"),
+        s => (s / (365 * DAY), "year"),
+    };
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// [`format_age`] of a timestamp; times in the future count as just now.
+pub fn format_age_since(time: SystemTime) -> String {
+    format_age(SystemTime::now().duration_since(time).unwrap_or_default())
+}
+
+/// Desktop platforms, each with its own way of opening a folder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Platform {
+    MacOs,
+    Windows,
+    Linux,
+}
+
+impl Platform {
+    pub fn current() -> Platform {
+        if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(windows) {
+            Platform::Windows
+        } else {
+            Platform::Linux
+        }
+    }
+
+    /// Program and arguments that show `dir` in the file manager.
+    fn opener(self, dir: &Path) -> (&'static str, Vec<OsString>) {
+        let program = match self {
+            Platform::MacOs => "open",
+            Platform::Windows => "explorer",
+            Platform::Linux => "xdg-open",
+        };
+        (program, vec![dir.as_os_str().to_owned()])
+    }
+}
+
+/// Directory containing `path`; `.` for a bare relative name.
+pub fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Opens `dir` in the system file manager without waiting for it.
+pub fn open_in_file_manager(dir: &Path) -> Result<()> {
+    let (program, args) = Platform::current().opener(dir);
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run {} for {}", program, dir.display()))?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_throughput() {
+        assert_eq!(format_throughput(12_000, Duration::from_millis(2500)), "Scanned 12000 items in 2.5s (4800 items/s)");
+        assert_eq!(format_throughput(7, Duration::from_secs(3)), "Scanned 7 items in 3.0s (2 items/s)");
+        assert_eq!(format_throughput(0, Duration::ZERO), "Scanned 0 items in 0.0s (0 items/s)");
+        assert_eq!(format_count(124_301), "124,301");
+        assert_eq!(format_count(1_000_000), "1,000,000");
+        assert_eq!(format_count(999), "999");
+    }
+
+    #[test]
+    fn test_truncate_path_keeps_basename() {
+        let path = Path::new("/home/user/projects/some/deep/dir/file.txt");
+        assert_eq!(truncate_path(path, 60), "/home/user/projects/some/deep/dir/file.txt");
+        assert_eq!(truncate_path(path, 20), "/home...dir/file.txt");
+        assert_eq!(truncate_path(path, 8), ".../file.txt");
+    }
+
+    #[test]
+    fn test_truncate_path_multibyte() {
+        let path = Path::new("/données/équipe/très_long_répertoire_de_vidéos/ファイル名.mkv");
+        for width in 5..60 {
+            let truncated = truncate_path(path, width);
+            assert!(truncated.ends_with("/ファイル名.mkv"), "{}", truncated);
+            assert!(truncated.chars().count() <= width.max(13), "{}", truncated);
+        }
+        assert_eq!(truncate_path(path, 30), "/données/...e_vidéos/ファイル名.mkv");
+    }
+
+    #[test]
+    fn test_format_age() {
+        let hours = |n: u64| Duration::from_secs(n * 3600);
+        assert_eq!(format_age(Duration::from_secs(42)), "just now");
+        assert_eq!(format_age(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(format_age(hours(5)), "5 hours ago");
+        assert_eq!(format_age(hours(24)), "1 day ago");
+        assert_eq!(format_age(hours(24 * 95)), "3 months ago");
+        assert_eq!(format_age(hours(24 * 800)), "2 years ago");
+    }
+
+    #[test]
+    fn test_opener_per_platform() {
+        let dir = Path::new("/data/videos");
+        assert_eq!(Platform::MacOs.opener(dir), ("open", vec![OsString::from("/data/videos")]));
+        assert_eq!(Platform::Linux.opener(dir), ("xdg-open", vec![OsString::from("/data/videos")]));
+        assert_eq!(Platform::Windows.opener(dir), ("explorer", vec![OsString::from("/data/videos")]));
+    }
+
+    #[test]
+    fn test_prompt_style_above_threshold() {
+        let threshold = 5_000_000_000;
+        assert_eq!(prompt_style(1_000_000, threshold), PromptStyle::YesNo);
+        assert_eq!(prompt_style(threshold, threshold), PromptStyle::YesNo);
+        assert_eq!(prompt_style(threshold + 1, threshold), PromptStyle::TypeWord);
+        assert_eq!(prompt_style(1, 0), PromptStyle::TypeWord);
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1048576), "1.0 MB");
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(1048576), "1.0 MiB");
+    }
+
+    #[test]
+    fn test_format_size_two_decimals() {
+        let options = FormatOptions { decimals: 2, ..FormatOptions::default() };
+        assert_eq!(format_size_opts(1_116_691_497, options), "1.04 GiB");
+        assert_eq!(format_size_opts(512, options), "512 B");
+        assert_eq!(format_size_opts(512, FormatOptions { pad_unit: true, ..options }), "512   B");
+    }
+
+    #[test]
+    fn test_format_size_decimal_base() {
+        let options = FormatOptions { decimals: 2, pad_unit: false, base: SizeBase::Decimal };
+        assert_eq!(format_size_opts(1_000_000_000, options), "1.00 GB");
+        assert_eq!(format_size_opts(1_500, options), "1.50 KB");
+        assert_eq!(format_size_opts(512, FormatOptions { pad_unit: true, ..options }), "512  B");
+    }
+
+    #[test]
+    fn test_format_size_raw_bytes() {
+        assert_eq!(format_size_bytes(1536), "1536");
+        assert_eq!(format_size_as(1536, true), "1536");
+        assert_eq!(format_size_as(1536, false), "1.5 KiB");
     }
 
     #[test]
     fn test_parse_size() {
         assert_eq!(parse_size("1024").unwrap(), 1024);
-        assert_eq!(parse_size("1KB").unwrap(), 1024);
-        assert_eq!(parse_size("1.5MB").unwrap(), 1572864);
-        assert_eq!(parse_size("2GB").unwrap(), 2147483648);
+        assert_eq!(parse_size("1KB").unwrap(), 1000);
+        assert_eq!(parse_size("1.5MB").unwrap(), 1500000);
+        assert_eq!(parse_size("2GB").unwrap(), 2000000000);
+    }
+
+    #[test]
+    fn test_format_size_exact_round_trips() {
+        assert_eq!(format_size_exact(100_000_000), "100 MB");
+        assert_eq!(format_size_exact(1536), "1536 B");
+        assert_eq!(format_size_exact(3 * 1024u64.pow(3)), "3 GiB");
+        assert_eq!(format_size_exact(0), "0 B");
+
+        let units = [1, 1000, 1024, 1000u64.pow(2), 1024u64.pow(2), 1000u64.pow(3), 1024u64.pow(3), 1000u64.pow(4), 1024u64.pow(4)];
+        for unit in units {
+            for count in (1..=2000).chain([4095, 65_537, 1_000_003]) {
+                let bytes = count * unit;
+                assert_eq!(parse_size(&format_size_exact(bytes)).unwrap(), bytes, "{} = {}", bytes, format_size_exact(bytes));
+            }
+        }
+        // Sizes that are no multiple of a unit fall back to bytes
+        let mut bytes = 1u64;
+        for _ in 0..500 {
+            bytes = bytes.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1) % (1 << 50);
+            assert_eq!(parse_size(&format_size_exact(bytes)).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_parse_size_spaces_and_short_units() {
+        assert_eq!(parse_size("100 MB").unwrap(), 100_000_000);
+        assert_eq!(parse_size(" 100 mb ").unwrap(), 100_000_000);
+        assert_eq!(parse_size("100m").unwrap(), 100 * 1024 * 1024);
+        assert_eq!(parse_size("1 g").unwrap(), 1024 * 1024 * 1024);
+        assert_eq!(parse_size("2K").unwrap(), 2048);
+        assert!(parse_size("abc").is_err());
+        assert!(parse_size("1 0MB").is_err());
+        assert!(parse_size("MB").is_err());
+    }
+
+    #[test]
+    fn test_parse_size_binary_units() {
+        assert_eq!(parse_size("1KiB").unwrap(), 1024);
+        assert_eq!(parse_size("1.5MiB").unwrap(), 1572864);
+        assert_eq!(parse_size("2GiB").unwrap(), 2147483648);
+        assert_eq!(parse_size("1TiB").unwrap(), 1024u64.pow(4));
+        assert_eq!(parse_size("512B").unwrap(), 512);
+    }
+
+    #[test]
+    fn test_parse_size_invalid() {
+        let err = parse_size("10XQ").unwrap_err().to_string();
+        assert!(err.contains("KiB"));
+        assert!(parse_size("abcMB").is_err());
+        assert!(parse_size("-5MB").is_err());
+        assert!(parse_size("NaNMB").is_err());
+        assert!(parse_size("infGB").is_err());
+        assert!(parse_size("1e30TB").is_err());
+        assert_eq!(parse_size("16777215TiB").unwrap(), 16_777_215 * 1024u64.pow(4));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("24h").unwrap(), Duration::from_secs(24 * 60 * 60));
+        assert_eq!(parse_duration("7d").unwrap(), Duration::from_secs(7 * 24 * 60 * 60));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(30 * 60));
+        assert!(parse_duration("tomorrow").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("18446744073709551615w").is_err());
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("7").unwrap(), Duration::from_secs(7 * 24 * 60 * 60));
+        assert_eq!(parse_age("12h").unwrap(), Duration::from_secs(12 * 60 * 60));
+        assert_eq!(parse_age("2w").unwrap(), parse_age("14").unwrap());
+        assert!(parse_age("-7").is_err());
+        assert!(parse_age("18446744073709551615").is_err());
+    }
+
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(parse_percent("10").unwrap(), 10.0);
+        assert_eq!(parse_percent("0").unwrap(), 0.0);
+        assert_eq!(parse_percent("2.5").unwrap(), 2.5);
+        assert!(parse_percent("-5").is_err());
+        assert!(parse_percent("NaN").is_err());
+        assert!(parse_percent("inf").is_err());
+        assert!(parse_percent("ten").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_confirm_assume_yes_skips_prompt() {
+        let result = confirm_with(true, false, || panic!("prompt must not be shown"));
+        assert!(result.unwrap());
+    }
+
+    #[test]
+    fn test_confirm_without_tty_fails_fast() {
+        let result = confirm_with(false, false, || panic!("prompt must not be shown"));
+        assert!(result.unwrap_err().to_string().contains("--yes"));
+        assert!(!confirm_with(false, true, || Ok(false)).unwrap());
+    }
+
+    #[test]
+    fn test_select_confirmed_keeps_approved_items() {
+        let items: Vec<FileItem> = (0..5)
+            .map(|i| FileItem::test(format!("item{}", i), 0, Some(0)))
+            .collect();
+        let mut asked = 0;
+        let kept = select_confirmed(items, |_| {
+            asked += 1;
+            Ok(asked % 2 == 1)
+        }).unwrap();
+        assert_eq!(asked, 5);
+        let names: Vec<_> = kept.iter().map(|item| item.path.to_string_lossy().into_owned()).collect();
+        assert_eq!(names, vec!["item0", "item2", "item4"]);
+    }
+}